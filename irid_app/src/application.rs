@@ -1,13 +1,15 @@
 //= USES ===========================================================================================
 
-use std::{fmt::Debug, path::PathBuf};
+use std::{collections::HashMap, fmt::Debug, path::PathBuf};
 
 use thiserror::Error;
 
 use irid_assets_interface::Vertex;
-use irid_renderer::{PerspectiveCamera, Renderer, RendererConfig, RendererError};
+use irid_renderer::{
+    PerspectiveCamera, RenderLayers, Renderer, RendererConfig, RendererError, Viewport,
+};
 
-use crate::{IridWindowConfig, Listener};
+use crate::{IridWindow, IridWindowConfig, Listener};
 
 //= ERRORS =========================================================================================
 
@@ -33,6 +35,7 @@ pub enum ApplicationError {
 pub struct ApplicationConfig<'a, L: Listener, V: Vertex> {
     listener: L,
     window_config: Option<IridWindowConfig>,
+    screenshot_key: Option<winit::event::VirtualKeyCode>,
     renderer_config: Option<
         RendererConfig<'a, PerspectiveCamera, &'a str, &'a str, V, u16>,
     >, // TODO: to refact
@@ -50,6 +53,7 @@ where
         Self {
             listener,
             window_config: None,
+            screenshot_key: None,
             renderer_config: None,
         }
     }
@@ -70,6 +74,17 @@ where
         self
     }
 
+    /// A key that, when pressed, saves the current frame as a PNG file in the working
+    /// directory. Disabled by default.
+    #[inline]
+    pub fn with_screenshot_key<K: Into<Option<winit::event::VirtualKeyCode>>>(
+        mut self,
+        screenshot_key: K,
+    ) -> Self {
+        self.screenshot_key = screenshot_key.into();
+        self
+    }
+
     /*
     ///
     #[inline]
@@ -108,10 +123,16 @@ where
 
     /// Build a new [Application] with given values.
     pub fn build(self) -> Application<'a, L, V> {
+        let mut renderer_config = self.renderer_config.unwrap_or_else(RendererConfig::new);
+        if self.screenshot_key.is_some() {
+            renderer_config = renderer_config.with_frame_capture(true);
+        }
+
         Application {
             listener: self.listener,
             window_config: self.window_config.unwrap_or_else(IridWindowConfig::new),
-            renderer_config: self.renderer_config.unwrap_or_else(RendererConfig::new),
+            screenshot_key: self.screenshot_key,
+            renderer_config,
         }
     }
 }
@@ -123,6 +144,7 @@ where
 pub struct Application<'a, L: Listener, V: Vertex> {
     listener: L,
     window_config: IridWindowConfig,
+    screenshot_key: Option<winit::event::VirtualKeyCode>,
     renderer_config:
         RendererConfig<'a, PerspectiveCamera, &'a str, &'a str, V, u16>, // TODO: to refact
 }
@@ -154,10 +176,20 @@ where
         // in this way we avoid a slight window's visible/invisible toggling effect
         window.conclude_visibility_delay();
 
-        let renderer = &mut self.renderer_config.build(&window.expose_inner_window())?;
+        // The windows opened by the listener, their surfaces are owned by the renderer: they
+        // are declared before it, like the main window, to be dropped after their surfaces
+        let main_window_id = window.id();
+        let mut opened_windows = HashMap::<winit::window::WindowId, IridWindow>::new();
+        let windows = &mut opened_windows;
+
+        let mut renderer = self.renderer_config.build(&window.expose_inner_window())?;
+        let renderer = &mut renderer;
+
+        // Set by the screenshot key, the other captures are left to the listener
+        let mut is_screenshot_requested = false;
 
         use winit::platform::run_return::EventLoopExtRunReturn;
-        event_loop.run_return(move |event, _, control_flow| {
+        event_loop.run_return(move |event, event_loop_target, control_flow| {
             match event {
                 winit::event::Event::NewEvents(start_cause) => {
                     self.on_new_events(start_cause);
@@ -167,43 +199,49 @@ where
                     event: window_event,
                     window_id,
                 } => {
-                    // TODO: add a multi-monitor support
-                    if window_id == window.id() {
+                    let is_main_window = window_id == main_window_id;
+                    if is_main_window || windows.contains_key(&window_id) {
+                        // The input consumed by the GUI must not reach the game logic, the
+                        // GUI is drawn on the main window only
+                        if is_main_window && renderer.handle_gui_event(&window_event) {
+                            return;
+                        }
+
                         match window_event {
                             winit::event::WindowEvent::Resized(physical_size) => {
-                                self.on_window_resize(renderer, physical_size);
+                                self.on_window_resize(window_id, renderer, physical_size);
                             }
 
                             winit::event::WindowEvent::Moved(physical_position) => {
-                                self.on_window_move(physical_position);
+                                self.on_window_move(window_id, physical_position);
                             }
 
                             winit::event::WindowEvent::CloseRequested => {
-                                self.on_window_close(control_flow);
+                                self.on_window_close(window_id, control_flow, renderer, windows);
                             }
 
                             winit::event::WindowEvent::Destroyed => {
-                                self.on_window_destroy();
+                                self.on_window_destroy(window_id);
                             }
 
                             winit::event::WindowEvent::DroppedFile(path) => {
-                                self.on_window_drop_file(path);
+                                self.on_window_drop_file(window_id, path);
                             }
 
                             winit::event::WindowEvent::HoveredFile(path) => {
-                                self.on_window_hover_file(path);
+                                self.on_window_hover_file(window_id, path);
                             }
 
                             winit::event::WindowEvent::HoveredFileCancelled => {
-                                self.on_window_hover_file_cancelled();
+                                self.on_window_hover_file_cancelled(window_id);
                             }
 
                             winit::event::WindowEvent::ReceivedCharacter(c) => {
-                                self.on_window_receive_character(c);
+                                self.on_window_receive_character(window_id, c);
                             }
 
                             winit::event::WindowEvent::Focused(gained_focus) => {
-                                self.on_window_focus(gained_focus);
+                                self.on_window_focus(window_id, gained_focus);
                             }
 
                             winit::event::WindowEvent::KeyboardInput {
@@ -213,16 +251,18 @@ where
                             } => {
                                 if !is_synthetic && input.virtual_keycode.is_some() {
                                     self.on_window_keyboard_input(
+                                        window_id,
                                         control_flow,
                                         device_id,
                                         renderer,
                                         input,
+                                        &mut is_screenshot_requested,
                                     );
                                 }
                             }
 
                             winit::event::WindowEvent::ModifiersChanged(state) => {
-                                self.on_window_modifiers_change(state);
+                                self.on_window_modifiers_change(window_id, state);
                             }
 
                             winit::event::WindowEvent::CursorMoved {
@@ -230,15 +270,15 @@ where
                                 position,
                                 ..
                             } => {
-                                self.on_window_cursor_move(device_id, position);
+                                self.on_window_cursor_move(window_id, device_id, position);
                             }
 
                             winit::event::WindowEvent::CursorEntered { device_id } => {
-                                self.on_window_cursor_enter(device_id);
+                                self.on_window_cursor_enter(window_id, device_id);
                             }
 
                             winit::event::WindowEvent::CursorLeft { device_id } => {
-                                self.on_window_cursor_left(device_id);
+                                self.on_window_cursor_left(window_id, device_id);
                             }
 
                             winit::event::WindowEvent::MouseWheel {
@@ -247,7 +287,7 @@ where
                                 phase,
                                 ..
                             } => {
-                                self.on_window_mouse_wheel(device_id, delta, phase);
+                                self.on_window_mouse_wheel(window_id, device_id, delta, phase);
                             }
 
                             winit::event::WindowEvent::MouseInput {
@@ -256,7 +296,7 @@ where
                                 button,
                                 ..
                             } => {
-                                self.on_window_mouse_input(device_id, state, button);
+                                self.on_window_mouse_input(window_id, device_id, state, button);
                             }
 
                             winit::event::WindowEvent::TouchpadPressure {
@@ -264,7 +304,9 @@ where
                                 pressure,
                                 stage,
                             } => {
-                                self.on_window_touchpad_pressure(device_id, pressure, stage);
+                                self.on_window_touchpad_pressure(
+                                    window_id, device_id, pressure, stage,
+                                );
                             }
 
                             winit::event::WindowEvent::AxisMotion {
@@ -272,11 +314,11 @@ where
                                 axis,
                                 value,
                             } => {
-                                self.on_window_axis_motion(device_id, axis, value);
+                                self.on_window_axis_motion(window_id, device_id, axis, value);
                             }
 
                             winit::event::WindowEvent::Touch(touch) => {
-                                self.on_window_touch(touch);
+                                self.on_window_touch(window_id, touch);
                             }
 
                             // The window's scale factor has changed.
@@ -284,11 +326,16 @@ where
                                 scale_factor,
                                 new_inner_size,
                             } => {
-                                self.on_window_scale_change(renderer, scale_factor, new_inner_size);
+                                self.on_window_scale_change(
+                                    window_id,
+                                    renderer,
+                                    scale_factor,
+                                    new_inner_size,
+                                );
                             }
 
                             winit::event::WindowEvent::ThemeChanged(theme) => {
-                                self.on_window_theme_change(theme);
+                                self.on_window_theme_change(window_id, theme);
                             }
                         }
                     }
@@ -314,7 +361,15 @@ where
                 }
 
                 winit::event::Event::MainEventsCleared => {
-                    self.on_redraw(renderer, control_flow);
+                    self.on_open_windows(event_loop_target, renderer, windows);
+                    self.on_update(renderer);
+                    self.on_gui(renderer);
+                    self.on_redraw(
+                        renderer,
+                        windows,
+                        &mut is_screenshot_requested,
+                        control_flow,
+                    );
                 }
 
                 winit::event::Event::RedrawRequested(window_id) => {
@@ -352,17 +407,35 @@ where
         let _use_default_behaviour = self.listener.on_resume();
     }
 
+    fn on_update(&self, renderer: &Renderer<PerspectiveCamera>) {
+        let _use_default_behaviour = self.listener.on_update(renderer.timestep());
+    }
+
+    fn on_gui(&self, renderer: &mut Renderer<PerspectiveCamera>) {
+        renderer.run_gui(|ctx| {
+            let _use_default_behaviour = self.listener.on_gui(ctx);
+        });
+    }
+
     // This method is probably one of the few that must always be inline.
     #[inline(always)]
     fn on_redraw(
         &self,
         renderer: &mut Renderer<PerspectiveCamera>,
+        windows: &HashMap<winit::window::WindowId, IridWindow>,
+        is_screenshot_requested: &mut bool,
         control_flow: &mut winit::event_loop::ControlFlow,
     ) {
         let use_default_behaviour = self.listener.on_redraw();
         if use_default_behaviour {
             match renderer.redraw() {
-                Ok(_) => {}
+                Ok(_) => {
+                    if *is_screenshot_requested {
+                        *is_screenshot_requested = !save_screenshot(renderer);
+                    }
+                    let _use_default_behaviour =
+                        self.listener.on_frame_stats(renderer.frame_stats());
+                }
                 Err(error) => match error {
                     // These errors should be resolved by the next frame
                     wgpu::SurfaceError::Timeout | wgpu::SurfaceError::Outdated => {
@@ -370,15 +443,100 @@ where
                         log::error!("{:?}", error)
                     }
 
-                    // Recreate the swap chain if lost
-                    wgpu::SurfaceError::Lost => renderer.refresh_current_size(),
+                    // Recreate the swap chain if lost, if it is lost again the device is lost
+                    wgpu::SurfaceError::Lost => {
+                        renderer.refresh_current_size();
+                        if let Err(wgpu::SurfaceError::Lost) = renderer.redraw() {
+                            self.recover_renderer(renderer, control_flow);
+                        }
+                    }
 
-                    // The system is out of memory, we should probably quit
+                    // The device is out of memory, its resources are recreated from scratch
                     wgpu::SurfaceError::OutOfMemory => {
-                        *control_flow = winit::event_loop::ControlFlow::Exit
+                        self.recover_renderer(renderer, control_flow)
                     }
                 },
             }
+
+            // By default the additional windows show the scene seen by the renderer camera
+            let main_camera = renderer.camera().cloned();
+            for (&window_id, window) in windows.iter() {
+                if let Some(viewport) = renderer.window_viewport_mut(window_id) {
+                    let window_size = window.inner_size();
+                    let aspect = viewport.calc_aspect_ratio(window_size);
+                    let camera = viewport.camera_mut();
+                    let use_default_behaviour = self.listener.on_window_camera(window_id, camera);
+                    if let (true, Some(main_camera)) = (use_default_behaviour, main_camera.as_ref())
+                    {
+                        *camera = main_camera.clone();
+                        if window_size.width > 0 && window_size.height > 0 {
+                            camera.set_aspect(aspect);
+                        }
+                    }
+                }
+                match renderer.redraw_window(window_id) {
+                    Ok(_) => (),
+                    Err(wgpu::SurfaceError::OutOfMemory) => {
+                        self.recover_renderer(renderer, control_flow)
+                    }
+                    Err(error) => log::error!("{:?}", error),
+                }
+            }
+        }
+    }
+
+    // Recreate the device and the GPU resources, the application exits if it is not possible.
+    fn recover_renderer(
+        &self,
+        renderer: &mut Renderer<PerspectiveCamera>,
+        control_flow: &mut winit::event_loop::ControlFlow,
+    ) {
+        match renderer.recover() {
+            Ok(_) => {
+                let _use_default_behaviour = self.listener.on_gpu_resources_rebuilt(renderer);
+            }
+            Err(error) => {
+                log::error!("Unable to recover the renderer: {}", error);
+                *control_flow = winit::event_loop::ControlFlow::Exit
+            }
+        }
+    }
+
+    fn on_open_windows(
+        &self,
+        event_loop: &winit::event_loop::EventLoopWindowTarget<()>,
+        renderer: &mut Renderer<PerspectiveCamera>,
+        windows: &mut HashMap<winit::window::WindowId, IridWindow>,
+    ) {
+        for window_config in self.listener.on_open_windows() {
+            let camera = match renderer.camera() {
+                Some(camera) => camera.clone(),
+                None => {
+                    log::error!("The windows cannot be opened without the renderer camera");
+                    return;
+                }
+            };
+
+            let mut window = match window_config.build(event_loop) {
+                Ok(window) => window,
+                Err(error) => {
+                    log::error!("Unable to open the window: {}", error);
+                    continue;
+                }
+            };
+
+            // The sprites and the screen texts are laid out for the main window
+            let layers = RenderLayers::ALL.without(RenderLayers::SPRITES | RenderLayers::TEXTS);
+            let viewport = Viewport::new(camera).with_layers(layers);
+            if let Err(error) = renderer.add_window(window.expose_inner_window(), viewport) {
+                log::error!("Unable to draw on the window: {}", error);
+                continue;
+            }
+            window.conclude_visibility_delay();
+
+            let window_id = window.id();
+            let _ = windows.insert(window_id, window);
+            let _use_default_behaviour = self.listener.on_window_open(window_id);
         }
     }
 
@@ -398,48 +556,65 @@ where
 
     fn on_window_resize(
         &self,
+        window_id: winit::window::WindowId,
         renderer: &mut Renderer<PerspectiveCamera>,
         physical_size: winit::dpi::PhysicalSize<u32>,
     ) {
-        let use_default_behaviour = self.listener.on_window_resize(physical_size);
+        let use_default_behaviour = self.listener.on_window_resize(window_id, physical_size);
         if use_default_behaviour {
-            renderer.resize(physical_size);
+            resize_renderer(renderer, window_id, physical_size);
         }
     }
 
-    fn on_window_move(&self, physical_position: winit::dpi::PhysicalPosition<i32>) {
-        let _use_default_behaviour = self.listener.on_window_move(physical_position);
+    fn on_window_move(
+        &self,
+        window_id: winit::window::WindowId,
+        physical_position: winit::dpi::PhysicalPosition<i32>,
+    ) {
+        let _use_default_behaviour = self.listener.on_window_move(window_id, physical_position);
     }
 
-    fn on_window_close(&self, control_flow: &mut winit::event_loop::ControlFlow) {
-        let use_default_behaviour = self.listener.on_window_close();
+    fn on_window_close(
+        &self,
+        window_id: winit::window::WindowId,
+        control_flow: &mut winit::event_loop::ControlFlow,
+        renderer: &mut Renderer<PerspectiveCamera>,
+        windows: &mut HashMap<winit::window::WindowId, IridWindow>,
+    ) {
+        let use_default_behaviour = self.listener.on_window_close(window_id);
         if use_default_behaviour {
-            *control_flow = winit::event_loop::ControlFlow::Exit;
+            // The surface is dropped before its window, closing the main window exits
+            if windows.contains_key(&window_id) {
+                let _ = renderer.remove_window(window_id);
+                let _ = windows.remove(&window_id);
+            } else {
+                *control_flow = winit::event_loop::ControlFlow::Exit;
+            }
         }
     }
 
-    fn on_window_destroy(&self) {
-        let _use_default_behaviour = self.listener.on_window_destroy();
+    fn on_window_destroy(&self, window_id: winit::window::WindowId) {
+        let _use_default_behaviour = self.listener.on_window_destroy(window_id);
     }
 
-    fn on_window_drop_file(&self, path: PathBuf) {
-        let _use_default_behaviour = self.listener.on_window_drop_file(path);
+    fn on_window_drop_file(&self, window_id: winit::window::WindowId, path: PathBuf) {
+        let _use_default_behaviour = self.listener.on_window_drop_file(window_id, path);
     }
 
-    fn on_window_hover_file(&self, path: PathBuf) {
-        let _use_default_behaviour = self.listener.on_window_hover_file(path);
+    fn on_window_hover_file(&self, window_id: winit::window::WindowId, path: PathBuf) {
+        let _use_default_behaviour = self.listener.on_window_hover_file(window_id, path);
     }
 
-    fn on_window_hover_file_cancelled(&self) {
-        let _use_default_behaviour = self.listener.on_window_hover_file_cancelled();
+    fn on_window_hover_file_cancelled(&self, window_id: winit::window::WindowId) {
+        let _use_default_behaviour = self.listener.on_window_hover_file_cancelled(window_id);
     }
 
-    fn on_window_receive_character(&self, c: char) {
-        let _use_default_behaviour = self.listener.on_window_receive_character(c);
+    fn on_window_receive_character(&self, window_id: winit::window::WindowId, c: char) {
+        let _use_default_behaviour = self.listener.on_window_receive_character(window_id, c);
     }
 
-    fn on_window_focus(&self, gained_focus: bool) {
-        let _use_default_behaviour = self.listener.on_window_focus(gained_focus);
+    fn on_window_focus(&self, window_id: winit::window::WindowId, gained_focus: bool) {
+        let _use_default_behaviour = self.listener.on_window_focus(window_id, gained_focus);
     }
 
     // Triggered then an user press a key upon this active window.
@@ -450,13 +625,16 @@ where
     // input.virtual_keycode KeyboardInput events equals to None.
     fn on_window_keyboard_input(
         &self,
+        window_id: winit::window::WindowId,
         control_flow: &mut winit::event_loop::ControlFlow,
         device_id: winit::event::DeviceId,
         renderer: &mut Renderer<PerspectiveCamera>,
         input: winit::event::KeyboardInput,
+        is_screenshot_requested: &mut bool,
     ) {
         // First call a generic method to manage the key events
         let use_default_behaviour = self.listener.on_window_keyboard_input(
+            window_id,
             device_id,
             input.state,
             input.virtual_keycode.unwrap(),
@@ -467,6 +645,13 @@ where
             // Check the camera controller
             let _ = renderer.process_camera_events(input);
 
+            if input.state == winit::event::ElementState::Pressed
+                && self.screenshot_key.is_some()
+                && input.virtual_keycode == self.screenshot_key
+            {
+                *is_screenshot_requested = request_screenshot(renderer);
+            }
+
             if let winit::event::KeyboardInput {
                 state: winit::event::ElementState::Pressed,
                 virtual_keycode: Some(winit::event::VirtualKeyCode::Escape),
@@ -478,84 +663,161 @@ where
         }
     }
 
-    fn on_window_modifiers_change(&self, state: winit::event::ModifiersState) {
-        let _use_default_behaviour = self.listener.on_window_modifiers_change(state);
+    fn on_window_modifiers_change(
+        &self,
+        window_id: winit::window::WindowId,
+        state: winit::event::ModifiersState,
+    ) {
+        let _use_default_behaviour = self.listener.on_window_modifiers_change(window_id, state);
     }
 
     fn on_window_cursor_move(
         &self,
+        window_id: winit::window::WindowId,
         device_id: winit::event::DeviceId,
         position: winit::dpi::PhysicalPosition<f64>,
     ) {
-        let _use_default_behaviour = self.listener.on_window_cursor_move(device_id, position);
+        let _use_default_behaviour = self
+            .listener
+            .on_window_cursor_move(window_id, device_id, position);
     }
 
-    fn on_window_cursor_enter(&self, device_id: winit::event::DeviceId) {
-        let _use_default_behaviour = self.listener.on_window_cursor_enter(device_id);
+    fn on_window_cursor_enter(
+        &self,
+        window_id: winit::window::WindowId,
+        device_id: winit::event::DeviceId,
+    ) {
+        let _use_default_behaviour = self.listener.on_window_cursor_enter(window_id, device_id);
     }
 
-    fn on_window_cursor_left(&self, device_id: winit::event::DeviceId) {
-        let _use_default_behaviour = self.listener.on_window_cursor_left(device_id);
+    fn on_window_cursor_left(
+        &self,
+        window_id: winit::window::WindowId,
+        device_id: winit::event::DeviceId,
+    ) {
+        let _use_default_behaviour = self.listener.on_window_cursor_left(window_id, device_id);
     }
 
     fn on_window_mouse_wheel(
         &self,
+        window_id: winit::window::WindowId,
         device_id: winit::event::DeviceId,
         delta: winit::event::MouseScrollDelta,
         phase: winit::event::TouchPhase,
     ) {
-        let _use_default_behaviour = self.listener.on_window_mouse_wheel(device_id, delta, phase);
+        let _use_default_behaviour = self
+            .listener
+            .on_window_mouse_wheel(window_id, device_id, delta, phase);
     }
 
     fn on_window_mouse_input(
         &self,
+        window_id: winit::window::WindowId,
         device_id: winit::event::DeviceId,
         state: winit::event::ElementState,
         button: winit::event::MouseButton,
     ) {
         let _use_default_behaviour = self
             .listener
-            .on_window_mouse_input(device_id, state, button);
+            .on_window_mouse_input(window_id, device_id, state, button);
     }
 
     fn on_window_touchpad_pressure(
         &self,
+        window_id: winit::window::WindowId,
         device_id: winit::event::DeviceId,
         pressure: f32,
         stage: i64,
     ) {
         let _use_default_behaviour = self
             .listener
-            .on_window_touchpad_pressure(device_id, pressure, stage);
+            .on_window_touchpad_pressure(window_id, device_id, pressure, stage);
     }
 
-    fn on_window_axis_motion(&self, device_id: winit::event::DeviceId, axis: u32, value: f64) {
-        let _use_default_behaviour = self.listener.on_window_axis_motion(device_id, axis, value);
+    fn on_window_axis_motion(
+        &self,
+        window_id: winit::window::WindowId,
+        device_id: winit::event::DeviceId,
+        axis: u32,
+        value: f64,
+    ) {
+        let _use_default_behaviour = self
+            .listener
+            .on_window_axis_motion(window_id, device_id, axis, value);
     }
 
-    fn on_window_touch(&self, touch: winit::event::Touch) {
-        let _use_default_behaviour = self.listener.on_window_touch(touch);
+    fn on_window_touch(&self, window_id: winit::window::WindowId, touch: winit::event::Touch) {
+        let _use_default_behaviour = self.listener.on_window_touch(window_id, touch);
     }
 
     fn on_window_scale_change(
         &self,
+        window_id: winit::window::WindowId,
         renderer: &mut Renderer<PerspectiveCamera>,
         scale_factor: f64,
         new_inner_size: &mut winit::dpi::PhysicalSize<u32>,
     ) {
         let use_default_behaviour = self
             .listener
-            .on_window_scale_change(scale_factor, new_inner_size);
+            .on_window_scale_change(window_id, scale_factor, new_inner_size);
 
         if use_default_behaviour {
             // listener.on_window_scale_change may change the new_inner_size values,
             // renderer.resize no
             let new_inner_size_copy = *new_inner_size;
-            renderer.resize(new_inner_size_copy);
+            resize_renderer(renderer, window_id, new_inner_size_copy);
         }
     }
 
-    fn on_window_theme_change(&self, theme: winit::window::Theme) {
-        let _use_default_behaviour = self.listener.on_window_theme_change(theme);
+    fn on_window_theme_change(
+        &self,
+        window_id: winit::window::WindowId,
+        theme: winit::window::Theme,
+    ) {
+        let _use_default_behaviour = self.listener.on_window_theme_change(window_id, theme);
+    }
+}
+
+//= FUNCTIONS ======================================================================================
+
+// Resize the surface of the window, the main one or one of the additional windows.
+fn resize_renderer(
+    renderer: &mut Renderer<PerspectiveCamera>,
+    window_id: winit::window::WindowId,
+    new_size: winit::dpi::PhysicalSize<u32>,
+) {
+    if renderer.window_ids().any(|id| id == window_id) {
+        renderer.resize_window(window_id, new_size);
+    } else {
+        renderer.resize(new_size);
     }
 }
+
+// Asks the renderer to capture the next frame, saved by the redraw that renders it; false if
+// the frame cannot be captured.
+fn request_screenshot(renderer: &mut Renderer<PerspectiveCamera>) -> bool {
+    match renderer.capture_next_frame() {
+        Ok(_) => true,
+        Err(error) => {
+            log::error!("Unable to take the screenshot: {}", error);
+            false
+        }
+    }
+}
+
+// Saves the captured frame in the working directory, naming the file with the current time;
+// false if the frame has not been rendered yet.
+fn save_screenshot(renderer: &mut Renderer<PerspectiveCamera>) -> bool {
+    let millis = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|duration| duration.as_millis())
+        .unwrap_or_default();
+    let filepath = PathBuf::from(format!("screenshot_{}.png", millis));
+
+    match renderer.save_captured_frame(&filepath) {
+        Ok(true) => log::info!("Screenshot saved to {}", filepath.display()),
+        Ok(false) => return false,
+        Err(error) => log::error!("Unable to save the screenshot: {}", error),
+    }
+    true
+}
@@ -2,6 +2,9 @@
 
 /// The Listeners to be implemented for the game logic.
 ///
+/// The window events are emitted with the id of the window they come from, the main one or
+/// one of the windows opened by [Listener::on_open_windows].
+///
 /// For more information about the events see
 /// [`enum Event`](winit::event::Event) and [`enum WindowEvent`](winit::event::WindowEvent).
 pub trait Listener {
@@ -83,13 +86,89 @@ pub trait Listener {
         true
     }
 
+    /// Emitted every frame, before `on_gui` and `on_redraw`, with the time the game logic should
+    /// advance: the [`FrameRecorder::timestep`](irid_renderer::FrameRecorder::timestep) while a
+    /// recording is in progress, so that it is deterministic, the duration of the last frame
+    /// otherwise.
+    #[allow(unused_variables)]
+    fn on_update(&self, timestep: std::time::Duration) -> bool {
+        true
+    }
+
+    /// Emitted every frame, just before `on_redraw`, to build the immediate-mode GUI drawn
+    /// over the scene.
+    ///
+    /// The window events used by the GUI, like a click over one of its windows, are not
+    /// forwarded to the other listeners.
+    #[allow(unused_variables)]
+    fn on_gui(&self, ctx: &irid_renderer::egui::Context) -> bool {
+        true
+    }
+
+    /// Emitted after every frame has been rendered, with its statistics.
+    ///
+    /// Useful to show them in an overlay or to log the performance regressions.
+    #[allow(unused_variables)]
+    fn on_frame_stats(&self, stats: &irid_renderer::FrameStats) -> bool {
+        true
+    }
+
+    /// Emitted every frame, before `on_gui`, to open additional windows: each returned config
+    /// opens a window drawing the scene seen by its own camera, see `on_window_camera`.
+    ///
+    /// The windows share the renderer device, their resize and close are managed by default
+    /// like the ones of the main window, but closing them does not exit the application.
+    fn on_open_windows(&self) -> Vec<crate::IridWindowConfig> {
+        Vec::new()
+    }
+
+    /// Emitted after the device has been lost, or has run out of memory, and the renderer has
+    /// recreated its GPU resources, see
+    /// [`Renderer::recover`](irid_renderer::Renderer::recover).
+    ///
+    /// The render targets, the registered pipelines and the materials whose texture has been
+    /// destroyed are not recreated, this is the place to create them again with the renderer,
+    /// together with the compute dispatches and the frame capture or the recording that have
+    /// been dropped.
+    #[allow(unused_variables)]
+    fn on_gpu_resources_rebuilt(
+        &self,
+        renderer: &mut irid_renderer::Renderer<irid_renderer::PerspectiveCamera>,
+    ) -> bool {
+        true
+    }
+
     //- Window Events ------------------------------------------------------------------------------
 
+    /// A window has been opened from a config returned by `on_open_windows`.
+    #[allow(unused_variables)]
+    fn on_window_open(&self, window_id: winit::window::WindowId) -> bool {
+        true
+    }
+
+    /// Emitted every frame before drawing a window opened by `on_open_windows`, to move its
+    /// camera.
+    ///
+    /// By default the camera is replaced by the renderer one, with the aspect ratio of the
+    /// window: return false to keep the camera moved here.
+    #[allow(unused_variables)]
+    fn on_window_camera(
+        &self,
+        window_id: winit::window::WindowId,
+        camera: &mut irid_renderer::PerspectiveCamera,
+    ) -> bool {
+        true
+    }
+
     /// The size of the window has changed.
     ///
     /// * `new_size` - Contains the client area's new dimensions.
     #[allow(unused_variables)]
-    fn on_window_resize(&self, new_size: winit::dpi::PhysicalSize<u32>) -> bool {
+    fn on_window_resize(
+        &self,
+        window_id: winit::window::WindowId,
+        new_size: winit::dpi::PhysicalSize<u32>,
+    ) -> bool {
         true
     }
 
@@ -97,17 +176,26 @@ pub trait Listener {
     ///
     /// * `physical_position` - Contains the window's new position.
     #[allow(unused_variables)]
-    fn on_window_move(&self, physical_position: winit::dpi::PhysicalPosition<i32>) -> bool {
+    fn on_window_move(
+        &self,
+        window_id: winit::window::WindowId,
+        physical_position: winit::dpi::PhysicalPosition<i32>,
+    ) -> bool {
         true
     }
 
     /// Triggered then an user try to close the window.
-    fn on_window_close(&self) -> bool {
+    ///
+    /// By default closing the main window exits the application, while the additional windows
+    /// are just closed.
+    #[allow(unused_variables)]
+    fn on_window_close(&self, window_id: winit::window::WindowId) -> bool {
         true
     }
 
     /// The window has been destroyed.
-    fn on_window_destroy(&self) -> bool {
+    #[allow(unused_variables)]
+    fn on_window_destroy(&self, window_id: winit::window::WindowId) -> bool {
         true
     }
 
@@ -116,7 +204,11 @@ pub trait Listener {
     /// When the user drops multiple files at once, this event will be emitted for each file
     /// separately.
     #[allow(unused_variables)]
-    fn on_window_drop_file(&self, path: std::path::PathBuf) -> bool {
+    fn on_window_drop_file(
+        &self,
+        window_id: winit::window::WindowId,
+        path: std::path::PathBuf,
+    ) -> bool {
         true
     }
 
@@ -125,7 +217,11 @@ pub trait Listener {
     /// When the user hovers multiple files at once, this event will be emitted for each file
     /// separately.
     #[allow(unused_variables)]
-    fn on_window_hover_file(&self, path: std::path::PathBuf) -> bool {
+    fn on_window_hover_file(
+        &self,
+        window_id: winit::window::WindowId,
+        path: std::path::PathBuf,
+    ) -> bool {
         true
     }
 
@@ -134,13 +230,13 @@ pub trait Listener {
     /// There will be a single `on_window_hover_file_cancelled` event triggered even if multiple
     /// files were hovered.
     #[allow(unused_variables)]
-    fn on_window_hover_file_cancelled(&self) -> bool {
+    fn on_window_hover_file_cancelled(&self, window_id: winit::window::WindowId) -> bool {
         true
     }
 
     /// The window received a unicode character.
     #[allow(unused_variables)]
-    fn on_window_receive_character(&self, c: char) -> bool {
+    fn on_window_receive_character(&self, window_id: winit::window::WindowId, c: char) -> bool {
         true
     }
 
@@ -148,7 +244,7 @@ pub trait Listener {
     ///
     /// * `gained_focus` - True if the window has gained focus, and false if it has lost focus.
     #[allow(unused_variables)]
-    fn on_window_focus(&self, gained_focus: bool) -> bool {
+    fn on_window_focus(&self, window_id: winit::window::WindowId, gained_focus: bool) -> bool {
         true
     }
 
@@ -156,6 +252,7 @@ pub trait Listener {
     #[allow(unused_variables)]
     fn on_window_keyboard_input(
         &self,
+        window_id: winit::window::WindowId,
         device_id: winit::event::DeviceId,
         state: winit::event::ElementState,
         virtual_keycode: winit::event::VirtualKeyCode,
@@ -169,7 +266,11 @@ pub trait Listener {
     /// - **Web**: This API is currently unimplemented on the web. This isn't by design - it's an
     ///   issue, and it should get fixed - but it's the current state of the API.
     #[allow(unused_variables)]
-    fn on_window_modifiers_change(&self, state: winit::event::ModifiersState) -> bool {
+    fn on_window_modifiers_change(
+        &self,
+        window_id: winit::window::WindowId,
+        state: winit::event::ModifiersState,
+    ) -> bool {
         true
     }
 
@@ -182,6 +283,7 @@ pub trait Listener {
     #[allow(unused_variables)]
     fn on_window_cursor_move(
         &self,
+        window_id: winit::window::WindowId,
         device_id: winit::event::DeviceId,
         position: winit::dpi::PhysicalPosition<f64>,
     ) -> bool {
@@ -190,13 +292,21 @@ pub trait Listener {
 
     /// The cursor has entered the window.
     #[allow(unused_variables)]
-    fn on_window_cursor_enter(&self, device_id: winit::event::DeviceId) -> bool {
+    fn on_window_cursor_enter(
+        &self,
+        window_id: winit::window::WindowId,
+        device_id: winit::event::DeviceId,
+    ) -> bool {
         true
     }
 
     /// The cursor has left the window.
     #[allow(unused_variables)]
-    fn on_window_cursor_left(&self, device_id: winit::event::DeviceId) -> bool {
+    fn on_window_cursor_left(
+        &self,
+        window_id: winit::window::WindowId,
+        device_id: winit::event::DeviceId,
+    ) -> bool {
         true
     }
 
@@ -204,6 +314,7 @@ pub trait Listener {
     #[allow(unused_variables)]
     fn on_window_mouse_wheel(
         &self,
+        window_id: winit::window::WindowId,
         device_id: winit::event::DeviceId,
         delta: winit::event::MouseScrollDelta,
         phase: winit::event::TouchPhase,
@@ -215,6 +326,7 @@ pub trait Listener {
     #[allow(unused_variables)]
     fn on_window_mouse_input(
         &self,
+        window_id: winit::window::WindowId,
         device_id: winit::event::DeviceId,
         state: winit::event::ElementState,
         button: winit::event::MouseButton,
@@ -231,6 +343,7 @@ pub trait Listener {
     #[allow(unused_variables)]
     fn on_window_touchpad_pressure(
         &self,
+        window_id: winit::window::WindowId,
         device_id: winit::event::DeviceId,
         pressure: f32,
         stage: i64,
@@ -242,6 +355,7 @@ pub trait Listener {
     #[allow(unused_variables)]
     fn on_window_axis_motion(
         &self,
+        window_id: winit::window::WindowId,
         device_id: winit::event::DeviceId,
         axis: winit::event::AxisId,
         value: f64,
@@ -251,7 +365,11 @@ pub trait Listener {
 
     /// Touch event has been received.
     #[allow(unused_variables)]
-    fn on_window_touch(&self, touch: winit::event::Touch) -> bool {
+    fn on_window_touch(
+        &self,
+        window_id: winit::window::WindowId,
+        touch: winit::event::Touch,
+    ) -> bool {
         true
     }
 
@@ -269,6 +387,7 @@ pub trait Listener {
     #[allow(unused_variables)]
     fn on_window_scale_change(
         &self,
+        window_id: winit::window::WindowId,
         scale_factor: f64,
         new_inner_size: &mut winit::dpi::PhysicalSize<u32>,
     ) -> bool {
@@ -282,7 +401,11 @@ pub trait Listener {
     ///
     /// At the moment this is only supported on Windows.
     #[allow(unused_variables)]
-    fn on_window_theme_change(&self, theme: winit::window::Theme) -> bool {
+    fn on_window_theme_change(
+        &self,
+        window_id: winit::window::WindowId,
+        theme: winit::window::Theme,
+    ) -> bool {
         true
     }
 
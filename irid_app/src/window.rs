@@ -100,9 +100,10 @@ impl IridWindowConfig {
 
     //- Building -----------------------------------------------------------------------------------
 
+    /// Also used to open the windows while the event loop is running.
     pub fn build(
         mut self,
-        event_loop: &winit::event_loop::EventLoop<()>,
+        event_loop: &winit::event_loop::EventLoopWindowTarget<()>,
     ) -> Result<IridWindow, OsError> {
         let delayed_visibility = Some(self.winit_builder.window.visible);
         self.winit_builder.window.visible = false;
@@ -0,0 +1,234 @@
+//= USES ===========================================================================================
+
+use std::{io::BufReader, mem};
+
+use crate::TextureError;
+
+//= CONSTS =========================================================================================
+
+/// Number of faces, and therefore of texture array layers, of a cubemap.
+pub const CUBEMAP_FACES: usize = 6;
+
+//= CUBEMAP FACE ===================================================================================
+
+/// The faces of a cubemap, in the same order as the texture array layers expected by wgpu.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CubemapFace {
+    PositiveX,
+    NegativeX,
+    PositiveY,
+    NegativeY,
+    PositiveZ,
+    NegativeZ,
+}
+
+impl CubemapFace {
+    //- Constants ----------------------------------------------------------------------------------
+
+    /// All the faces ordered by layer index.
+    pub const ALL: [CubemapFace; CUBEMAP_FACES] = [
+        CubemapFace::PositiveX,
+        CubemapFace::NegativeX,
+        CubemapFace::PositiveY,
+        CubemapFace::NegativeY,
+        CubemapFace::PositiveZ,
+        CubemapFace::NegativeZ,
+    ];
+
+    //- Getters ------------------------------------------------------------------------------------
+
+    /// The texture array layer that contains this face.
+    pub fn layer(&self) -> u32 {
+        *self as u32
+    }
+
+    // Returns the world direction that passes through the texel at the given face coordinates,
+    // where u and v are in the range -1.0 to +1.0 and v grows downward.
+    fn direction(&self, u: f32, v: f32) -> [f32; 3] {
+        match *self {
+            CubemapFace::PositiveX => [1.0, -v, -u],
+            CubemapFace::NegativeX => [-1.0, -v, u],
+            CubemapFace::PositiveY => [u, 1.0, v],
+            CubemapFace::NegativeY => [u, -1.0, -v],
+            CubemapFace::PositiveZ => [u, -v, 1.0],
+            CubemapFace::NegativeZ => [-u, -v, -1.0],
+        }
+    }
+}
+
+//= CUBEMAP TEXTURE ================================================================================
+
+/// Six square images of the same size, ready to be uploaded as the layers of a cube texture.
+///
+/// Faces loaded from common image files are stored as `Rgba8UnormSrgb`, while the ones
+/// projected from an equirectangular HDR image are stored as `Rgba16Float`.
+#[derive(Clone, Debug)]
+pub struct CubemapTexture {
+    face_size: u32,
+    format: wgpu::TextureFormat,
+    faces: Vec<Vec<u8>>,
+}
+
+impl CubemapTexture {
+    //- Constructors -------------------------------------------------------------------------------
+
+    /// Load the six faces from image files, ordered as [CubemapFace::ALL].
+    ///
+    /// All the faces must be square and share the same size.
+    pub fn load_faces<P: AsRef<std::path::Path>>(
+        filepaths: &[P; CUBEMAP_FACES],
+    ) -> Result<Self, TextureError> {
+        let mut face_size = None;
+        let mut faces = Vec::with_capacity(CUBEMAP_FACES);
+
+        for filepath in filepaths.iter() {
+            let rgba8 = image::open(filepath)?.to_rgba8();
+            let (width, height) = rgba8.dimensions();
+
+            if width != height || width == 0 || face_size.unwrap_or(width) != width {
+                return Err(TextureError::CubemapFaceSize {
+                    path: filepath.as_ref().to_path_buf(),
+                });
+            }
+
+            face_size = Some(width);
+            faces.push(rgba8.into_raw());
+        }
+
+        Ok(Self {
+            // The faces array cannot be empty, so the size has been set for sure
+            face_size: face_size.unwrap_or_default(),
+            format: wgpu::TextureFormat::Rgba8UnormSrgb,
+            faces,
+        })
+    }
+
+    /// Load an equirectangular (latitude-longitude) Radiance HDR image and project it
+    /// on the six faces of a cube.
+    ///
+    /// If `face_size` is `None` a quarter of the image width is used.
+    pub fn load_equirectangular<P: AsRef<std::path::Path>, S: Into<Option<u32>>>(
+        filepath: P,
+        face_size: S,
+    ) -> Result<Self, TextureError> {
+        let file = std::fs::File::open(&filepath).map_err(image::ImageError::from)?;
+        let decoder = image::codecs::hdr::HdrDecoder::new(BufReader::new(file))?;
+        let metadata = decoder.metadata();
+        let pixels = decoder.read_image_hdr()?;
+
+        let face_size = face_size.into().unwrap_or(metadata.width / 4);
+        if face_size == 0 || metadata.width == 0 || metadata.height == 0 {
+            return Err(TextureError::CubemapFaceSize {
+                path: filepath.as_ref().to_path_buf(),
+            });
+        }
+
+        let equirect = Equirectangular {
+            width: metadata.width,
+            height: metadata.height,
+            pixels: &pixels,
+        };
+
+        let faces = CubemapFace::ALL
+            .iter()
+            .map(|face| equirect.project_face(*face, face_size))
+            .collect();
+
+        Ok(Self {
+            face_size,
+            format: wgpu::TextureFormat::Rgba16Float,
+            faces,
+        })
+    }
+
+    //- Getters ------------------------------------------------------------------------------------
+
+    /// Width and height, in texels, of every face.
+    pub fn face_size(&self) -> u32 {
+        self.face_size
+    }
+
+    /// The texture format of the faces bytes.
+    pub fn format(&self) -> wgpu::TextureFormat {
+        self.format
+    }
+
+    /// Size in bytes of a single texel.
+    pub fn bytes_per_texel(&self) -> u32 {
+        self.format.describe().block_size as u32
+    }
+
+    /// The raw bytes of a face.
+    pub fn face(&self, face: CubemapFace) -> &[u8] {
+        &self.faces[face.layer() as usize]
+    }
+}
+
+//= EQUIRECTANGULAR PROJECTION =====================================================================
+
+// Borrowed view over a decoded HDR image used to sample it by direction.
+struct Equirectangular<'a> {
+    width: u32,
+    height: u32,
+    pixels: &'a [image::Rgb<f32>],
+}
+
+impl Equirectangular<'_> {
+    fn project_face(&self, face: CubemapFace, face_size: u32) -> Vec<u8> {
+        let bytes_per_texel = 4 * mem::size_of::<u16>();
+        let mut bytes = Vec::with_capacity((face_size * face_size) as usize * bytes_per_texel);
+
+        for y in 0..face_size {
+            for x in 0..face_size {
+                // Sample at the center of the texel
+                let u = 2.0 * (x as f32 + 0.5) / face_size as f32 - 1.0;
+                let v = 2.0 * (y as f32 + 0.5) / face_size as f32 - 1.0;
+                let rgb = self.sample(face.direction(u, v));
+
+                for channel in rgb.iter().chain(std::iter::once(&1.0)) {
+                    bytes.extend_from_slice(&half::f16::from_f32(*channel).to_le_bytes());
+                }
+            }
+        }
+
+        bytes
+    }
+
+    // Bilinear sampling, wrapping horizontally and clamping vertically.
+    fn sample(&self, direction: [f32; 3]) -> [f32; 3] {
+        use std::f32::consts::PI;
+
+        let [x, y, z] = direction;
+        let length = (x * x + y * y + z * z).sqrt();
+
+        let longitude = z.atan2(x);
+        let latitude = (y / length).clamp(-1.0, 1.0).acos();
+
+        let s = (longitude / (2.0 * PI) + 0.5) * self.width as f32 - 0.5;
+        let t = (latitude / PI) * self.height as f32 - 0.5;
+
+        let s0 = s.floor();
+        let t0 = t.floor();
+        let fs = s - s0;
+        let ft = t - t0;
+
+        let texel = |s: f32, t: f32| -> [f32; 3] {
+            let s = (s as i64).rem_euclid(self.width as i64) as u32;
+            let t = (t.max(0.0) as u32).min(self.height - 1);
+            self.pixels[(t * self.width + s) as usize].0
+        };
+
+        let a = texel(s0, t0);
+        let b = texel(s0 + 1.0, t0);
+        let c = texel(s0, t0 + 1.0);
+        let d = texel(s0 + 1.0, t0 + 1.0);
+
+        let mut rgb = [0.0; 3];
+        for i in 0..3 {
+            let top = a[i] + (b[i] - a[i]) * fs;
+            let bottom = c[i] + (d[i] - c[i]) * fs;
+            rgb[i] = top + (bottom - top) * ft;
+        }
+        rgb
+    }
+}
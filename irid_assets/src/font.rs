@@ -0,0 +1,362 @@
+//= USES ===========================================================================================
+
+use ab_glyph::{Font as _, ScaleFont as _};
+use thiserror::Error;
+
+//= FONT ERRORS ====================================================================================
+
+#[non_exhaustive]
+#[derive(Debug, Error)]
+pub enum FontError {
+    #[error("Cannot read the font file")]
+    CannotRead {
+        #[from]
+        source: std::io::Error,
+    },
+    #[error("The data is not a valid TTF or OTF font")]
+    InvalidFont {
+        #[from]
+        source: ab_glyph::InvalidFont,
+    },
+}
+
+//= TEXT ALIGNMENT =================================================================================
+
+/// Horizontal alignment of every line inside the text box.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TextAlignment {
+    #[default]
+    Left,
+    Center,
+    Right,
+}
+
+//= TEXT STYLE =====================================================================================
+
+/// How a text is laid out and colored.
+#[derive(Clone, Copy, Debug)]
+pub struct TextStyle {
+    size: f32,
+    color: [f32; 4],
+    max_width: Option<f32>,
+    alignment: TextAlignment,
+    line_spacing: f32,
+}
+
+impl Default for TextStyle {
+    fn default() -> Self {
+        Self {
+            size: 16.0,
+            color: [1.0, 1.0, 1.0, 1.0],
+            max_width: None,
+            alignment: TextAlignment::Left,
+            line_spacing: 1.0,
+        }
+    }
+}
+
+impl TextStyle {
+    //- Constructors -------------------------------------------------------------------------------
+
+    ///
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    //- Setters ------------------------------------------------------------------------------------
+
+    /// Height of the glyphs in pixels.
+    #[inline]
+    pub fn with_size(mut self, size: f32) -> Self {
+        self.size = size;
+        self
+    }
+
+    ///
+    #[inline]
+    pub fn with_color(mut self, r: f32, g: f32, b: f32, a: f32) -> Self {
+        self.color = [r, g, b, a];
+        self
+    }
+
+    /// Words that exceed this width, in pixels, are wrapped on a new line.
+    #[inline]
+    pub fn with_max_width<W: Into<Option<f32>>>(mut self, max_width: W) -> Self {
+        self.max_width = max_width.into();
+        self
+    }
+
+    /// Lines are aligned inside a box as wide as the max width, or as the widest line
+    /// if no max width is set.
+    #[inline]
+    pub fn with_alignment(mut self, alignment: TextAlignment) -> Self {
+        self.alignment = alignment;
+        self
+    }
+
+    /// Multiplier of the font line height.
+    #[inline]
+    pub fn with_line_spacing(mut self, line_spacing: f32) -> Self {
+        self.line_spacing = line_spacing;
+        self
+    }
+
+    //- Getters ------------------------------------------------------------------------------------
+
+    ///
+    pub fn size(&self) -> f32 {
+        self.size
+    }
+
+    ///
+    pub fn color(&self) -> [f32; 4] {
+        self.color
+    }
+
+    ///
+    pub fn max_width(&self) -> Option<f32> {
+        self.max_width
+    }
+
+    ///
+    pub fn alignment(&self) -> TextAlignment {
+        self.alignment
+    }
+
+    ///
+    pub fn line_spacing(&self) -> f32 {
+        self.line_spacing
+    }
+}
+
+//= LAID OUT GLYPH =================================================================================
+
+/// A glyph placed by [Font::layout].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct LayoutGlyph {
+    /// The glyph index inside the font.
+    pub id: u16,
+    /// Position of the glyph origin on the baseline, in pixels, relative to the top-left
+    /// corner of the text with the y axis pointing down.
+    pub position: [f32; 2],
+}
+
+//= GLYPH BITMAP ===================================================================================
+
+/// The coverage of a rasterized glyph, one byte per pixel.
+#[derive(Clone, Debug)]
+pub struct GlyphBitmap {
+    /// Width in pixels.
+    pub width: u32,
+    /// Height in pixels.
+    pub height: u32,
+    /// Offset of the top-left corner of the bitmap from the glyph origin, y axis pointing down.
+    pub offset: [f32; 2],
+    /// Row-major coverage values.
+    pub coverage: Vec<u8>,
+}
+
+//= FONT ===========================================================================================
+
+/// A TrueType or OpenType font.
+#[derive(Clone, Debug)]
+pub struct Font {
+    path: Option<std::path::PathBuf>,
+    inner: ab_glyph::FontArc,
+}
+
+impl Font {
+    //- Constructors -------------------------------------------------------------------------------
+
+    /// Load a TTF or OTF file.
+    pub fn load<P: AsRef<std::path::Path>>(filepath: P) -> Result<Self, FontError> {
+        let data = std::fs::read(&filepath)?;
+        Ok(Self {
+            path: Some(filepath.as_ref().to_path_buf()),
+            inner: ab_glyph::FontArc::try_from_vec(data)?,
+        })
+    }
+
+    /// Create a font from the bytes of a TTF or OTF file.
+    pub fn from_bytes(data: Vec<u8>) -> Result<Self, FontError> {
+        Ok(Self {
+            path: None,
+            inner: ab_glyph::FontArc::try_from_vec(data)?,
+        })
+    }
+
+    //- Getters ------------------------------------------------------------------------------------
+
+    /// The file path, if the font has been loaded from a file.
+    pub fn path(&self) -> Option<&std::path::PathBuf> {
+        self.path.as_ref()
+    }
+
+    /// Distance, in pixels, between two consecutive baselines.
+    pub fn line_height(&self, size: f32) -> f32 {
+        let scaled = self.inner.as_scaled(size);
+        scaled.ascent() - scaled.descent() + scaled.line_gap()
+    }
+
+    //- Layout -------------------------------------------------------------------------------------
+
+    /// Place the glyphs of the text applying kerning, line wrapping and alignment.
+    ///
+    /// Explicit new lines are always honored, while words are wrapped only when a max width
+    /// is set in the style.
+    pub fn layout(&self, text: &str, style: &TextStyle) -> Vec<LayoutGlyph> {
+        let scaled = self.inner.as_scaled(style.size);
+        let line_height = self.line_height(style.size) * style.line_spacing;
+
+        let mut lines: Vec<(Vec<LayoutGlyph>, f32)> = vec![];
+
+        for paragraph in text.split('\n') {
+            let mut line = vec![];
+            let mut caret = 0.0_f32;
+            let mut previous: Option<ab_glyph::GlyphId> = None;
+            // Width of the white spaces at the end of the line, not counted for the alignment
+            let mut trailing = 0.0_f32;
+
+            for word in split_inclusive_whitespace(paragraph) {
+                // Measure the word first, so that it can be moved as a whole on a new line
+                let mut word_glyphs = Vec::with_capacity(word.len());
+                let mut word_caret = caret;
+                let mut word_previous = previous;
+                for c in word.chars() {
+                    let id = scaled.glyph_id(c);
+                    if let Some(previous_id) = word_previous {
+                        word_caret += scaled.kern(previous_id, id);
+                    }
+                    word_glyphs.push((id, word_caret));
+                    word_caret += scaled.h_advance(id);
+                    word_previous = Some(id);
+                }
+
+                let word_trailing = word
+                    .chars()
+                    .rev()
+                    .take_while(|c| c.is_whitespace())
+                    .map(|c| scaled.h_advance(scaled.glyph_id(c)))
+                    .sum::<f32>();
+                let trimmed_width = word_caret - caret - word_trailing;
+
+                let exceeds = match style.max_width {
+                    Some(max_width) => caret + trimmed_width > max_width,
+                    None => false,
+                };
+
+                if exceeds && !line.is_empty() {
+                    lines.push((line, caret - trailing));
+                    line = vec![];
+
+                    // Lay out the word again from the start of the new line, without the
+                    // kerning with the last glyph of the previous line
+                    let mut new_caret = 0.0;
+                    let mut new_previous = None;
+                    for &(id, _) in word_glyphs.iter() {
+                        if let Some(previous_id) = new_previous {
+                            new_caret += scaled.kern(previous_id, id);
+                        }
+                        line.push(LayoutGlyph {
+                            id: id.0,
+                            position: [new_caret, 0.0],
+                        });
+                        new_caret += scaled.h_advance(id);
+                        new_previous = Some(id);
+                    }
+                    caret = new_caret;
+                    previous = new_previous;
+                } else {
+                    line.extend(word_glyphs.iter().map(|&(id, x)| LayoutGlyph {
+                        id: id.0,
+                        position: [x, 0.0],
+                    }));
+                    caret = word_caret;
+                    previous = word_previous;
+                }
+                trailing = word_trailing;
+            }
+
+            lines.push((line, caret - trailing));
+        }
+
+        //- Alignment ------------------------------------------------------------------------------
+
+        let box_width = style.max_width.unwrap_or_else(|| {
+            lines
+                .iter()
+                .map(|&(_, width)| width)
+                .fold(0.0_f32, f32::max)
+        });
+
+        let mut glyphs = Vec::with_capacity(text.len());
+        for (i, (line, width)) in lines.into_iter().enumerate() {
+            let offset_x = match style.alignment {
+                TextAlignment::Left => 0.0,
+                TextAlignment::Center => (box_width - width) * 0.5,
+                TextAlignment::Right => box_width - width,
+            };
+            let baseline = scaled.ascent() + line_height * i as f32;
+
+            glyphs.extend(line.into_iter().map(|glyph| LayoutGlyph {
+                id: glyph.id,
+                position: [glyph.position[0] + offset_x, baseline],
+            }));
+        }
+        glyphs
+    }
+
+    //- Rasterization ------------------------------------------------------------------------------
+
+    /// Rasterize a glyph at the given pixel size.
+    ///
+    /// Returns `None` for glyphs without an outline, like the white spaces.
+    pub fn rasterize(&self, id: u16, size: f32) -> Option<GlyphBitmap> {
+        let glyph = ab_glyph::GlyphId(id).with_scale(size);
+        let outlined = self.inner.outline_glyph(glyph)?;
+        let bounds = outlined.px_bounds();
+
+        let width = bounds.width() as u32;
+        let height = bounds.height() as u32;
+        let mut coverage = vec![0_u8; (width * height) as usize];
+        outlined.draw(|x, y, c| {
+            if x < width && y < height {
+                coverage[(y * width + x) as usize] = (c.clamp(0.0, 1.0) * 255.0).round() as u8;
+            }
+        });
+
+        Some(GlyphBitmap {
+            width,
+            height,
+            offset: [bounds.min.x, bounds.min.y],
+            coverage,
+        })
+    }
+}
+
+//= FUNCTIONS ======================================================================================
+
+// Splits the text in words, each one keeps the white spaces that follow it.
+fn split_inclusive_whitespace(text: &str) -> impl Iterator<Item = &str> {
+    let mut rest = text;
+    std::iter::from_fn(move || {
+        if rest.is_empty() {
+            return None;
+        }
+
+        let mut in_whitespace = false;
+        let mut end = rest.len();
+        for (i, c) in rest.char_indices() {
+            if c.is_whitespace() {
+                in_whitespace = true;
+            } else if in_whitespace {
+                end = i;
+                break;
+            }
+        }
+
+        let (word, remainder) = rest.split_at(end);
+        rest = remainder;
+        Some(word)
+    })
+}
@@ -37,6 +37,8 @@ clippy::pattern_type_mismatch,
 
 //= USES ===========================================================================================
 
+pub use self::cubemap::*;
+pub use self::font::*;
 pub use self::image::*;
 pub use self::model::*;
 pub use self::texture::*;
@@ -44,6 +46,8 @@ pub use self::vertex::*;
 
 //= MODS ===========================================================================================
 
+pub(crate) mod cubemap;
+pub(crate) mod font;
 pub(crate) mod image;
 pub(crate) mod model;
 pub(crate) mod texture;
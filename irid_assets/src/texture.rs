@@ -16,6 +16,8 @@ pub enum TextureError {
         #[from]
         source: image::error::ImageError,
     },
+    #[error("Cubemap faces must be square and of the same size: {path:?}")]
+    CubemapFaceSize { path: std::path::PathBuf },
 }
 
 //= DIFFUSE TEXTURE ================================================================================
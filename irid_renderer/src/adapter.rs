@@ -0,0 +1,341 @@
+//= USES ===========================================================================================
+
+use std::{fmt, sync::Arc};
+
+use pollster::FutureExt;
+use thiserror::Error;
+
+//= ERRORS =========================================================================================
+
+#[non_exhaustive]
+#[derive(Debug, Error)]
+pub enum AdapterError {
+    #[error("no adapter compatible with the surface could be obtained")]
+    NotObtained,
+    #[error("no adapter matches the selector {selector}")]
+    NoMatch { selector: String },
+    #[error("the adapter {name} cannot present to the surface")]
+    IncompatibleSurface { name: String },
+    #[error("the adapter {name} does not support the features {missing:?}")]
+    UnsupportedFeatures {
+        name: String,
+        missing: wgpu::Features,
+    },
+    #[error("the adapter {name} does not support the limits {limits:?}")]
+    UnsupportedLimits {
+        name: String,
+        limits: Vec<&'static str>,
+    },
+}
+
+//= ADAPTER DETAILS ================================================================================
+
+/// The description of an adapter, as listed by [enumerate_adapters].
+#[derive(Clone, Debug)]
+pub struct AdapterDetails {
+    info: wgpu::AdapterInfo,
+    features: wgpu::Features,
+    limits: wgpu::Limits,
+}
+
+impl AdapterDetails {
+    //- Constructors -------------------------------------------------------------------------------
+
+    pub(crate) fn new(adapter: &wgpu::Adapter) -> Self {
+        Self {
+            info: adapter.get_info(),
+            features: adapter.features(),
+            limits: adapter.limits(),
+        }
+    }
+
+    //- Getters ------------------------------------------------------------------------------------
+
+    ///
+    pub fn name(&self) -> &str {
+        &self.info.name
+    }
+
+    /// The PCI id of the vendor.
+    pub fn vendor(&self) -> usize {
+        self.info.vendor
+    }
+
+    /// The PCI id of the adapter.
+    pub fn device(&self) -> usize {
+        self.info.device
+    }
+
+    ///
+    pub fn device_type(&self) -> wgpu::DeviceType {
+        self.info.device_type
+    }
+
+    ///
+    pub fn backend(&self) -> wgpu::Backend {
+        self.info.backend
+    }
+
+    /// All the features that a device of this adapter can enable.
+    pub fn features(&self) -> wgpu::Features {
+        self.features
+    }
+
+    /// The best limits that a device of this adapter can have.
+    pub fn limits(&self) -> &wgpu::Limits {
+        &self.limits
+    }
+
+    //- Support ------------------------------------------------------------------------------------
+
+    /// The requested features that the adapter does not support, empty if it supports them all.
+    pub fn missing_features(&self, features: wgpu::Features) -> wgpu::Features {
+        features - self.features
+    }
+
+    /// The names of the requested limits that are better than the adapter ones, empty if the
+    /// adapter supports them all.
+    pub fn unsupported_limits(&self, limits: &wgpu::Limits) -> Vec<&'static str> {
+        let supported = &self.limits;
+        let mut unsupported = vec![];
+
+        // The maximums must not be higher than the adapter ones
+        macro_rules! check_max {
+            ($($field:ident),* $(,)?) => {$(
+                if limits.$field > supported.$field {
+                    unsupported.push(stringify!($field));
+                }
+            )*};
+        }
+        // The minimums, that are alignments, must not be lower than the adapter ones
+        macro_rules! check_min {
+            ($($field:ident),* $(,)?) => {$(
+                if limits.$field < supported.$field {
+                    unsupported.push(stringify!($field));
+                }
+            )*};
+        }
+
+        check_max!(
+            max_texture_dimension_1d,
+            max_texture_dimension_2d,
+            max_texture_dimension_3d,
+            max_texture_array_layers,
+            max_bind_groups,
+            max_dynamic_uniform_buffers_per_pipeline_layout,
+            max_dynamic_storage_buffers_per_pipeline_layout,
+            max_sampled_textures_per_shader_stage,
+            max_samplers_per_shader_stage,
+            max_storage_buffers_per_shader_stage,
+            max_storage_textures_per_shader_stage,
+            max_uniform_buffers_per_shader_stage,
+            max_uniform_buffer_binding_size,
+            max_storage_buffer_binding_size,
+            max_vertex_buffers,
+            max_vertex_attributes,
+            max_vertex_buffer_array_stride,
+            max_push_constant_size,
+            max_inter_stage_shader_components,
+            max_compute_workgroup_storage_size,
+            max_compute_invocations_per_workgroup,
+            max_compute_workgroup_size_x,
+            max_compute_workgroup_size_y,
+            max_compute_workgroup_size_z,
+            max_compute_workgroups_per_dimension,
+        );
+        check_min!(
+            min_uniform_buffer_offset_alignment,
+            min_storage_buffer_offset_alignment,
+        );
+
+        unsupported
+    }
+
+    /// Fails if the adapter does not support all the features and limits.
+    pub fn check_support(
+        &self,
+        features: wgpu::Features,
+        limits: &wgpu::Limits,
+    ) -> Result<(), AdapterError> {
+        let missing = self.missing_features(features);
+        if !missing.is_empty() {
+            return Err(AdapterError::UnsupportedFeatures {
+                name: self.name().to_owned(),
+                missing,
+            });
+        }
+        let unsupported = self.unsupported_limits(limits);
+        if !unsupported.is_empty() {
+            return Err(AdapterError::UnsupportedLimits {
+                name: self.name().to_owned(),
+                limits: unsupported,
+            });
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for AdapterDetails {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} ({:?}, {:?}, vendor {:#06x}, device {:#06x})",
+            self.info.name,
+            self.info.device_type,
+            self.info.backend,
+            self.info.vendor,
+            self.info.device
+        )
+    }
+}
+
+//= ADAPTER SELECTOR ===============================================================================
+
+/// Choose the adapter among the ones listed by [enumerate_adapters], see
+/// [RendererConfig::with_adapter](crate::RendererConfig::with_adapter).
+///
+/// The first adapter that matches and can present to the window is picked.
+#[derive(Clone)]
+pub enum AdapterSelector {
+    /// The adapter at this index of the list.
+    Index(usize),
+    /// The adapters whose name contains this text, ignoring the case.
+    Name(String),
+    /// The adapters for which the predicate is true.
+    Predicate(Arc<dyn Fn(&AdapterDetails) -> bool + Send + Sync>),
+}
+
+impl AdapterSelector {
+    //- Constructors -------------------------------------------------------------------------------
+
+    ///
+    pub fn name<S: Into<String>>(name: S) -> Self {
+        Self::Name(name.into())
+    }
+
+    ///
+    pub fn predicate<F>(predicate: F) -> Self
+    where
+        F: Fn(&AdapterDetails) -> bool + Send + Sync + 'static,
+    {
+        Self::Predicate(Arc::new(predicate))
+    }
+
+    //- Selection ----------------------------------------------------------------------------------
+
+    ///
+    pub fn matches(&self, index: usize, details: &AdapterDetails) -> bool {
+        match *self {
+            Self::Index(selected_index) => index == selected_index,
+            Self::Name(ref name) => details.name().to_lowercase().contains(&name.to_lowercase()),
+            Self::Predicate(ref predicate) => predicate(details),
+        }
+    }
+}
+
+impl fmt::Debug for AdapterSelector {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Self::Index(index) => f.debug_tuple("Index").field(&index).finish(),
+            Self::Name(ref name) => f.debug_tuple("Name").field(name).finish(),
+            Self::Predicate(_) => f.debug_tuple("Predicate").finish(),
+        }
+    }
+}
+
+//= ADAPTER REQUEST ================================================================================
+
+/// The options used to obtain the adapter of the renderer.
+#[derive(Clone, Debug)]
+pub(crate) struct AdapterRequest {
+    pub(crate) power_preference: wgpu::PowerPreference,
+    pub(crate) force_fallback_adapter: bool,
+    // When None the adapter is picked by wgpu, following the options above
+    pub(crate) selector: Option<AdapterSelector>,
+}
+
+impl AdapterRequest {
+    //- Request ------------------------------------------------------------------------------------
+
+    /// Obtain an adapter able to present to the surface.
+    pub(crate) fn request(
+        &self,
+        instance: &wgpu::Instance,
+        backends: wgpu::Backends,
+        surface: &wgpu::Surface,
+    ) -> Result<wgpu::Adapter, AdapterError> {
+        let adapters = enumerate(instance, backends);
+        log_adapters(&adapters);
+
+        let selector = match self.selector.as_ref() {
+            Some(selector) => selector,
+            None => {
+                let adapter_options = wgpu::RequestAdapterOptions {
+                    power_preference: self.power_preference,
+                    force_fallback_adapter: self.force_fallback_adapter,
+                    compatible_surface: Some(surface),
+                };
+                return async { instance.request_adapter(&adapter_options).await }
+                    .block_on()
+                    .ok_or(AdapterError::NotObtained);
+            }
+        };
+
+        let mut incompatible_name = None;
+        for (index, (adapter, details)) in adapters.into_iter().enumerate() {
+            if !selector.matches(index, &details) {
+                continue;
+            }
+            if adapter.is_surface_supported(surface) {
+                return Ok(adapter);
+            }
+            let _ = incompatible_name.get_or_insert_with(|| details.name().to_owned());
+        }
+
+        Err(match incompatible_name {
+            Some(name) => AdapterError::IncompatibleSurface { name },
+            None => AdapterError::NoMatch {
+                selector: format!("{:?}", selector),
+            },
+        })
+    }
+}
+
+//= FUNCTIONS ======================================================================================
+
+/// List the adapters of the backends, in the order used by [AdapterSelector::Index].
+pub fn enumerate_adapters(backends: wgpu::Backends) -> Vec<AdapterDetails> {
+    let instance = wgpu::Instance::new(backends);
+    enumerate(&instance, backends)
+        .into_iter()
+        .map(|(_, details)| details)
+        .collect()
+}
+
+fn enumerate(
+    instance: &wgpu::Instance,
+    backends: wgpu::Backends,
+) -> Vec<(wgpu::Adapter, AdapterDetails)> {
+    instance.poll_all(true);
+    instance
+        .enumerate_adapters(backends)
+        .map(|adapter| {
+            let details = AdapterDetails::new(&adapter);
+            (adapter, details)
+        })
+        .collect()
+}
+
+// For debug purpose prints on console all the available adapters.
+fn log_adapters(adapters: &[(wgpu::Adapter, AdapterDetails)]) {
+    if adapters.is_empty() {
+        log::info!("No Adapter Found");
+    }
+    for (index, details) in adapters.iter().map(|pair| &pair.1).enumerate() {
+        if index == 0 {
+            log::info!("Adapter(s) found - [{}] {}", index, details);
+        } else {
+            log::info!("                 - [{}] {}", index, details);
+        }
+    }
+}
@@ -0,0 +1,224 @@
+//= USES ===========================================================================================
+
+use crate::device::Device;
+
+//= BIND GROUP LAYOUT BUILDER ======================================================================
+
+///
+#[derive(Clone, Debug, Default)]
+pub struct BindGroupLayoutBuilder<'a> {
+    label: wgpu::Label<'a>,
+    entries: Vec<wgpu::BindGroupLayoutEntry>,
+}
+
+impl<'a> BindGroupLayoutBuilder<'a> {
+    //- Constructors -------------------------------------------------------------------------------
+
+    ///
+    pub fn new() -> Self {
+        Self {
+            label: Some("Bind Group Layout Default Label"),
+            entries: vec![],
+        }
+    }
+
+    //- Setters ------------------------------------------------------------------------------------
+
+    ///
+    pub fn with_label(mut self, label_text: &'a str) -> Self {
+        self.label = if label_text.is_empty() {
+            wgpu::Label::default()
+        } else {
+            Some(label_text)
+        };
+        self
+    }
+
+    /// Add a uniform buffer entry.
+    pub fn with_uniform(self, binding: u32, visibility: wgpu::ShaderStages) -> Self {
+        self.with_entry(
+            binding,
+            visibility,
+            wgpu::BindingType::Buffer {
+                ty: wgpu::BufferBindingType::Uniform,
+                has_dynamic_offset: false,
+                min_binding_size: None,
+            },
+        )
+    }
+
+    /// Add a uniform buffer entry bound with a dynamic offset, each binding sees `binding_size`
+    /// bytes of the buffer.
+    pub fn with_dynamic_uniform(
+        self,
+        binding: u32,
+        visibility: wgpu::ShaderStages,
+        binding_size: wgpu::BufferSize,
+    ) -> Self {
+        self.with_entry(
+            binding,
+            visibility,
+            wgpu::BindingType::Buffer {
+                ty: wgpu::BufferBindingType::Uniform,
+                has_dynamic_offset: true,
+                min_binding_size: Some(binding_size),
+            },
+        )
+    }
+
+    /// Add a storage buffer entry, writable by the shaders unless `read_only`.
+    pub fn with_storage(
+        self,
+        binding: u32,
+        visibility: wgpu::ShaderStages,
+        read_only: bool,
+    ) -> Self {
+        self.with_entry(
+            binding,
+            visibility,
+            wgpu::BindingType::Buffer {
+                ty: wgpu::BufferBindingType::Storage { read_only },
+                has_dynamic_offset: false,
+                min_binding_size: None,
+            },
+        )
+    }
+
+    /// Add a filterable float texture entry, not multisampled.
+    pub fn with_texture(
+        self,
+        binding: u32,
+        visibility: wgpu::ShaderStages,
+        view_dimension: wgpu::TextureViewDimension,
+    ) -> Self {
+        self.with_entry(
+            binding,
+            visibility,
+            wgpu::BindingType::Texture {
+                multisampled: false,
+                view_dimension,
+                sample_type: wgpu::TextureSampleType::Float { filterable: true },
+            },
+        )
+    }
+
+    /// Add a filtering sampler entry.
+    pub fn with_sampler(self, binding: u32, visibility: wgpu::ShaderStages) -> Self {
+        self.with_entry(
+            binding,
+            visibility,
+            wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+        )
+    }
+
+    /// Add an entry of any type, for the ones not covered by the other setters.
+    pub fn with_entry(
+        mut self,
+        binding: u32,
+        visibility: wgpu::ShaderStages,
+        ty: wgpu::BindingType,
+    ) -> Self {
+        self.entries.push(wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility,
+            ty,
+            count: None,
+        });
+        self
+    }
+
+    //- Build --------------------------------------------------------------------------------------
+
+    /// Build a new [BindGroupLayout](wgpu::BindGroupLayout).
+    pub fn build(self, device: &Device) -> wgpu::BindGroupLayout {
+        device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: self.label,
+            entries: &self.entries,
+        })
+    }
+}
+
+//= BIND GROUP BUILDER =============================================================================
+
+///
+#[derive(Clone, Debug)]
+pub struct BindGroupBuilder<'a> {
+    label: wgpu::Label<'a>,
+    layout: &'a wgpu::BindGroupLayout,
+    entries: Vec<wgpu::BindGroupEntry<'a>>,
+}
+
+impl<'a> BindGroupBuilder<'a> {
+    //- Constructors -------------------------------------------------------------------------------
+
+    /// The entries must match the ones of the given layout.
+    pub fn new(layout: &'a wgpu::BindGroupLayout) -> Self {
+        Self {
+            label: Some("Bind Group Default Label"),
+            layout,
+            entries: vec![],
+        }
+    }
+
+    //- Setters ------------------------------------------------------------------------------------
+
+    ///
+    pub fn with_label(mut self, label_text: &'a str) -> Self {
+        self.label = if label_text.is_empty() {
+            wgpu::Label::default()
+        } else {
+            Some(label_text)
+        };
+        self
+    }
+
+    /// Bind the whole buffer, for uniform and storage entries.
+    pub fn with_buffer(self, binding: u32, buffer: &'a wgpu::Buffer) -> Self {
+        self.with_resource(binding, buffer.as_entire_binding())
+    }
+
+    /// Bind `size` bytes of the buffer from its start, for the dynamic offset entries.
+    pub fn with_buffer_range(
+        self,
+        binding: u32,
+        buffer: &'a wgpu::Buffer,
+        size: wgpu::BufferSize,
+    ) -> Self {
+        self.with_resource(
+            binding,
+            wgpu::BindingResource::Buffer(wgpu::BufferBinding {
+                buffer,
+                offset: 0,
+                size: Some(size),
+            }),
+        )
+    }
+
+    ///
+    pub fn with_texture_view(self, binding: u32, texture_view: &'a wgpu::TextureView) -> Self {
+        self.with_resource(binding, wgpu::BindingResource::TextureView(texture_view))
+    }
+
+    ///
+    pub fn with_sampler(self, binding: u32, sampler: &'a wgpu::Sampler) -> Self {
+        self.with_resource(binding, wgpu::BindingResource::Sampler(sampler))
+    }
+
+    /// Bind a resource of any type, for the ones not covered by the other setters.
+    pub fn with_resource(mut self, binding: u32, resource: wgpu::BindingResource<'a>) -> Self {
+        self.entries
+            .push(wgpu::BindGroupEntry { binding, resource });
+        self
+    }
+
+    //- Build --------------------------------------------------------------------------------------
+
+    /// Build a new [BindGroup](wgpu::BindGroup).
+    pub fn build(self, device: &Device) -> wgpu::BindGroup {
+        device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: self.label,
+            layout: self.layout,
+            entries: &self.entries,
+        })
+    }
+}
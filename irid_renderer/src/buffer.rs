@@ -0,0 +1,394 @@
+//= USES ===========================================================================================
+
+use std::{marker::PhantomData, sync::Arc};
+
+use bytemuck::Pod;
+use thiserror::Error;
+
+use crate::{
+    bind_group::{BindGroupBuilder, BindGroupLayoutBuilder},
+    device::Device,
+    queue::Queue,
+};
+
+//= ERRORS =========================================================================================
+
+#[non_exhaustive]
+#[derive(Clone, Debug, Error, PartialEq, Eq)]
+pub enum BufferError {
+    #[error("{type_name} is zero sized")]
+    ZeroSized { type_name: &'static str },
+    #[error(
+        "the size of {type_name} is {size} bytes, it must be padded to a multiple of {alignment}"
+    )]
+    Padding {
+        type_name: &'static str,
+        size: usize,
+        alignment: usize,
+    },
+    #[error("a binding of {size} bytes exceeds the limit of {limit} bytes")]
+    BindingTooLarge { size: u64, limit: u32 },
+    #[error("the elements {start}..{end} are out of the {capacity} elements of the buffer")]
+    OutOfBounds { start: u32, end: u32, capacity: u32 },
+}
+
+//= CONSTS =========================================================================================
+
+// The uniform structs are aligned as a vec4 (std140), the storage ones as a scalar (std430).
+const UNIFORM_ALIGNMENT: usize = 16;
+const STORAGE_ALIGNMENT: usize = 4;
+
+//= UNIFORM BUFFER =================================================================================
+
+/// A uniform buffer holding a value of T, or many values each bound with its own dynamic
+/// offset, together with a bind group exposing it at binding 0.
+///
+/// T must follow the std140 layout of the uniforms. Only its size is checked to be a multiple
+/// of 16 bytes, the offsets of its fields are not: the padding after the vec3 fields has to be
+/// added by hand.
+#[derive(Debug)]
+pub struct UniformBuffer<T: Pod> {
+    buffer: wgpu::Buffer,
+    bind_group_layout: Arc<wgpu::BindGroupLayout>,
+    bind_group: wgpu::BindGroup,
+    // The distance between two values, aligned to the dynamic offsets alignment
+    stride: wgpu::BufferAddress,
+    capacity: u32,
+    is_dynamic: bool,
+    marker: PhantomData<T>,
+}
+
+impl<T: Pod> UniformBuffer<T> {
+    //- Constructors -------------------------------------------------------------------------------
+
+    /// A buffer holding the single value.
+    pub fn new(
+        device: &Device,
+        label_text: &str,
+        value: &T,
+        visibility: wgpu::ShaderStages,
+    ) -> Result<Self, BufferError> {
+        let binding_size = check_size::<T>(UNIFORM_ALIGNMENT)?;
+        check_binding_size(
+            binding_size.get(),
+            device.limits().max_uniform_buffer_binding_size,
+        )?;
+
+        let buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some(label_text),
+            contents: bytemuck::bytes_of(value),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        });
+
+        let bind_group_layout = Arc::new(Self::create_bind_group_layout(device, visibility));
+        let bind_group = BindGroupBuilder::new(&bind_group_layout)
+            .with_label(label_text)
+            .with_buffer(0, &buffer)
+            .build(device);
+
+        Ok(Self {
+            buffer,
+            bind_group_layout,
+            bind_group,
+            stride: binding_size.get(),
+            capacity: 1,
+            is_dynamic: false,
+            marker: PhantomData,
+        })
+    }
+
+    /// A buffer of `capacity` values, not initialized, sharing the same bind group: each one
+    /// is bound with its [UniformBuffer::dynamic_offset]. Useful for many small per-object
+    /// uniforms.
+    pub fn new_dynamic(
+        device: &Device,
+        label_text: &str,
+        capacity: u32,
+        visibility: wgpu::ShaderStages,
+    ) -> Result<Self, BufferError> {
+        let binding_size = check_size::<T>(UNIFORM_ALIGNMENT)?;
+        let limits = device.limits();
+        check_binding_size(binding_size.get(), limits.max_uniform_buffer_binding_size)?;
+
+        let alignment = limits.min_uniform_buffer_offset_alignment as wgpu::BufferAddress;
+        let stride = (binding_size.get() + alignment - 1) / alignment * alignment;
+        let capacity = capacity.max(1);
+
+        let buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some(label_text),
+            size: stride * capacity as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        let bind_group_layout = Arc::new(
+            BindGroupLayoutBuilder::new()
+                .with_label("Dynamic Uniform Bind Group Layout")
+                .with_dynamic_uniform(0, visibility, binding_size)
+                .build(device),
+        );
+        let bind_group = BindGroupBuilder::new(&bind_group_layout)
+            .with_label(label_text)
+            .with_buffer_range(0, &buffer, binding_size)
+            .build(device);
+
+        Ok(Self {
+            buffer,
+            bind_group_layout,
+            bind_group,
+            stride,
+            capacity,
+            is_dynamic: true,
+            marker: PhantomData,
+        })
+    }
+
+    /// Create a layout compatible with the bind groups of the buffers holding a single value,
+    /// useful to build a pipeline layout before any buffer has been created.
+    pub fn create_bind_group_layout(
+        device: &Device,
+        visibility: wgpu::ShaderStages,
+    ) -> wgpu::BindGroupLayout {
+        BindGroupLayoutBuilder::new()
+            .with_label("Uniform Bind Group Layout")
+            .with_uniform(0, visibility)
+            .build(device)
+    }
+
+    //- Writes -------------------------------------------------------------------------------------
+
+    /// Schedule the write of the value, the first one of a dynamic buffer.
+    pub fn write(&self, queue: &Queue, value: &T) {
+        queue.write_buffer(&self.buffer, 0, bytemuck::bytes_of(value));
+    }
+
+    /// Schedule the write of the values from the `first` one, each at its dynamic offset.
+    pub fn write_from(&self, queue: &Queue, first: u32, values: &[T]) -> Result<(), BufferError> {
+        check_bounds(first, values.len(), self.capacity)?;
+
+        // Every value is padded up to the stride
+        let value_size = std::mem::size_of::<T>();
+        let mut bytes = vec![0_u8; self.stride as usize * values.len()];
+        for (chunk, value) in bytes.chunks_mut(self.stride as usize).zip(values) {
+            chunk[..value_size].copy_from_slice(bytemuck::bytes_of(value));
+        }
+        queue.write_buffer(
+            &self.buffer,
+            first as wgpu::BufferAddress * self.stride,
+            &bytes,
+        );
+        Ok(())
+    }
+
+    //- Getters ------------------------------------------------------------------------------------
+
+    /// The offset given to `set_bind_group` to bind the value at `index`.
+    pub fn dynamic_offset(&self, index: u32) -> Result<wgpu::DynamicOffset, BufferError> {
+        check_bounds(index, 1, self.capacity)?;
+        Ok((index as wgpu::BufferAddress * self.stride) as wgpu::DynamicOffset)
+    }
+
+    /// The number of values, one if the buffer is not dynamic.
+    pub fn capacity(&self) -> u32 {
+        self.capacity
+    }
+
+    ///
+    pub fn is_dynamic(&self) -> bool {
+        self.is_dynamic
+    }
+
+    ///
+    pub fn buffer(&self) -> &wgpu::Buffer {
+        &self.buffer
+    }
+
+    /// Shared, so that a [PipelineCache](crate::PipelineCache) can keep it alive.
+    pub fn bind_group_layout(&self) -> &Arc<wgpu::BindGroupLayout> {
+        &self.bind_group_layout
+    }
+
+    ///
+    pub fn bind_group(&self) -> &wgpu::BindGroup {
+        &self.bind_group
+    }
+}
+
+//= STORAGE BUFFER =================================================================================
+
+/// A storage buffer of T elements, together with a bind group exposing it at binding 0.
+///
+/// T must follow the std430 layout of the storages. Only its size is checked to be a multiple
+/// of 4 bytes, the offsets of its fields are not: the elements with vector fields need to be
+/// padded to the alignment of their largest vector by hand.
+#[derive(Debug)]
+pub struct StorageBuffer<T: Pod> {
+    buffer: wgpu::Buffer,
+    bind_group_layout: wgpu::BindGroupLayout,
+    bind_group: wgpu::BindGroup,
+    capacity: u32,
+    marker: PhantomData<T>,
+}
+
+impl<T: Pod> StorageBuffer<T> {
+    //- Constructors -------------------------------------------------------------------------------
+
+    /// A buffer initialized with the elements, the shaders can write it unless `read_only`.
+    pub fn new(
+        device: &Device,
+        label_text: &str,
+        elements: &[T],
+        visibility: wgpu::ShaderStages,
+        read_only: bool,
+    ) -> Result<Self, BufferError> {
+        let element_size = check_size::<T>(STORAGE_ALIGNMENT)?;
+        check_storage_size(device, element_size, elements.len())?;
+
+        let buffer = if elements.is_empty() {
+            // The empty bindings are not valid
+            device.create_storage_buffer(label_text, element_size.get())
+        } else {
+            device.create_storage_buffer_init(label_text, elements)
+        };
+        Ok(Self::from_buffer(
+            device,
+            label_text,
+            buffer,
+            elements.len().max(1) as u32,
+            visibility,
+            read_only,
+        ))
+    }
+
+    /// A buffer of `capacity` elements, not initialized.
+    pub fn with_capacity(
+        device: &Device,
+        label_text: &str,
+        capacity: u32,
+        visibility: wgpu::ShaderStages,
+        read_only: bool,
+    ) -> Result<Self, BufferError> {
+        let element_size = check_size::<T>(STORAGE_ALIGNMENT)?;
+        let capacity = capacity.max(1);
+        check_storage_size(device, element_size, capacity as usize)?;
+
+        let size = element_size.get() * capacity as wgpu::BufferAddress;
+        let buffer = device.create_storage_buffer(label_text, size);
+        Ok(Self::from_buffer(
+            device, label_text, buffer, capacity, visibility, read_only,
+        ))
+    }
+
+    /// Create a layout compatible with the bind groups of the storage buffers, useful to build
+    /// a pipeline layout before any buffer has been created.
+    pub fn create_bind_group_layout(
+        device: &Device,
+        visibility: wgpu::ShaderStages,
+        read_only: bool,
+    ) -> wgpu::BindGroupLayout {
+        BindGroupLayoutBuilder::new()
+            .with_label("Storage Bind Group Layout")
+            .with_storage(0, visibility, read_only)
+            .build(device)
+    }
+
+    fn from_buffer(
+        device: &Device,
+        label_text: &str,
+        buffer: wgpu::Buffer,
+        capacity: u32,
+        visibility: wgpu::ShaderStages,
+        read_only: bool,
+    ) -> Self {
+        let bind_group_layout = Self::create_bind_group_layout(device, visibility, read_only);
+        let bind_group = BindGroupBuilder::new(&bind_group_layout)
+            .with_label(label_text)
+            .with_buffer(0, &buffer)
+            .build(device);
+
+        Self {
+            buffer,
+            bind_group_layout,
+            bind_group,
+            capacity,
+            marker: PhantomData,
+        }
+    }
+
+    //- Writes -------------------------------------------------------------------------------------
+
+    /// Schedule the write of the elements from the `first` one.
+    pub fn write(&self, queue: &Queue, first: u32, elements: &[T]) -> Result<(), BufferError> {
+        check_bounds(first, elements.len(), self.capacity)?;
+        let offset = first as wgpu::BufferAddress * std::mem::size_of::<T>() as wgpu::BufferAddress;
+        queue.write_buffer(&self.buffer, offset, bytemuck::cast_slice(elements));
+        Ok(())
+    }
+
+    //- Getters ------------------------------------------------------------------------------------
+
+    /// The number of elements.
+    pub fn capacity(&self) -> u32 {
+        self.capacity
+    }
+
+    /// The buffer, it can also be copied from and to and be used as a vertex buffer.
+    pub fn buffer(&self) -> &wgpu::Buffer {
+        &self.buffer
+    }
+
+    ///
+    pub fn bind_group_layout(&self) -> &wgpu::BindGroupLayout {
+        &self.bind_group_layout
+    }
+
+    ///
+    pub fn bind_group(&self) -> &wgpu::BindGroup {
+        &self.bind_group
+    }
+}
+
+//= FUNCTIONS ======================================================================================
+
+// The size of T, if it is a multiple of the alignment of its address space; the offsets of the
+// fields cannot be checked without knowing them.
+fn check_size<T>(alignment: usize) -> Result<wgpu::BufferSize, BufferError> {
+    let type_name = std::any::type_name::<T>();
+    let size = std::mem::size_of::<T>();
+    if size % alignment != 0 {
+        return Err(BufferError::Padding {
+            type_name,
+            size,
+            alignment,
+        });
+    }
+    wgpu::BufferSize::new(size as u64).ok_or(BufferError::ZeroSized { type_name })
+}
+
+fn check_binding_size(size: u64, limit: u32) -> Result<(), BufferError> {
+    if size > limit as u64 {
+        return Err(BufferError::BindingTooLarge { size, limit });
+    }
+    Ok(())
+}
+
+fn check_storage_size(
+    device: &Device,
+    element_size: wgpu::BufferSize,
+    count: usize,
+) -> Result<(), BufferError> {
+    let size = element_size.get() * count.max(1) as u64;
+    check_binding_size(size, device.limits().max_storage_buffer_binding_size)
+}
+
+fn check_bounds(first: u32, count: usize, capacity: u32) -> Result<(), BufferError> {
+    let end = first as usize + count;
+    if end > capacity as usize {
+        return Err(BufferError::OutOfBounds {
+            start: first,
+            end: end as u32,
+            capacity,
+        });
+    }
+    Ok(())
+}
@@ -15,6 +15,16 @@ const OPENGL_TO_WGPU_MATRIX: cgmath::Matrix4<f32> = cgmath::Matrix4::new(
     0.0, 0.0, 0.5, 1.0,
 );
 
+/// Maps the depths from 0.0..1.0 to 1.0..0.0, for the projections already mapped to the Wgpu
+/// coordinate system and used with reverse-Z.
+#[rustfmt::skip]
+const REVERSE_Z_MATRIX: cgmath::Matrix4<f32> = cgmath::Matrix4::new(
+    1.0, 0.0, 0.0, 0.0,
+    0.0, 1.0, 0.0, 0.0,
+    0.0, 0.0, -1.0, 0.0,
+    0.0, 0.0, 1.0, 1.0,
+);
+
 //= CAMERA TRAIT ===================================================================================
 
 pub trait Camera {
@@ -37,10 +47,20 @@ pub trait Camera {
     ///
     fn up(&self) -> cgmath::Vector3<f32>;
 
+    /// If the projection maps the near plane to the depth 1.0, false by default.
+    fn is_reverse_z(&self) -> bool {
+        false
+    }
+
     //- Setters ------------------------------------------------------------------------------------
 
     fn set_eye(&mut self, value: cgmath::Point3<f32>);
 
+    /// Called by the renderer to follow
+    /// [DepthConfig::with_reverse_z](crate::DepthConfig::with_reverse_z), ignored by default.
+    #[allow(unused_variables)]
+    fn set_reverse_z(&mut self, reverse_z: bool) {}
+
     ///
     fn add_to_eye(&mut self, value: cgmath::Vector3<f32>);
 
@@ -60,6 +80,41 @@ pub struct PerspectiveCamera {
     fovy: f32,
     znear: f32,
     zfar: f32,
+    reverse_z: bool,
+}
+
+impl PerspectiveCamera {
+    //- Setters ------------------------------------------------------------------------------------
+
+    /// Use a reversed projection with an infinite far plane: the depth is 1.0 on the near plane
+    /// and tends to 0.0 with the distance.
+    ///
+    /// The renderer replaces it with
+    /// [DepthConfig::with_reverse_z](crate::DepthConfig::with_reverse_z).
+    pub fn with_reverse_z(mut self, reverse_z: bool) -> Self {
+        self.reverse_z = reverse_z;
+        self
+    }
+
+    /// Change the aspect ratio, usually called after the window has been resized.
+    pub fn set_aspect(&mut self, aspect: f32) {
+        self.aspect = aspect;
+    }
+
+    //- Projections --------------------------------------------------------------------------------
+
+    // Already mapped to the Wgpu coordinate system, the far plane is ignored.
+    fn build_reverse_z_projection_matrix(&self) -> cgmath::Matrix4<f32> {
+        let focal_length = 1.0 / (cgmath::Rad::from(cgmath::Deg(self.fovy)).0 * 0.5).tan();
+        #[rustfmt::skip]
+        let proj = cgmath::Matrix4::new(
+            focal_length / self.aspect, 0.0, 0.0, 0.0,
+            0.0, focal_length, 0.0, 0.0,
+            0.0, 0.0, 0.0, -1.0,
+            0.0, 0.0, self.znear, 0.0,
+        );
+        proj
+    }
 }
 
 impl Camera for PerspectiveCamera {
@@ -78,6 +133,7 @@ impl Camera for PerspectiveCamera {
             fovy: 45.0,
             znear: 0.1,
             zfar: 100.0,
+            reverse_z: false,
         }
     }
 
@@ -88,6 +144,10 @@ impl Camera for PerspectiveCamera {
         // It's essentially an inverse of whatever the transform matrix of the camera would be.
         let view = cgmath::Matrix4::look_at_rh(self.eye, self.target, self.up);
 
+        if self.reverse_z {
+            return self.build_reverse_z_projection_matrix() * view;
+        }
+
         // The proj matrix wraps the scene to give the effect of depth.
         // Without this, objects up close would be the same size as objects far away.
         let proj = cgmath::perspective(cgmath::Deg(self.fovy), self.aspect, self.znear, self.zfar);
@@ -112,6 +172,11 @@ impl Camera for PerspectiveCamera {
         self.up
     }
 
+    #[inline]
+    fn is_reverse_z(&self) -> bool {
+        self.reverse_z
+    }
+
     //- Setters ------------------------------------------------------------------------------------
 
     #[inline]
@@ -119,6 +184,139 @@ impl Camera for PerspectiveCamera {
         self.eye = value;
     }
 
+    #[inline]
+    fn set_reverse_z(&mut self, reverse_z: bool) {
+        self.reverse_z = reverse_z;
+    }
+
+    #[inline]
+    fn add_to_eye(&mut self, value: cgmath::Vector3<f32>) {
+        self.eye += value;
+    }
+
+    #[inline]
+    fn sub_to_eye(&mut self, value: cgmath::Vector3<f32>) {
+        self.eye -= value;
+    }
+}
+
+//= ORTHOGRAPHIC CAMERA ============================================================================
+
+/// A camera without perspective, mainly used for 2D rendering.
+///
+/// The visible area is `width` x `height` world units centered on the target, so using the
+/// window's physical size as dimensions makes one world unit equal to one pixel.
+#[derive(Debug, Clone)]
+pub struct OrthographicCamera {
+    eye: cgmath::Point3<f32>,
+    target: cgmath::Point3<f32>,
+    up: cgmath::Vector3<f32>,
+    width: f32,
+    height: f32,
+    znear: f32,
+    zfar: f32,
+    reverse_z: bool,
+}
+
+impl OrthographicCamera {
+    //- Setters ------------------------------------------------------------------------------------
+
+    /// Map the depths from 1.0, on the near plane, to 0.0, on the far one.
+    ///
+    /// The renderer replaces it with
+    /// [DepthConfig::with_reverse_z](crate::DepthConfig::with_reverse_z).
+    pub fn with_reverse_z(mut self, reverse_z: bool) -> Self {
+        self.reverse_z = reverse_z;
+        self
+    }
+
+    /// Change the visible area, usually called after the window has been resized.
+    pub fn resize(&mut self, width: f32, height: f32) {
+        self.width = width;
+        self.height = height;
+    }
+
+    /// Move both the eye and the target, keeping the view direction.
+    pub fn translate(&mut self, value: cgmath::Vector3<f32>) {
+        self.eye += value;
+        self.target += value;
+    }
+}
+
+impl Camera for OrthographicCamera {
+    //- Constructors -------------------------------------------------------------------------------
+
+    fn new(width: f32, height: f32) -> Self {
+        Self {
+            // Look at the XY plane from the front, +y is up
+            eye: (0.0, 0.0, 1.0).into(),
+            target: (0.0, 0.0, 0.0).into(),
+            up: cgmath::Vector3::unit_y(),
+            width,
+            height,
+            znear: 0.0,
+            zfar: 2.0,
+            reverse_z: false,
+        }
+    }
+
+    //- Camera Uniform Helpers ---------------------------------------------------------------------
+
+    fn build_view_projection_matrix(&self) -> cgmath::Matrix4<f32> {
+        let view = cgmath::Matrix4::look_at_rh(self.eye, self.target, self.up);
+
+        let half_width = self.width * 0.5;
+        let half_height = self.height * 0.5;
+        let proj = cgmath::ortho(
+            -half_width,
+            half_width,
+            -half_height,
+            half_height,
+            self.znear,
+            self.zfar,
+        );
+
+        if self.reverse_z {
+            REVERSE_Z_MATRIX * OPENGL_TO_WGPU_MATRIX * proj * view
+        } else {
+            OPENGL_TO_WGPU_MATRIX * proj * view
+        }
+    }
+
+    //- Getters ------------------------------------------------------------------------------------
+
+    #[inline]
+    fn eye(&self) -> cgmath::Point3<f32> {
+        self.eye
+    }
+
+    #[inline]
+    fn target(&self) -> cgmath::Point3<f32> {
+        self.target
+    }
+
+    #[inline]
+    fn up(&self) -> cgmath::Vector3<f32> {
+        self.up
+    }
+
+    #[inline]
+    fn is_reverse_z(&self) -> bool {
+        self.reverse_z
+    }
+
+    //- Setters ------------------------------------------------------------------------------------
+
+    #[inline]
+    fn set_eye(&mut self, value: cgmath::Point3<f32>) {
+        self.eye = value;
+    }
+
+    #[inline]
+    fn set_reverse_z(&mut self, reverse_z: bool) {
+        self.reverse_z = reverse_z;
+    }
+
     #[inline]
     fn add_to_eye(&mut self, value: cgmath::Vector3<f32>) {
         self.eye += value;
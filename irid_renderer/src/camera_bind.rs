@@ -1,17 +1,23 @@
 //= USES ===========================================================================================
 
-use crate::Camera;
+use std::sync::Arc;
+
+use cgmath::InnerSpace;
+
+use crate::buffer::UniformBuffer;
 use crate::device::Device;
+use crate::Camera;
+
+//= CONSTS =========================================================================================
+
+const CAMERA_VISIBILITY: wgpu::ShaderStages = wgpu::ShaderStages::VERTEX;
 
 //= CAMERA BIND GROUP ==============================================================================
 
 ///
 #[derive(Debug)]
 pub(crate) struct CameraBindGroup {
-    uniform: CameraUniform,
-    buffer: wgpu::Buffer,
-    bind_group_layout: wgpu::BindGroupLayout,
-    bind_group: wgpu::BindGroup,
+    uniform_buffer: UniformBuffer<CameraUniform>,
 }
 
 impl CameraBindGroup {
@@ -19,66 +25,39 @@ impl CameraBindGroup {
 
     /// Create a new CameraMetadatas from this camera.
     pub(crate) fn new<C: Camera>(camera: &C, device: &Device) -> Self {
-        let mut uniform = CameraUniform::new();
-        uniform.update_view_proj(camera);
-
-        let buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-            label: Some("Camera Buffer"),
-            contents: bytemuck::cast_slice(&[uniform]), // Copy!
-            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
-        });
-
-        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
-            entries: &[wgpu::BindGroupLayoutEntry {
-                binding: 0,
-                visibility: wgpu::ShaderStages::VERTEX,
-                ty: wgpu::BindingType::Buffer {
-                    ty: wgpu::BufferBindingType::Uniform,
-                    has_dynamic_offset: false,
-                    min_binding_size: None,
-                },
-                count: None,
-            }],
-            label: Some("Camera Bind Group Layout"),
-        });
-
-        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
-            layout: &bind_group_layout,
-            entries: &[wgpu::BindGroupEntry {
-                binding: 0,
-                resource: buffer.as_entire_binding(),
-            }],
-            label: Some("Camera Bind Group"),
-        });
+        let uniform_buffer = UniformBuffer::new(
+            device,
+            "Camera",
+            &CameraUniform::new(camera),
+            CAMERA_VISIBILITY,
+        )
+        // The matrix and the vectors are std140 aligned, far below the binding size limit
+        .expect("the camera uniform must fit the uniform buffer layout");
+
+        Self { uniform_buffer }
+    }
 
-        Self {
-            uniform,
-            buffer,
-            bind_group_layout,
-            bind_group,
-        }
+    /// Create a layout compatible with every camera bind group, useful to build a pipeline
+    /// layout when the renderer has no camera.
+    pub(crate) fn create_bind_group_layout(device: &Device) -> wgpu::BindGroupLayout {
+        UniformBuffer::<CameraUniform>::create_bind_group_layout(device, CAMERA_VISIBILITY)
     }
 
     //- Getters ------------------------------------------------------------------------------------
 
     ///
-    pub(crate) fn uniform(&self) -> &CameraUniform {
-        &self.uniform
+    pub(crate) fn uniform_buffer(&self) -> &UniformBuffer<CameraUniform> {
+        &self.uniform_buffer
     }
 
     ///
-    pub(crate) fn buffer(&self) -> &wgpu::Buffer {
-        &self.buffer
-    }
-
-    ///
-    pub(crate) fn bind_group_layout(&self) -> &wgpu::BindGroupLayout {
-        &self.bind_group_layout
+    pub(crate) fn bind_group_layout(&self) -> &Arc<wgpu::BindGroupLayout> {
+        self.uniform_buffer.bind_group_layout()
     }
 
     ///
     pub(crate) fn bind_group(&self) -> &wgpu::BindGroup {
-        &self.bind_group
+        self.uniform_buffer.bind_group()
     }
 }
 
@@ -91,17 +70,31 @@ pub(crate) struct CameraUniform {
     // We can't use cgmath with bytemuck directly so we'll have
     // to convert the Matrix4 into a 4x4 f32 array
     view_proj: [[f32; 4]; 4],
+    // The world directions of the screen right and up, used to orient the billboards
+    right: [f32; 4],
+    up: [f32; 4],
 }
 
 impl CameraUniform {
-    pub(crate) fn new() -> Self {
-        use cgmath::SquareMatrix;
+    pub(crate) fn new<C: Camera>(camera: &C) -> Self {
+        let (right, up) = billboard_axes(camera);
         Self {
-            view_proj: cgmath::Matrix4::identity().into(),
+            view_proj: camera.build_view_projection_matrix().into(),
+            right: right.extend(0.0).into(),
+            up: up.extend(0.0).into(),
         }
     }
+}
+
+//= FUNCTIONS ======================================================================================
 
-    pub(crate) fn update_view_proj<C: Camera>(&mut self, camera: &C) {
-        self.view_proj = camera.build_view_projection_matrix().into();
+// The world directions of the screen right and up of the camera.
+fn billboard_axes<C: Camera>(camera: &C) -> (cgmath::Vector3<f32>, cgmath::Vector3<f32>) {
+    let forward = camera.target() - camera.eye();
+    let right = forward.cross(camera.up());
+    if forward.magnitude2() <= f32::EPSILON || right.magnitude2() <= f32::EPSILON {
+        return (cgmath::Vector3::unit_x(), cgmath::Vector3::unit_y());
     }
+    let right = right.normalize();
+    (right, right.cross(forward).normalize())
 }
@@ -0,0 +1,307 @@
+//= USES ===========================================================================================
+
+use pollster::FutureExt;
+use thiserror::Error;
+
+use crate::device::Device;
+
+//= ERRORS =========================================================================================
+
+#[non_exhaustive]
+#[derive(Debug, Error)]
+pub enum CaptureError {
+    #[error("the window has no area, there is no frame to capture")]
+    EmptyFrame,
+    #[error("the frame capture is not enabled or not supported by the surface")]
+    Disabled,
+    #[error("the surface format {format:?} cannot be captured, only 8 bits RGBA or BGRA are")]
+    UnsupportedFormat { format: wgpu::TextureFormat },
+    #[error("unable to read back the captured frame")]
+    BufferMapping {
+        #[from]
+        source: wgpu::BufferAsyncError,
+    },
+    #[error("unable to save the captured frame")]
+    SaveImage {
+        #[from]
+        source: image::ImageError,
+    },
+}
+
+//= FRAME CAPTURE ==================================================================================
+
+/// A readback buffer where a frame is copied, before being presented, to be saved as an image.
+///
+/// The buffer can be reused for the following frames of the same size and format.
+#[derive(Debug)]
+pub(crate) struct FrameCapture {
+    buffer: wgpu::Buffer,
+    size: wgpu::Extent3d,
+    format: wgpu::TextureFormat,
+    padded_bytes_per_row: u32,
+    is_bgra: bool,
+}
+
+impl FrameCapture {
+    //- Constructors -------------------------------------------------------------------------------
+
+    /// Create a buffer big enough to contain a frame of the given size and format.
+    pub(crate) fn new(
+        device: &Device,
+        window_size: winit::dpi::PhysicalSize<u32>,
+        format: wgpu::TextureFormat,
+    ) -> Result<Self, CaptureError> {
+        if window_size.width == 0 || window_size.height == 0 {
+            return Err(CaptureError::EmptyFrame);
+        }
+
+        let is_bgra = match format {
+            wgpu::TextureFormat::Rgba8Unorm | wgpu::TextureFormat::Rgba8UnormSrgb => false,
+            wgpu::TextureFormat::Bgra8Unorm | wgpu::TextureFormat::Bgra8UnormSrgb => true,
+            _ => return Err(CaptureError::UnsupportedFormat { format }),
+        };
+
+        // The rows of a texture to buffer copy must be aligned to 256 bytes
+        let unpadded_bytes_per_row = window_size.width * 4;
+        let alignment = wgpu::COPY_BYTES_PER_ROW_ALIGNMENT;
+        let padding = (alignment - unpadded_bytes_per_row % alignment) % alignment;
+        let padded_bytes_per_row = unpadded_bytes_per_row + padding;
+
+        let buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Frame Capture Buffer"),
+            size: (padded_bytes_per_row * window_size.height) as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+
+        Ok(Self {
+            buffer,
+            size: wgpu::Extent3d {
+                width: window_size.width,
+                height: window_size.height,
+                depth_or_array_layers: 1,
+            },
+            format,
+            padded_bytes_per_row,
+            is_bgra,
+        })
+    }
+
+    //- Getters ------------------------------------------------------------------------------------
+
+    /// Returns true if a frame of the given size and format can be copied into the buffer.
+    pub(crate) fn fits(
+        &self,
+        window_size: winit::dpi::PhysicalSize<u32>,
+        format: wgpu::TextureFormat,
+    ) -> bool {
+        self.size.width == window_size.width
+            && self.size.height == window_size.height
+            && self.format == format
+    }
+
+    //- Capture ------------------------------------------------------------------------------------
+
+    /// Record the copy of the frame texture into the readback buffer.
+    pub(crate) fn copy_from(&self, encoder: &mut wgpu::CommandEncoder, texture: &wgpu::Texture) {
+        encoder.copy_texture_to_buffer(
+            texture.as_image_copy(),
+            wgpu::ImageCopyBuffer {
+                buffer: &self.buffer,
+                layout: wgpu::ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: std::num::NonZeroU32::new(self.padded_bytes_per_row),
+                    rows_per_image: std::num::NonZeroU32::new(self.size.height),
+                },
+            },
+            self.size,
+        );
+    }
+
+    /// Wait for the copy to be done and convert the buffer content into an RGBA image,
+    /// must be called after the copy has been submitted.
+    pub(crate) fn read(&self, device: &Device) -> Result<image::RgbaImage, CaptureError> {
+        let slice = self.buffer.slice(..);
+        let mapping = slice.map_async(wgpu::MapMode::Read);
+        device.poll(wgpu::Maintain::Wait);
+        mapping.block_on()?;
+
+        let unpadded_bytes_per_row = (self.size.width * 4) as usize;
+        let mut pixels = Vec::with_capacity(unpadded_bytes_per_row * self.size.height as usize);
+        {
+            let padded = slice.get_mapped_range();
+            for row in padded.chunks(self.padded_bytes_per_row as usize) {
+                pixels.extend_from_slice(&row[..unpadded_bytes_per_row]);
+            }
+        }
+        self.buffer.unmap();
+
+        if self.is_bgra {
+            swizzle_bgra_to_rgba(&mut pixels);
+        }
+
+        // The buffer size always matches the image dimensions
+        Ok(
+            image::RgbaImage::from_raw(self.size.width, self.size.height, pixels)
+                .unwrap_or_default(),
+        )
+    }
+}
+
+//= FUNCTIONS ======================================================================================
+
+// Swap the blue and red channels of every pixel, in place.
+fn swizzle_bgra_to_rgba(pixels: &mut [u8]) {
+    for pixel in pixels.chunks_exact_mut(4) {
+        pixel.swap(0, 2);
+    }
+}
+
+//= TESTS ==========================================================================================
+
+#[cfg(test)]
+mod tests {
+    use pollster::FutureExt;
+
+    use super::{swizzle_bgra_to_rgba, FrameCapture};
+    use crate::{
+        device::Device,
+        pipeline::PipelineLayoutBuilder,
+        texture_metadatas::{TextureBindGroupMetadatas, TextureImageMetadatas},
+    };
+
+    // Draw a full screen triangle loading the texel under every pixel, without filtering.
+    const COPY_SHADER: &str = r#"
+[[group(0), binding(0)]]
+var t_diffuse: texture_2d<f32>;
+
+[[stage(vertex)]]
+fn vs_main([[builtin(vertex_index)]] index: u32) -> [[builtin(position)]] vec4<f32> {
+    let corner = vec2<f32>(f32((index << 1u) & 2u), f32(index & 2u));
+    return vec4<f32>(corner * 2.0 - 1.0, 0.0, 1.0);
+}
+
+[[stage(fragment)]]
+fn fs_main([[builtin(position)]] position: vec4<f32>) -> [[location(0)]] vec4<f32> {
+    return textureLoad(t_diffuse, vec2<i32>(position.xy), 0);
+}
+"#;
+
+    #[test]
+    fn swizzle_swaps_blue_and_red_of_every_pixel() {
+        let mut pixels = vec![1, 2, 3, 4, 10, 20, 30, 40];
+        swizzle_bgra_to_rgba(&mut pixels);
+        assert_eq!(pixels, vec![3, 2, 1, 4, 30, 20, 10, 40]);
+    }
+
+    #[test]
+    fn swizzle_ignores_an_incomplete_pixel() {
+        let mut pixels = vec![1, 2, 3, 4, 5, 6];
+        swizzle_bgra_to_rgba(&mut pixels);
+        assert_eq!(pixels, vec![3, 2, 1, 4, 5, 6]);
+    }
+
+    #[test]
+    fn a_bgra_surface_renders_textures_with_the_correct_channel_order() {
+        let instance = wgpu::Instance::new(wgpu::Backends::all());
+        let adapter = match instance
+            .request_adapter(&wgpu::RequestAdapterOptions::default())
+            .block_on()
+        {
+            Some(adapter) => adapter,
+            None => return,
+        };
+        let (device, queue) = match Device::new(
+            &adapter,
+            wgpu::Features::empty(),
+            wgpu::Limits::downlevel_defaults(),
+        ) {
+            Ok(device_queue) => device_queue,
+            Err(_) => return,
+        };
+
+        // Red, green, blue and yellow; fully saturated, so that the sRGB decoding keeps them
+        #[rustfmt::skip]
+        let texels: [u8; 16] = [
+            255, 0, 0, 255,     0, 255, 0, 255,
+            0, 0, 255, 255,     255, 255, 0, 255,
+        ];
+        let size = winit::dpi::PhysicalSize::new(2, 2);
+        let image_metadatas = TextureImageMetadatas::new(&device, size.width, size.height);
+        queue.write_texture_region(
+            image_metadatas.texture(),
+            wgpu::Origin3d::ZERO,
+            &texels,
+            4,
+            *image_metadatas.image_size(),
+        );
+        let bind_group_metadatas =
+            TextureBindGroupMetadatas::new(&device, image_metadatas.texture());
+
+        let format = wgpu::TextureFormat::Bgra8Unorm;
+        let target = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Bgra Target Texture"),
+            size: *image_metadatas.image_size(),
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+        });
+        let target_view = target.create_view(&wgpu::TextureViewDescriptor::default());
+
+        let shader_module = device.create_shader_module(&wgpu::ShaderModuleDescriptor {
+            label: Some("Copy Shader"),
+            source: wgpu::ShaderSource::Wgsl(std::borrow::Cow::Borrowed(COPY_SHADER)),
+        });
+        let pipeline_layout = PipelineLayoutBuilder::new()
+            .with_label("Copy Pipeline Layout")
+            .with_bind_group_layouts(&[bind_group_metadatas.bind_group_layout()])
+            .build(&device);
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Copy Render Pipeline"),
+            layout: Some(&pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &shader_module,
+                entry_point: "vs_main",
+                buffers: &[],
+            },
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            fragment: Some(wgpu::FragmentState {
+                module: &shader_module,
+                entry_point: "fs_main",
+                targets: &[format.into()],
+            }),
+            multiview: None,
+        });
+
+        let capture = FrameCapture::new(&device, size, format).unwrap();
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("Copy Encoder"),
+        });
+        {
+            let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("Copy Pass"),
+                color_attachments: &[wgpu::RenderPassColorAttachment {
+                    view: &target_view,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                        store: true,
+                    },
+                }],
+                depth_stencil_attachment: None,
+            });
+            render_pass.set_pipeline(&pipeline);
+            render_pass.set_bind_group(0, bind_group_metadatas.bind_group(), &[]);
+            render_pass.draw(0..3, 0..1);
+        }
+        capture.copy_from(&mut encoder, &target);
+        queue.submit(std::iter::once(encoder.finish()));
+
+        let image = capture.read(&device).unwrap();
+        assert_eq!(image.into_raw(), texels.to_vec());
+    }
+}
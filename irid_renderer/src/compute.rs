@@ -0,0 +1,86 @@
+//= USES ===========================================================================================
+
+use std::sync::Arc;
+
+use crate::pipeline::ComputePipeline;
+
+//= COMPUTE STAGE ==================================================================================
+
+/// When a compute dispatch is recorded, relative to the render passes of the frame.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ComputeStage {
+    /// Before the render passes, to produce the data drawn in the same frame.
+    BeforeRender,
+    /// After the render passes, to process the data of the frame.
+    AfterRender,
+}
+
+//= COMPUTE DISPATCH ===============================================================================
+
+/// A compute pipeline run over a grid of workgroups, with its bind groups.
+///
+/// The clones share the pipeline and the bind groups, so the same dispatch can be cheaply
+/// enqueued every frame.
+#[derive(Clone, Debug)]
+pub struct ComputeDispatch {
+    label: String,
+    pipeline: Arc<ComputePipeline>,
+    bind_groups: Vec<Arc<wgpu::BindGroup>>,
+    workgroups: [u32; 3],
+}
+
+impl ComputeDispatch {
+    //- Constructors -------------------------------------------------------------------------------
+
+    ///
+    pub fn new(pipeline: Arc<ComputePipeline>, workgroups: [u32; 3]) -> Self {
+        Self {
+            label: "Compute Pass".to_owned(),
+            pipeline,
+            bind_groups: vec![],
+            workgroups,
+        }
+    }
+
+    //- Setters ------------------------------------------------------------------------------------
+
+    /// The label of the compute pass, also used by the GPU profiler.
+    pub fn with_label(mut self, label_text: &str) -> Self {
+        self.label = label_text.to_owned();
+        self
+    }
+
+    /// Add the bind group following the ones already added, starting from index zero.
+    pub fn with_bind_group(mut self, bind_group: wgpu::BindGroup) -> Self {
+        self.bind_groups.push(Arc::new(bind_group));
+        self
+    }
+
+    // Like with_bind_group, for the bind groups also used by other dispatches.
+    pub(crate) fn with_shared_bind_group(mut self, bind_group: Arc<wgpu::BindGroup>) -> Self {
+        self.bind_groups.push(bind_group);
+        self
+    }
+
+    //- Getters ------------------------------------------------------------------------------------
+
+    ///
+    pub fn label(&self) -> &str {
+        &self.label
+    }
+
+    //- Recording ----------------------------------------------------------------------------------
+
+    /// Record the dispatch in its own compute pass.
+    pub(crate) fn record(&self, encoder: &mut wgpu::CommandEncoder) {
+        let mut compute_pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+            label: Some(&self.label),
+        });
+        compute_pass.set_pipeline(self.pipeline.expose_wrapped_compute_pipeline());
+        for (index, bind_group) in self.bind_groups.iter().enumerate() {
+            compute_pass.set_bind_group(index as u32, bind_group, &[]);
+        }
+        let [x, y, z] = self.workgroups;
+        compute_pass.dispatch(x, y, z);
+    }
+}
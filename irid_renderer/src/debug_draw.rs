@@ -0,0 +1,359 @@
+//= USES ===========================================================================================
+
+use std::{
+    mem,
+    time::{Duration, Instant},
+};
+
+use cgmath::{InnerSpace, Point3, Vector3};
+
+use crate::{
+    camera_bind::CameraBindGroup,
+    depth::DepthConfig,
+    device::Device,
+    pipeline::{
+        PipelineLayoutBuilder, PrimitiveStateBuilder, RenderPipeline, RenderPipelineBuilder,
+    },
+    queue::Queue,
+    shader::{DEBUG_LINE_SHADER, DEFAULT_FRAGMENT_ENTRY_POINT, DEFAULT_VERTEX_ENTRY_POINT},
+    stats::FrameStats,
+};
+
+//= CONSTS =========================================================================================
+
+// Number of vertices the vertex buffer can contain when it is first created.
+const INITIAL_VERTICES_CAPACITY: usize = 4096;
+
+// Number of segments used to approximate each circle of a sphere.
+const SPHERE_CIRCLE_SEGMENTS: usize = 32;
+
+// Length of the arrow heads relative to the whole arrow.
+const ARROW_HEAD_RATIO: f32 = 0.2;
+
+//= DEBUG DRAW =====================================================================================
+
+// A line enqueued by the debug draw calls.
+#[derive(Clone, Copy, Debug)]
+struct DebugLine {
+    vertices: [DebugLineVertex; 2],
+    // None for the lines drawn in the next frame only
+    expires_at: Option<Instant>,
+}
+
+/// Collects the debug shapes, as lines, and draws them over the scene every frame until
+/// their duration has elapsed.
+///
+/// The lines are tested against the depth buffer but do not write it, and are drawn only
+/// if the renderer has a camera.
+#[derive(Debug)]
+pub(crate) struct DebugDraw {
+    lines: Vec<DebugLine>,
+    vertex_buffer: wgpu::Buffer,
+    vertices_capacity: usize,
+    vertices_count: u32,
+    pipeline: RenderPipeline,
+}
+
+impl DebugDraw {
+    //- Constructors -------------------------------------------------------------------------------
+
+    ///
+    pub(crate) fn new(
+        device: &Device,
+        color_format: wgpu::TextureFormat,
+        depth: &DepthConfig,
+    ) -> Self {
+        let vertex_buffer = DebugDraw::create_vertex_buffer(device, INITIAL_VERTICES_CAPACITY);
+
+        //- Pipeline -------------------------------------------------------------------------------
+
+        let shader_module = device.create_shader_module(&wgpu::ShaderModuleDescriptor {
+            label: Some("Debug Line Shader"),
+            source: wgpu::ShaderSource::Wgsl(std::borrow::Cow::Borrowed(DEBUG_LINE_SHADER)),
+        });
+
+        let camera_bgl = CameraBindGroup::create_bind_group_layout(device);
+        let pipeline_layout = PipelineLayoutBuilder::new()
+            .with_label("Debug Line Pipeline Layout")
+            .with_bind_group_layouts(&[&camera_bgl])
+            .build(device);
+
+        let vertex_buffers = [DebugLineVertex::desc()];
+
+        let color_targets = [wgpu::ColorTargetState {
+            format: color_format,
+            blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+            write_mask: wgpu::ColorWrites::ALL,
+        }];
+
+        let pipeline = RenderPipelineBuilder::new(wgpu::VertexState {
+            module: &shader_module,
+            entry_point: DEFAULT_VERTEX_ENTRY_POINT,
+            buffers: &vertex_buffers,
+        })
+        .with_label("Debug Line Render Pipeline")
+        .with_layout(&pipeline_layout)
+        .with_primitive(
+            PrimitiveStateBuilder::new()
+                .with_topology(wgpu::PrimitiveTopology::LineList)
+                .build(),
+        )
+        .with_fragment(wgpu::FragmentState {
+            module: &shader_module,
+            entry_point: DEFAULT_FRAGMENT_ENTRY_POINT,
+            targets: &color_targets,
+        })
+        .with_depth_stencil(depth.depth_stencil_state(false, wgpu::CompareFunction::LessEqual))
+        .build(device);
+
+        Self {
+            lines: vec![],
+            vertex_buffer,
+            vertices_capacity: INITIAL_VERTICES_CAPACITY,
+            vertices_count: 0,
+            pipeline,
+        }
+    }
+
+    fn create_vertex_buffer(device: &Device, capacity: usize) -> wgpu::Buffer {
+        device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Debug Line Vertex Buffer"),
+            size: (capacity * mem::size_of::<DebugLineVertex>()) as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        })
+    }
+
+    //- Shapes -------------------------------------------------------------------------------------
+
+    /// Enqueue a segment, drawn only in the next frame if `duration` is None.
+    pub(crate) fn line(
+        &mut self,
+        start: Point3<f32>,
+        end: Point3<f32>,
+        color: [f32; 4],
+        duration: Option<Duration>,
+    ) {
+        self.lines.push(DebugLine {
+            vertices: [
+                DebugLineVertex {
+                    position: start.into(),
+                    color,
+                },
+                DebugLineVertex {
+                    position: end.into(),
+                    color,
+                },
+            ],
+            expires_at: duration.map(|duration| Instant::now() + duration),
+        });
+    }
+
+    /// Enqueue the twelve edges of an axis-aligned box.
+    pub(crate) fn aabb(
+        &mut self,
+        min: Point3<f32>,
+        max: Point3<f32>,
+        color: [f32; 4],
+        duration: Option<Duration>,
+    ) {
+        let corner = |i: usize| {
+            Point3::new(
+                if i & 1 == 0 { min.x } else { max.x },
+                if i & 2 == 0 { min.y } else { max.y },
+                if i & 4 == 0 { min.z } else { max.z },
+            )
+        };
+
+        // Every pair of corners that differs by a single coordinate is an edge
+        for i in 0..8 {
+            for bit in [1, 2, 4].iter() {
+                if i & bit == 0 {
+                    self.line(corner(i), corner(i | bit), color, duration);
+                }
+            }
+        }
+    }
+
+    /// Enqueue three circles, one for each axis plane, approximating a sphere.
+    pub(crate) fn sphere(
+        &mut self,
+        center: Point3<f32>,
+        radius: f32,
+        color: [f32; 4],
+        duration: Option<Duration>,
+    ) {
+        let axes = [
+            (Vector3::unit_x(), Vector3::unit_y()),
+            (Vector3::unit_y(), Vector3::unit_z()),
+            (Vector3::unit_z(), Vector3::unit_x()),
+        ];
+
+        for &(u, v) in axes.iter() {
+            let point = |i: usize| {
+                let angle = i as f32 / SPHERE_CIRCLE_SEGMENTS as f32 * std::f32::consts::TAU;
+                center + (u * angle.cos() + v * angle.sin()) * radius
+            };
+            for i in 0..SPHERE_CIRCLE_SEGMENTS {
+                self.line(point(i), point(i + 1), color, duration);
+            }
+        }
+    }
+
+    /// Enqueue a segment with a head on its end, zero length arrows are ignored.
+    pub(crate) fn arrow(
+        &mut self,
+        start: Point3<f32>,
+        end: Point3<f32>,
+        color: [f32; 4],
+        duration: Option<Duration>,
+    ) {
+        let direction = end - start;
+        let length = direction.magnitude();
+        if length <= f32::EPSILON {
+            return;
+        }
+        let direction = direction / length;
+
+        // Any vector not parallel to the direction gives two perpendicular ones
+        let reference = if direction.y.abs() < 0.99 {
+            Vector3::unit_y()
+        } else {
+            Vector3::unit_x()
+        };
+        let side = direction.cross(reference).normalize();
+        let up = side.cross(direction);
+
+        let head_length = length * ARROW_HEAD_RATIO;
+        let head_base = end - direction * head_length;
+        let head_width = head_length * 0.5;
+
+        self.line(start, end, color, duration);
+        for offset in [side, -side, up, -up].iter() {
+            self.line(end, head_base + offset * head_width, color, duration);
+        }
+    }
+
+    /// Enqueue a square grid on the XZ plane, centered on `center`, with `cells` cells for
+    /// each side.
+    pub(crate) fn grid(
+        &mut self,
+        center: Point3<f32>,
+        cells: u32,
+        spacing: f32,
+        color: [f32; 4],
+        duration: Option<Duration>,
+    ) {
+        let half_size = cells as f32 * spacing * 0.5;
+        for i in 0..=cells {
+            let offset = i as f32 * spacing - half_size;
+            self.line(
+                center + Vector3::new(offset, 0.0, -half_size),
+                center + Vector3::new(offset, 0.0, half_size),
+                color,
+                duration,
+            );
+            self.line(
+                center + Vector3::new(-half_size, 0.0, offset),
+                center + Vector3::new(half_size, 0.0, offset),
+                color,
+                duration,
+            );
+        }
+    }
+
+    /// Enqueue three arrows along the world axes, colored red, green and blue.
+    pub(crate) fn axes(&mut self, origin: Point3<f32>, length: f32, duration: Option<Duration>) {
+        let axes = [
+            (Vector3::unit_x(), [1.0, 0.0, 0.0, 1.0]),
+            (Vector3::unit_y(), [0.0, 1.0, 0.0, 1.0]),
+            (Vector3::unit_z(), [0.0, 0.0, 1.0, 1.0]),
+        ];
+        for &(axis, color) in axes.iter() {
+            self.arrow(origin, origin + axis * length, color, duration);
+        }
+    }
+
+    //- Rendering ----------------------------------------------------------------------------------
+
+    /// Upload the lines to draw in this frame and forget the expired ones, must be called
+    /// before [DebugDraw::draw].
+    pub(crate) fn prepare(&mut self, device: &Device, queue: &Queue) {
+        let vertices = self
+            .lines
+            .iter()
+            .flat_map(|line| line.vertices.iter().copied())
+            .collect::<Vec<_>>();
+        self.vertices_count = vertices.len() as u32;
+
+        let now = Instant::now();
+        self.lines.retain(|line| match line.expires_at {
+            Some(expires_at) => expires_at > now,
+            None => false,
+        });
+
+        if vertices.is_empty() {
+            return;
+        }
+
+        if vertices.len() > self.vertices_capacity {
+            self.vertices_capacity = vertices.len().next_power_of_two();
+            self.vertex_buffer = DebugDraw::create_vertex_buffer(device, self.vertices_capacity);
+        }
+        queue.write_buffer(&self.vertex_buffer, 0, bytemuck::cast_slice(&vertices));
+    }
+
+    /// Record the draw call of the lines prepared for this frame, nothing is drawn without
+    /// the bind group of the scene camera.
+    pub(crate) fn draw<'a>(
+        &'a self,
+        render_pass: &mut wgpu::RenderPass<'a>,
+        camera_bind_group: Option<&'a CameraBindGroup>,
+        stats: &mut FrameStats,
+    ) {
+        let camera_bind_group = match camera_bind_group {
+            Some(camera_bind_group) if self.vertices_count > 0 => camera_bind_group,
+            _ => return,
+        };
+
+        render_pass.set_pipeline(self.pipeline.expose_wrapped_render_pipeline());
+        render_pass.set_bind_group(0, camera_bind_group.bind_group(), &[]);
+        render_pass.set_vertex_buffer(0, self.vertex_buffer.slice(..));
+        render_pass.draw(0..self.vertices_count, 0..1);
+        stats.record_pipeline();
+        stats.record_bind_group();
+        stats.record_draw(self.vertices_count, 1, wgpu::PrimitiveTopology::LineList);
+    }
+}
+
+//= DEBUG LINE VERTEX ==============================================================================
+
+/// A vertex of the debug lines, as it goes into the vertex buffer.
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+pub(crate) struct DebugLineVertex {
+    position: [f32; 3],
+    color: [f32; 4],
+}
+
+impl DebugLineVertex {
+    ///
+    pub(crate) fn desc<'a>() -> wgpu::VertexBufferLayout<'a> {
+        wgpu::VertexBufferLayout {
+            array_stride: mem::size_of::<DebugLineVertex>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Vertex,
+            attributes: &[
+                wgpu::VertexAttribute {
+                    offset: 0,
+                    shader_location: 0,
+                    format: wgpu::VertexFormat::Float32x3,
+                },
+                wgpu::VertexAttribute {
+                    offset: mem::size_of::<[f32; 3]>() as wgpu::BufferAddress,
+                    shader_location: 1,
+                    format: wgpu::VertexFormat::Float32x4,
+                },
+            ],
+        }
+    }
+}
@@ -0,0 +1,287 @@
+//= USES ===========================================================================================
+
+use std::sync::Arc;
+
+use cgmath::{One, Zero};
+
+use crate::{
+    depth::DepthConfig,
+    device::Device,
+    instance::{Instance, InstanceRaw},
+    pipeline::{PipelineCache, PrimitiveStateBuilder, RenderPipeline, RenderPipelineBuilder},
+    shader::{DEBUG_VIEW_SHADER, DEFAULT_VERTEX_ENTRY_POINT},
+};
+
+//= DEBUG VIEW =====================================================================================
+
+/// How the meshes are drawn, see [Renderer::set_debug_view](crate::Renderer::set_debug_view).
+///
+/// The debug views replace the shaders of the meshes with the renderer ones, so they work
+/// with any shader but show the geometry only, moved by the instances transforms.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum DebugView {
+    /// The meshes are drawn by their shaders.
+    #[default]
+    Shaded,
+    /// The edges of the triangles, without filling them.
+    Wireframe,
+    /// The normals of the faces as colors, each axis mapped from [-1, 1] to [0, 1].
+    Normals,
+    /// A checkerboard laid on the texture coordinates, or in world space when the vertices
+    /// have none.
+    TexCoords,
+    /// The depth of the fragments, from white near the camera to black at the far plane.
+    Depth,
+    /// How many times each pixel is drawn, from black to red, yellow and finally white.
+    Overdraw,
+}
+
+//= DEBUG VIEW PIPELINES ===========================================================================
+
+/// The pipelines of the debug views, drawing the vertices of a single layout and the instances.
+#[derive(Debug)]
+pub(crate) struct DebugViewPipelines {
+    wireframe: Arc<RenderPipeline>,
+    // Without the line polygon mode the edges are found by the barycentric coordinates
+    is_barycentric_wireframe: bool,
+    normals: Arc<RenderPipeline>,
+    tex_coords: Arc<RenderPipeline>,
+    depth: Arc<RenderPipeline>,
+    overdraw: Arc<RenderPipeline>,
+    // Bound in place of the instances when the renderer has none
+    identity_instance: wgpu::Buffer,
+}
+
+impl DebugViewPipelines {
+    //- Constructors -------------------------------------------------------------------------------
+
+    /// None if the vertices of the layout have no Float32x3 position at location 0.
+    ///
+    /// The texture coordinates are the first Float32x2 attribute, if any.
+    pub(crate) fn new(
+        device: &Device,
+        pipeline_cache: &mut PipelineCache,
+        vertex_layout: &wgpu::VertexBufferLayout<'_>,
+        camera_bind_group_layout: &Arc<wgpu::BindGroupLayout>,
+        color_format: wgpu::TextureFormat,
+        depth: &DepthConfig,
+    ) -> Option<Self> {
+        let position = *vertex_layout.attributes.iter().find(|attribute| {
+            attribute.shader_location == 0 && attribute.format == wgpu::VertexFormat::Float32x3
+        })?;
+        let tex_coords = vertex_layout
+            .attributes
+            .iter()
+            .find(|attribute| attribute.format == wgpu::VertexFormat::Float32x2)
+            .map(|&attribute| wgpu::VertexAttribute {
+                shader_location: 1,
+                ..attribute
+            });
+
+        //- Vertex States --------------------------------------------------------------------------
+
+        let shader_module =
+            pipeline_cache.shader_module(device, "Debug View Shader", DEBUG_VIEW_SHADER);
+
+        let position_attributes = [position];
+        let position_buffers = [
+            wgpu::VertexBufferLayout {
+                attributes: &position_attributes,
+                ..vertex_layout.clone()
+            },
+            InstanceRaw::desc(),
+        ];
+        let position_state = wgpu::VertexState {
+            module: &shader_module,
+            entry_point: DEFAULT_VERTEX_ENTRY_POINT,
+            buffers: &position_buffers,
+        };
+
+        let tex_coords_attributes = tex_coords.map(|tex_coords| [position, tex_coords]);
+        let tex_coords_buffers = tex_coords_attributes.as_ref().map(|attributes| {
+            [
+                wgpu::VertexBufferLayout {
+                    attributes,
+                    ..vertex_layout.clone()
+                },
+                InstanceRaw::desc(),
+            ]
+        });
+
+        //- Pipelines ------------------------------------------------------------------------------
+
+        let pipeline_layout = pipeline_cache.pipeline_layout(
+            device,
+            "Debug View Pipeline Layout",
+            &[camera_bind_group_layout],
+            &[],
+        );
+
+        let opaque_targets = [wgpu::ColorTargetState {
+            format: color_format,
+            blend: Some(wgpu::BlendState::REPLACE),
+            write_mask: wgpu::ColorWrites::ALL,
+        }];
+        let additive_targets = [wgpu::ColorTargetState {
+            format: color_format,
+            blend: Some(wgpu::BlendState {
+                color: wgpu::BlendComponent {
+                    src_factor: wgpu::BlendFactor::One,
+                    dst_factor: wgpu::BlendFactor::One,
+                    operation: wgpu::BlendOperation::Add,
+                },
+                alpha: wgpu::BlendComponent::REPLACE,
+            }),
+            write_mask: wgpu::ColorWrites::ALL,
+        }];
+
+        let mut build = |label_text: &str,
+                         vertex_state: wgpu::VertexState<'_>,
+                         fragment_entry_point: &str,
+                         primitive: wgpu::PrimitiveState,
+                         targets: &[wgpu::ColorTargetState],
+                         depth_stencil: wgpu::DepthStencilState| {
+            RenderPipelineBuilder::new(vertex_state)
+                .with_label(label_text)
+                .with_layout(&pipeline_layout)
+                .with_primitive(primitive)
+                .with_fragment(wgpu::FragmentState {
+                    module: &shader_module,
+                    entry_point: fragment_entry_point,
+                    targets,
+                })
+                .with_depth_stencil(depth_stencil)
+                .build_cached(device, pipeline_cache)
+        };
+
+        let tested = depth.depth_stencil_state(true, wgpu::CompareFunction::LessEqual);
+
+        let is_barycentric_wireframe = !device
+            .features()
+            .contains(wgpu::Features::POLYGON_MODE_LINE);
+        let wireframe = if is_barycentric_wireframe {
+            build(
+                "Debug View Barycentric Wireframe Pipeline",
+                wgpu::VertexState {
+                    entry_point: "vs_barycentric",
+                    ..position_state.clone()
+                },
+                "fs_barycentric",
+                wgpu::PrimitiveState::default(),
+                &opaque_targets,
+                tested.clone(),
+            )
+        } else {
+            build(
+                "Debug View Wireframe Pipeline",
+                position_state.clone(),
+                "fs_wireframe",
+                PrimitiveStateBuilder::new()
+                    .with_polygon_mode(wgpu::PolygonMode::Line)
+                    .build(),
+                &opaque_targets,
+                tested.clone(),
+            )
+        };
+
+        let normals = build(
+            "Debug View Normals Pipeline",
+            position_state.clone(),
+            "fs_normals",
+            wgpu::PrimitiveState::default(),
+            &opaque_targets,
+            tested.clone(),
+        );
+
+        let tex_coords = match tex_coords_buffers.as_ref() {
+            Some(buffers) => build(
+                "Debug View Texture Coordinates Pipeline",
+                wgpu::VertexState {
+                    module: &shader_module,
+                    entry_point: "vs_tex_coords",
+                    buffers,
+                },
+                "fs_tex_coords_checker",
+                wgpu::PrimitiveState::default(),
+                &opaque_targets,
+                tested.clone(),
+            ),
+            None => build(
+                "Debug View World Checker Pipeline",
+                position_state.clone(),
+                "fs_world_checker",
+                wgpu::PrimitiveState::default(),
+                &opaque_targets,
+                tested.clone(),
+            ),
+        };
+
+        let depth_entry_point = if depth.is_reverse_z() {
+            "fs_depth_reversed"
+        } else {
+            "fs_depth"
+        };
+        let depth_pipeline = build(
+            "Debug View Depth Pipeline",
+            position_state.clone(),
+            depth_entry_point,
+            wgpu::PrimitiveState::default(),
+            &opaque_targets,
+            tested,
+        );
+
+        // Every fragment is counted, also the hidden ones
+        let overdraw = build(
+            "Debug View Overdraw Pipeline",
+            position_state,
+            "fs_overdraw",
+            wgpu::PrimitiveState::default(),
+            &additive_targets,
+            depth.depth_stencil_state(false, wgpu::CompareFunction::Always),
+        );
+
+        let identity = Instance::new(cgmath::Vector3::zero(), cgmath::Quaternion::one()).to_raw();
+        let identity_instance = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Debug View Identity Instance"),
+            contents: bytemuck::bytes_of(&identity),
+            usage: wgpu::BufferUsages::VERTEX,
+        });
+
+        Some(Self {
+            wireframe,
+            is_barycentric_wireframe,
+            normals,
+            tex_coords,
+            depth: depth_pipeline,
+            overdraw,
+            identity_instance,
+        })
+    }
+
+    //- Getters ------------------------------------------------------------------------------------
+
+    /// None for [DebugView::Shaded].
+    pub(crate) fn pipeline(&self, view: DebugView) -> Option<&RenderPipeline> {
+        let pipeline = match view {
+            DebugView::Shaded => return None,
+            DebugView::Wireframe => &self.wireframe,
+            DebugView::Normals => &self.normals,
+            DebugView::TexCoords => &self.tex_coords,
+            DebugView::Depth => &self.depth,
+            DebugView::Overdraw => &self.overdraw,
+        };
+        Some(pipeline)
+    }
+
+    /// If the meshes must be drawn without indices, see [Mesh::draw_unindexed].
+    ///
+    /// [Mesh::draw_unindexed]: crate::Mesh::draw_unindexed
+    pub(crate) fn is_unindexed(&self, view: DebugView) -> bool {
+        view == DebugView::Wireframe && self.is_barycentric_wireframe
+    }
+
+    /// A single instance, not moved, to draw the meshes when there are no instances.
+    pub(crate) fn identity_instance(&self) -> wgpu::BufferSlice<'_> {
+        self.identity_instance.slice(..)
+    }
+}
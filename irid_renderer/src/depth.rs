@@ -0,0 +1,134 @@
+//= DEPTH CONFIG ===================================================================================
+
+/// How the depth buffer is created, cleared and tested, given to
+/// [RendererConfig::with_depth](crate::RendererConfig::with_depth).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DepthConfig {
+    format: wgpu::TextureFormat,
+    reverse_z: bool,
+    prepass: bool,
+}
+
+impl Default for DepthConfig {
+    fn default() -> Self {
+        Self {
+            format: wgpu::TextureFormat::Depth32Float,
+            reverse_z: false,
+            prepass: false,
+        }
+    }
+}
+
+impl DepthConfig {
+    //- Constructors -------------------------------------------------------------------------------
+
+    ///
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    //- Setters ------------------------------------------------------------------------------------
+
+    /// A depth format, [Depth24PlusStencil8](wgpu::TextureFormat::Depth24PlusStencil8) to also
+    /// have a stencil buffer. Depth32Float by default.
+    ///
+    /// The renderer fails to build with a format without a depth aspect.
+    #[inline]
+    pub fn with_format(mut self, format: wgpu::TextureFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// Clear the depth to 0.0 and keep the nearest fragments with greater depths, which spreads
+    /// the floating point precision evenly over the distance.
+    ///
+    /// The renderer applies it to the projections of its cameras too, see
+    /// [Camera::set_reverse_z](crate::Camera::set_reverse_z).
+    #[inline]
+    pub fn with_reverse_z(mut self, reverse_z: bool) -> Self {
+        self.reverse_z = reverse_z;
+        self
+    }
+
+    /// Draw the opaque geometry in a depth-only pass before the color one, so that its
+    /// fragments are shaded only once, where they are visible.
+    #[inline]
+    pub fn with_prepass(mut self, prepass: bool) -> Self {
+        self.prepass = prepass;
+        self
+    }
+
+    //- Getters ------------------------------------------------------------------------------------
+
+    ///
+    pub fn format(&self) -> wgpu::TextureFormat {
+        self.format
+    }
+
+    ///
+    pub fn is_reverse_z(&self) -> bool {
+        self.reverse_z
+    }
+
+    ///
+    pub fn has_prepass(&self) -> bool {
+        self.prepass
+    }
+
+    /// If the format has a depth aspect.
+    pub fn has_depth(&self) -> bool {
+        matches!(
+            self.format,
+            wgpu::TextureFormat::Depth32Float
+                | wgpu::TextureFormat::Depth24Plus
+                | wgpu::TextureFormat::Depth24PlusStencil8
+        )
+    }
+
+    /// If the format has a stencil aspect.
+    pub fn has_stencil(&self) -> bool {
+        self.format == wgpu::TextureFormat::Depth24PlusStencil8
+    }
+
+    /// The depth of the far plane, used to clear the depth buffer.
+    pub fn clear_depth(&self) -> f32 {
+        if self.reverse_z {
+            0.0
+        } else {
+            1.0
+        }
+    }
+
+    //- Conversions --------------------------------------------------------------------------------
+
+    /// Convert a compare function meant for depths growing with the distance, flipping it when
+    /// reverse-Z is used.
+    pub fn compare(&self, compare: wgpu::CompareFunction) -> wgpu::CompareFunction {
+        if !self.reverse_z {
+            return compare;
+        }
+        match compare {
+            wgpu::CompareFunction::Less => wgpu::CompareFunction::Greater,
+            wgpu::CompareFunction::LessEqual => wgpu::CompareFunction::GreaterEqual,
+            wgpu::CompareFunction::Greater => wgpu::CompareFunction::Less,
+            wgpu::CompareFunction::GreaterEqual => wgpu::CompareFunction::LessEqual,
+            _ => compare,
+        }
+    }
+
+    /// The depth state of a pipeline drawing in the depth buffer of this config, the compare
+    /// function is converted as by [DepthConfig::compare]. The stencil is left disabled.
+    pub fn depth_stencil_state(
+        &self,
+        depth_write_enabled: bool,
+        depth_compare: wgpu::CompareFunction,
+    ) -> wgpu::DepthStencilState {
+        wgpu::DepthStencilState {
+            format: self.format,
+            depth_write_enabled,
+            depth_compare: self.compare(depth_compare),
+            stencil: wgpu::StencilState::default(),
+            bias: wgpu::DepthBiasState::default(),
+        }
+    }
+}
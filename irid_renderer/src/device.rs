@@ -1,5 +1,10 @@
 //= USES ===========================================================================================
 
+use std::{
+    future::Future,
+    sync::atomic::{AtomicU64, Ordering},
+};
+
 use bytemuck::Pod;
 use pollster::FutureExt;
 
@@ -21,9 +26,17 @@ pub struct Device {
     #[allow(dead_code)]
     label_text: String,
     wgpu_device: wgpu::Device,
+    // Bytes of the resources created so far, never decreased when they are released
+    cumulative_buffer_memory: AtomicU64,
+    cumulative_texture_memory: AtomicU64,
 }
 
 impl Device {
+    const STORAGE_BUFFER_USAGES: wgpu::BufferUsages = wgpu::BufferUsages::STORAGE
+        .union(wgpu::BufferUsages::VERTEX)
+        .union(wgpu::BufferUsages::COPY_SRC)
+        .union(wgpu::BufferUsages::COPY_DST);
+
     //- Constructors -------------------------------------------------------------------------------
 
     /// Create a new Device and Queue given ad adapter.
@@ -54,6 +67,8 @@ impl Device {
         let device = Self {
             label_text,
             wgpu_device,
+            cumulative_buffer_memory: AtomicU64::new(0),
+            cumulative_texture_memory: AtomicU64::new(0),
         };
 
         let queue = Queue::new(wgpu_queue);
@@ -61,14 +76,37 @@ impl Device {
         Ok((device, queue))
     }
 
+    //- Getters ------------------------------------------------------------------------------------
+
+    /// The limits the device has been created with, like the alignment of the dynamic offsets.
+    pub fn limits(&self) -> wgpu::Limits {
+        self.wgpu_device.limits()
+    }
+
+    /// The features enabled on the device.
+    pub fn features(&self) -> wgpu::Features {
+        self.wgpu_device.features()
+    }
+
     //- Object Creation ----------------------------------------------------------------------------
 
+    /// Creates a [Buffer](wgpu::Buffer), its content is not initialized.
+    pub fn create_buffer(&self, buffer_desc: &wgpu::BufferDescriptor<'_>) -> wgpu::Buffer {
+        let _ = self
+            .cumulative_buffer_memory
+            .fetch_add(buffer_desc.size, Ordering::Relaxed);
+        self.wgpu_device.create_buffer(buffer_desc)
+    }
+
     /// Creates a [Buffer](wgpu::Buffer) with data to initialize it.
     pub fn create_buffer_init(
         &self,
         buffer_init_desc: &wgpu::util::BufferInitDescriptor<'_>,
     ) -> wgpu::Buffer {
         use wgpu::util::DeviceExt;
+        let _ = self
+            .cumulative_buffer_memory
+            .fetch_add(buffer_init_desc.contents.len() as u64, Ordering::Relaxed);
         self.wgpu_device.create_buffer_init(buffer_init_desc)
     }
 
@@ -78,13 +116,11 @@ impl Device {
         label_text: &str,
         vertices: &[V],
     ) -> wgpu::Buffer {
-        use wgpu::util::DeviceExt;
-        self.wgpu_device
-            .create_buffer_init(&wgpu::util::BufferInitDescriptor {
-                label: Some(label_text),
-                contents: bytemuck::cast_slice(vertices),
-                usage: wgpu::BufferUsages::VERTEX,
-            })
+        self.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some(label_text),
+            contents: bytemuck::cast_slice(vertices),
+            usage: wgpu::BufferUsages::VERTEX,
+        })
     }
 
     /// Creates a indices Buffer with data to initialize it.
@@ -93,13 +129,42 @@ impl Device {
         label_text: &str,
         indices: &[I],
     ) -> wgpu::Buffer {
-        use wgpu::util::DeviceExt;
-        self.wgpu_device
-            .create_buffer_init(&wgpu::util::BufferInitDescriptor {
-                label: Some(label_text),
-                contents: bytemuck::cast_slice(indices),
-                usage: wgpu::BufferUsages::INDEX,
-            })
+        self.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some(label_text),
+            contents: bytemuck::cast_slice(indices),
+            usage: wgpu::BufferUsages::INDEX,
+        })
+    }
+
+    /// Creates a storage Buffer of `size` bytes, its content is not initialized.
+    ///
+    /// The buffer can also be copied from and to, and be used as a vertex buffer.
+    pub fn create_storage_buffer(
+        &self,
+        label_text: &str,
+        size: wgpu::BufferAddress,
+    ) -> wgpu::Buffer {
+        self.create_buffer(&wgpu::BufferDescriptor {
+            label: Some(label_text),
+            size,
+            usage: Device::STORAGE_BUFFER_USAGES,
+            mapped_at_creation: false,
+        })
+    }
+
+    /// Creates a storage Buffer with data to initialize it.
+    ///
+    /// The buffer can also be copied from and to, and be used as a vertex buffer.
+    pub fn create_storage_buffer_init<T: Pod>(
+        &self,
+        label_text: &str,
+        contents: &[T],
+    ) -> wgpu::Buffer {
+        self.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some(label_text),
+            contents: bytemuck::cast_slice(contents),
+            usage: Device::STORAGE_BUFFER_USAGES,
+        })
     }
 
     /// Creates a [BindGroupLayout](wgpu::BindGroupLayout).
@@ -145,6 +210,15 @@ impl Device {
             .create_render_pipeline(render_pipeline_desc)
     }
 
+    /// Creates a [ComputePipeline](wgpu::ComputePipeline).
+    pub fn create_compute_pipeline(
+        &self,
+        compute_pipeline_desc: &wgpu::ComputePipelineDescriptor<'_>,
+    ) -> wgpu::ComputePipeline {
+        self.wgpu_device
+            .create_compute_pipeline(compute_pipeline_desc)
+    }
+
     /// Creates a [CommandEncoder](wgpu::CommandEncoder).
     pub fn create_command_encoder(
         &self,
@@ -159,6 +233,9 @@ impl Device {
     /// # Param
     /// - texture_desc specifies the general format of the texture.
     pub fn create_texture(&self, texture_desc: &wgpu::TextureDescriptor<'_>) -> wgpu::Texture {
+        let _ = self
+            .cumulative_texture_memory
+            .fetch_add(texture_size(texture_desc), Ordering::Relaxed);
         self.wgpu_device.create_texture(texture_desc)
     }
 
@@ -170,6 +247,46 @@ impl Device {
         self.wgpu_device.create_sampler(sampler_desc)
     }
 
+    /// Creates a new [QuerySet](wgpu::QuerySet).
+    pub fn create_query_set(
+        &self,
+        query_set_desc: &wgpu::QuerySetDescriptor<'_>,
+    ) -> wgpu::QuerySet {
+        self.wgpu_device.create_query_set(query_set_desc)
+    }
+
+    //- Wrapped Methods ----------------------------------------------------------------------------
+
+    /// Check for resource cleanups and mapping callbacks, waiting for the submitted work if
+    /// `maintain` is [Maintain::Wait](wgpu::Maintain::Wait).
+    pub fn poll(&self, maintain: wgpu::Maintain) {
+        self.wgpu_device.poll(maintain);
+    }
+
+    /// Capture the errors of the given type, instead of panicking, until the scope is popped.
+    pub fn push_error_scope(&self, filter: wgpu::ErrorFilter) {
+        self.wgpu_device.push_error_scope(filter);
+    }
+
+    /// The first error captured since the matching [Device::push_error_scope], if any.
+    pub fn pop_error_scope(&self) -> impl Future<Output = Option<wgpu::Error>> + Send {
+        self.wgpu_device.pop_error_scope()
+    }
+
+    //- Getters ------------------------------------------------------------------------------------
+
+    /// Bytes of all the buffers ever created by this device, the ones already released
+    /// included: it only grows and is not the memory currently in use.
+    pub fn cumulative_buffer_memory(&self) -> u64 {
+        self.cumulative_buffer_memory.load(Ordering::Relaxed)
+    }
+
+    /// Bytes of all the textures ever created by this device, the ones already released
+    /// included, an estimation that does not consider the padding and compression of the driver.
+    pub fn cumulative_texture_memory(&self) -> u64 {
+        self.cumulative_texture_memory.load(Ordering::Relaxed)
+    }
+
     //- Crate-Public Methods -----------------------------------------------------------------------
 
     // This method MUST remains public at the crate level.
@@ -177,3 +294,20 @@ impl Device {
         &self.wgpu_device
     }
 }
+
+// Sum of the sizes of every mip level of a texture.
+fn texture_size(texture_desc: &wgpu::TextureDescriptor<'_>) -> u64 {
+    let info = texture_desc.format.describe();
+    let (block_width, block_height) = info.block_dimensions;
+    (0..texture_desc.mip_level_count)
+        .filter_map(|level| texture_desc.mip_level_size(level))
+        .map(|size| {
+            let size = size.physical_size(texture_desc.format);
+            (size.width / block_width as u32) as u64
+                * (size.height / block_height as u32) as u64
+                * size.depth_or_array_layers as u64
+                * info.block_size as u64
+                * texture_desc.sample_count as u64
+        })
+        .sum()
+}
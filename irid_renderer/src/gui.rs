@@ -0,0 +1,677 @@
+//= USES ===========================================================================================
+
+use std::{
+    collections::{HashMap, HashSet},
+    hash::Hash,
+    mem,
+    ops::Range,
+    time::Instant,
+};
+
+use egui::epaint;
+
+use crate::{
+    device::Device,
+    pipeline::{
+        PipelineLayoutBuilder, PrimitiveStateBuilder, RenderPipeline, RenderPipelineBuilder,
+    },
+    queue::Queue,
+    shader::{DEFAULT_FRAGMENT_ENTRY_POINT, DEFAULT_VERTEX_ENTRY_POINT, GUI_SHADER},
+    stats::FrameStats,
+    texture_metadatas::TextureBindGroupMetadatas,
+};
+
+//= CONSTS =========================================================================================
+
+// Points scrolled for every line of a mouse wheel.
+const SCROLL_POINTS_PER_LINE: f32 = 50.0;
+
+// Number of vertices and indices the buffers can contain when they are first created.
+const INITIAL_VERTICES_CAPACITY: usize = 4096;
+const INITIAL_INDICES_CAPACITY: usize = 8192;
+
+//= GUI ============================================================================================
+
+// A texture allocated by egui, like the fonts one.
+#[derive(Debug)]
+struct GuiTexture {
+    texture: wgpu::Texture,
+    bind_group_metadatas: TextureBindGroupMetadatas,
+}
+
+// A mesh ready to be drawn from the shared buffers.
+#[derive(Debug)]
+struct GuiDrawCall {
+    texture_id: egui::TextureId,
+    clip_rect: egui::Rect,
+    indices: Range<u32>,
+    base_vertex: i32,
+}
+
+/// The immediate-mode GUI drawn as an overlay after the scene.
+///
+/// It collects the window events into the egui input, runs the user interface when asked
+/// and paints the resulting meshes with its own pipeline.
+pub(crate) struct Gui {
+    context: egui::Context,
+    raw_input: egui::RawInput,
+    start_time: Instant,
+    scale_factor: f32,
+    pointer_position: egui::Pos2,
+    consumed_buttons: HashSet<winit::event::MouseButton>,
+    consumed_keys: HashSet<winit::event::VirtualKeyCode>,
+    meshes: Vec<epaint::ClippedMesh>,
+    textures_delta: egui::TexturesDelta,
+    textures: HashMap<egui::TextureId, GuiTexture>,
+    draw_calls: Vec<GuiDrawCall>,
+    vertex_buffer: wgpu::Buffer,
+    vertices_capacity: usize,
+    index_buffer: wgpu::Buffer,
+    indices_capacity: usize,
+    screen_buffer: wgpu::Buffer,
+    screen_bind_group: wgpu::BindGroup,
+    pipeline: RenderPipeline,
+}
+
+// egui::Context does not implement Debug
+impl std::fmt::Debug for Gui {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Gui")
+            .field("raw_input", &self.raw_input)
+            .field("scale_factor", &self.scale_factor)
+            .field("textures", &self.textures)
+            .field("draw_calls", &self.draw_calls)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Gui {
+    //- Constructors -------------------------------------------------------------------------------
+
+    ///
+    pub(crate) fn new(
+        device: &Device,
+        scale_factor: f64,
+        color_format: wgpu::TextureFormat,
+    ) -> Self {
+        let vertex_buffer = Gui::create_buffer(
+            device,
+            "Gui Vertex Buffer",
+            INITIAL_VERTICES_CAPACITY * mem::size_of::<GuiVertex>(),
+            wgpu::BufferUsages::VERTEX,
+        );
+        let index_buffer = Gui::create_buffer(
+            device,
+            "Gui Index Buffer",
+            INITIAL_INDICES_CAPACITY * mem::size_of::<u32>(),
+            wgpu::BufferUsages::INDEX,
+        );
+
+        //- Screen Uniform -------------------------------------------------------------------------
+
+        let screen_buffer = Gui::create_buffer(
+            device,
+            "Gui Screen Buffer",
+            mem::size_of::<[f32; 4]>(),
+            wgpu::BufferUsages::UNIFORM,
+        );
+
+        let screen_bind_group_layout =
+            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                entries: &[wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::VERTEX,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                }],
+                label: Some("Gui Screen Bind Group Layout"),
+            });
+
+        let screen_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: &screen_bind_group_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: screen_buffer.as_entire_binding(),
+            }],
+            label: Some("Gui Screen Bind Group"),
+        });
+
+        //- Pipeline -------------------------------------------------------------------------------
+
+        let shader_module = device.create_shader_module(&wgpu::ShaderModuleDescriptor {
+            label: Some("Gui Shader"),
+            source: wgpu::ShaderSource::Wgsl(std::borrow::Cow::Borrowed(GUI_SHADER)),
+        });
+
+        let texture_bgl = TextureBindGroupMetadatas::create_bind_group_layout(device);
+        let pipeline_layout = PipelineLayoutBuilder::new()
+            .with_label("Gui Pipeline Layout")
+            .with_bind_group_layouts(&[&texture_bgl, &screen_bind_group_layout])
+            .build(device);
+
+        let vertex_buffers = [GuiVertex::desc()];
+
+        // egui outputs colors premultiplied by alpha
+        let color_targets = [wgpu::ColorTargetState {
+            format: color_format,
+            blend: Some(wgpu::BlendState {
+                color: wgpu::BlendComponent {
+                    src_factor: wgpu::BlendFactor::One,
+                    dst_factor: wgpu::BlendFactor::OneMinusSrcAlpha,
+                    operation: wgpu::BlendOperation::Add,
+                },
+                alpha: wgpu::BlendComponent {
+                    src_factor: wgpu::BlendFactor::OneMinusDstAlpha,
+                    dst_factor: wgpu::BlendFactor::One,
+                    operation: wgpu::BlendOperation::Add,
+                },
+            }),
+            write_mask: wgpu::ColorWrites::ALL,
+        }];
+
+        let pipeline = RenderPipelineBuilder::new(wgpu::VertexState {
+            module: &shader_module,
+            entry_point: DEFAULT_VERTEX_ENTRY_POINT,
+            buffers: &vertex_buffers,
+        })
+        .with_label("Gui Render Pipeline")
+        .with_layout(&pipeline_layout)
+        // egui does not use a consistent winding order
+        .with_primitive(PrimitiveStateBuilder::new().with_cull_mode(None).build())
+        .with_fragment(wgpu::FragmentState {
+            module: &shader_module,
+            entry_point: DEFAULT_FRAGMENT_ENTRY_POINT,
+            targets: &color_targets,
+        })
+        .build(device);
+
+        Self {
+            context: egui::Context::default(),
+            raw_input: egui::RawInput::default(),
+            start_time: Instant::now(),
+            scale_factor: scale_factor as f32,
+            pointer_position: egui::Pos2::ZERO,
+            consumed_buttons: HashSet::new(),
+            consumed_keys: HashSet::new(),
+            meshes: vec![],
+            textures_delta: egui::TexturesDelta::default(),
+            textures: HashMap::new(),
+            draw_calls: vec![],
+            vertex_buffer,
+            vertices_capacity: INITIAL_VERTICES_CAPACITY,
+            index_buffer,
+            indices_capacity: INITIAL_INDICES_CAPACITY,
+            screen_buffer,
+            screen_bind_group,
+            pipeline,
+        }
+    }
+
+    fn create_buffer(
+        device: &Device,
+        label: &str,
+        size: usize,
+        usage: wgpu::BufferUsages,
+    ) -> wgpu::Buffer {
+        device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some(label),
+            size: size as wgpu::BufferAddress,
+            usage: usage | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        })
+    }
+
+    //- Input --------------------------------------------------------------------------------------
+
+    /// Collect a window event into the input of the next frame.
+    ///
+    /// Returns true if the user interface wants the event for itself, in that case the event
+    /// should not be used by the game logic.
+    ///
+    /// A button or key release is consumed only if its press was consumed too, so the game
+    /// logic always receives the release of what it has seen pressed.
+    pub(crate) fn handle_event(&mut self, event: &winit::event::WindowEvent<'_>) -> bool {
+        use winit::event::WindowEvent;
+
+        match *event {
+            WindowEvent::ScaleFactorChanged { scale_factor, .. } => {
+                self.scale_factor = scale_factor as f32;
+                false
+            }
+
+            WindowEvent::CursorMoved { position, .. } => {
+                self.pointer_position = egui::pos2(
+                    position.x as f32 / self.scale_factor,
+                    position.y as f32 / self.scale_factor,
+                );
+                self.raw_input
+                    .events
+                    .push(egui::Event::PointerMoved(self.pointer_position));
+                self.context.is_using_pointer()
+            }
+
+            WindowEvent::CursorLeft { .. } => {
+                self.raw_input.events.push(egui::Event::PointerGone);
+                false
+            }
+
+            WindowEvent::MouseInput { state, button, .. } => {
+                let pressed = state == winit::event::ElementState::Pressed;
+                let egui_button = match button {
+                    winit::event::MouseButton::Left => egui::PointerButton::Primary,
+                    winit::event::MouseButton::Right => egui::PointerButton::Secondary,
+                    winit::event::MouseButton::Middle => egui::PointerButton::Middle,
+                    winit::event::MouseButton::Other(_) => return false,
+                };
+                self.raw_input.events.push(egui::Event::PointerButton {
+                    pos: self.pointer_position,
+                    button: egui_button,
+                    pressed,
+                    modifiers: self.raw_input.modifiers,
+                });
+                let wants_input = self.context.wants_pointer_input();
+                track_consumed(&mut self.consumed_buttons, button, pressed, wants_input)
+            }
+
+            WindowEvent::MouseWheel { delta, .. } => {
+                let delta = match delta {
+                    winit::event::MouseScrollDelta::LineDelta(x, y) => {
+                        egui::vec2(x, y) * SCROLL_POINTS_PER_LINE
+                    }
+                    winit::event::MouseScrollDelta::PixelDelta(position) => {
+                        egui::vec2(position.x as f32, position.y as f32) / self.scale_factor
+                    }
+                };
+                self.raw_input.events.push(egui::Event::Scroll(delta));
+                self.context.wants_pointer_input()
+            }
+
+            WindowEvent::ReceivedCharacter(c) => {
+                // The control characters are sent as key events
+                if !c.is_control() {
+                    self.raw_input.events.push(egui::Event::Text(c.to_string()));
+                }
+                self.context.wants_keyboard_input()
+            }
+
+            WindowEvent::ModifiersChanged(state) => {
+                self.raw_input.modifiers = egui::Modifiers {
+                    alt: state.alt(),
+                    ctrl: state.ctrl(),
+                    shift: state.shift(),
+                    mac_cmd: cfg!(target_os = "macos") && state.logo(),
+                    command: if cfg!(target_os = "macos") {
+                        state.logo()
+                    } else {
+                        state.ctrl()
+                    },
+                };
+                false
+            }
+
+            WindowEvent::KeyboardInput { input, .. } => {
+                let pressed = input.state == winit::event::ElementState::Pressed;
+                let keycode = match input.virtual_keycode {
+                    Some(keycode) => keycode,
+                    None => return pressed && self.context.wants_keyboard_input(),
+                };
+                let wants_input = self.context.wants_keyboard_input();
+                let consumed = &mut self.consumed_keys;
+                let key = match key_from_virtual_keycode(keycode) {
+                    Some(key) => key,
+                    None => return track_consumed(consumed, keycode, pressed, wants_input),
+                };
+
+                if pressed && self.raw_input.modifiers.command {
+                    match key {
+                        egui::Key::C => self.raw_input.events.push(egui::Event::Copy),
+                        egui::Key::X => self.raw_input.events.push(egui::Event::Cut),
+                        _ => {}
+                    }
+                }
+                self.raw_input.events.push(egui::Event::Key {
+                    key,
+                    pressed,
+                    modifiers: self.raw_input.modifiers,
+                });
+                track_consumed(&mut self.consumed_keys, keycode, pressed, wants_input)
+            }
+
+            _ => false,
+        }
+    }
+
+    //- Frame --------------------------------------------------------------------------------------
+
+    /// Run the user interface with the input collected since the previous frame, the result
+    /// is drawn by the next redraws.
+    pub(crate) fn run<F: FnOnce(&egui::Context)>(
+        &mut self,
+        window_size: winit::dpi::PhysicalSize<u32>,
+        run_ui: F,
+    ) {
+        let mut raw_input = mem::take(&mut self.raw_input);
+        raw_input.screen_rect = Some(egui::Rect::from_min_size(
+            egui::Pos2::ZERO,
+            egui::vec2(
+                window_size.width as f32 / self.scale_factor,
+                window_size.height as f32 / self.scale_factor,
+            ),
+        ));
+        raw_input.pixels_per_point = Some(self.scale_factor);
+        raw_input.time = Some(self.start_time.elapsed().as_secs_f64());
+
+        // The modifiers state persists between the frames
+        self.raw_input.modifiers = raw_input.modifiers;
+
+        let output = self.context.run(raw_input, run_ui);
+        self.textures_delta.append(output.textures_delta);
+        self.meshes = self.context.tessellate(output.shapes);
+    }
+
+    ///
+    pub(crate) fn context(&self) -> &egui::Context {
+        &self.context
+    }
+
+    ///
+    pub(crate) fn scale_factor(&self) -> f64 {
+        f64::from(self.scale_factor)
+    }
+
+    //- Rendering ----------------------------------------------------------------------------------
+
+    /// Apply the texture changes and upload the meshes, must be called before [Gui::draw].
+    pub(crate) fn prepare(
+        &mut self,
+        device: &Device,
+        queue: &Queue,
+        window_size: winit::dpi::PhysicalSize<u32>,
+    ) {
+        for (texture_id, image_delta) in mem::take(&mut self.textures_delta.set) {
+            self.set_texture(device, queue, texture_id, &image_delta);
+        }
+
+        let size_in_points = [
+            window_size.width as f32 / self.scale_factor,
+            window_size.height as f32 / self.scale_factor,
+            0.0,
+            0.0,
+        ];
+        queue.write_buffer(
+            &self.screen_buffer,
+            0,
+            bytemuck::cast_slice(&size_in_points),
+        );
+
+        //- Meshes ---------------------------------------------------------------------------------
+
+        self.draw_calls.clear();
+        let mut vertices: Vec<GuiVertex> = vec![];
+        let mut indices: Vec<u32> = vec![];
+
+        for clipped_mesh in self.meshes.iter() {
+            let mesh = &clipped_mesh.1;
+            if mesh.indices.is_empty() || !self.textures.contains_key(&mesh.texture_id) {
+                continue;
+            }
+
+            let start = indices.len() as u32;
+            self.draw_calls.push(GuiDrawCall {
+                texture_id: mesh.texture_id,
+                clip_rect: clipped_mesh.0,
+                indices: start..start + mesh.indices.len() as u32,
+                base_vertex: vertices.len() as i32,
+            });
+            indices.extend_from_slice(&mesh.indices);
+            vertices.extend(mesh.vertices.iter().map(GuiVertex::from));
+        }
+
+        if self.draw_calls.is_empty() {
+            return;
+        }
+
+        if vertices.len() > self.vertices_capacity {
+            self.vertices_capacity = vertices.len().next_power_of_two();
+            self.vertex_buffer = Gui::create_buffer(
+                device,
+                "Gui Vertex Buffer",
+                self.vertices_capacity * mem::size_of::<GuiVertex>(),
+                wgpu::BufferUsages::VERTEX,
+            );
+        }
+        if indices.len() > self.indices_capacity {
+            self.indices_capacity = indices.len().next_power_of_two();
+            self.index_buffer = Gui::create_buffer(
+                device,
+                "Gui Index Buffer",
+                self.indices_capacity * mem::size_of::<u32>(),
+                wgpu::BufferUsages::INDEX,
+            );
+        }
+
+        queue.write_buffer(&self.vertex_buffer, 0, bytemuck::cast_slice(&vertices));
+        queue.write_buffer(&self.index_buffer, 0, bytemuck::cast_slice(&indices));
+    }
+
+    fn set_texture(
+        &mut self,
+        device: &Device,
+        queue: &Queue,
+        texture_id: egui::TextureId,
+        image_delta: &epaint::ImageDelta,
+    ) {
+        let pixels: Vec<u8> = match image_delta.image {
+            egui::ImageData::Color(ref image) => image
+                .pixels
+                .iter()
+                .flat_map(|color| color.to_array())
+                .collect(),
+            egui::ImageData::Alpha(ref image) => image
+                .srgba_pixels(1.0)
+                .flat_map(|color| color.to_array())
+                .collect(),
+        };
+        let [width, height] = image_delta.image.size();
+        let size = wgpu::Extent3d {
+            width: width as u32,
+            height: height as u32,
+            depth_or_array_layers: 1,
+        };
+
+        let origin = match image_delta.pos {
+            // A partial update of an existing texture
+            Some([x, y]) => wgpu::Origin3d {
+                x: x as u32,
+                y: y as u32,
+                z: 0,
+            },
+            None => {
+                let texture = device.create_texture(&wgpu::TextureDescriptor {
+                    label: Some("Gui Texture"),
+                    format: wgpu::TextureFormat::Rgba8UnormSrgb,
+                    size,
+                    mip_level_count: 1,
+                    sample_count: 1,
+                    dimension: wgpu::TextureDimension::D2,
+                    usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+                });
+                let bind_group_metadatas = TextureBindGroupMetadatas::new(device, &texture);
+                let _ = self.textures.insert(
+                    texture_id,
+                    GuiTexture {
+                        texture,
+                        bind_group_metadatas,
+                    },
+                );
+                wgpu::Origin3d::ZERO
+            }
+        };
+
+        if let Some(gui_texture) = self.textures.get(&texture_id) {
+            queue.write_texture_region(&gui_texture.texture, origin, &pixels, 4, size);
+        }
+    }
+
+    /// Record the draw calls of the meshes prepared for this frame, the render pass must
+    /// not have a depth attachment.
+    pub(crate) fn draw<'a>(
+        &'a self,
+        render_pass: &mut wgpu::RenderPass<'a>,
+        window_size: winit::dpi::PhysicalSize<u32>,
+        stats: &mut FrameStats,
+    ) {
+        if self.draw_calls.is_empty() {
+            return;
+        }
+
+        render_pass.set_pipeline(self.pipeline.expose_wrapped_render_pipeline());
+        render_pass.set_bind_group(1, &self.screen_bind_group, &[]);
+        render_pass.set_vertex_buffer(0, self.vertex_buffer.slice(..));
+        render_pass.set_index_buffer(self.index_buffer.slice(..), wgpu::IndexFormat::Uint32);
+        stats.record_pipeline();
+        stats.record_bind_group();
+
+        for draw_call in self.draw_calls.iter() {
+            // From points to physical pixels, clamped inside the framebuffer
+            let clip_min_x = (draw_call.clip_rect.min.x * self.scale_factor).round() as u32;
+            let clip_min_y = (draw_call.clip_rect.min.y * self.scale_factor).round() as u32;
+            let clip_max_x = (draw_call.clip_rect.max.x * self.scale_factor).round() as u32;
+            let clip_max_y = (draw_call.clip_rect.max.y * self.scale_factor).round() as u32;
+
+            let x = clip_min_x.min(window_size.width);
+            let y = clip_min_y.min(window_size.height);
+            let width = clip_max_x.min(window_size.width).saturating_sub(x);
+            let height = clip_max_y.min(window_size.height).saturating_sub(y);
+            if width == 0 || height == 0 {
+                continue;
+            }
+
+            if let Some(gui_texture) = self.textures.get(&draw_call.texture_id) {
+                render_pass.set_scissor_rect(x, y, width, height);
+                render_pass.set_bind_group(0, gui_texture.bind_group_metadatas.bind_group(), &[]);
+                render_pass.draw_indexed(draw_call.indices.clone(), draw_call.base_vertex, 0..1);
+                stats.record_bind_group();
+                stats.record_draw_indexed(
+                    draw_call.indices.end - draw_call.indices.start,
+                    1,
+                    wgpu::PrimitiveTopology::TriangleList,
+                );
+            }
+        }
+    }
+
+    /// Release the textures that egui does not use anymore, must be called after the frame
+    /// has been submitted.
+    pub(crate) fn free_textures(&mut self) {
+        for texture_id in self.textures_delta.free.drain(..) {
+            let _ = self.textures.remove(&texture_id);
+        }
+    }
+}
+
+//= GUI VERTEX =====================================================================================
+
+/// An egui vertex as it goes into the vertex buffer.
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+pub(crate) struct GuiVertex {
+    position: [f32; 2],
+    tex_coords: [f32; 2],
+    color: [u8; 4],
+}
+
+impl From<&epaint::Vertex> for GuiVertex {
+    fn from(vertex: &epaint::Vertex) -> Self {
+        Self {
+            position: [vertex.pos.x, vertex.pos.y],
+            tex_coords: [vertex.uv.x, vertex.uv.y],
+            color: vertex.color.to_array(),
+        }
+    }
+}
+
+impl GuiVertex {
+    ///
+    pub(crate) fn desc<'a>() -> wgpu::VertexBufferLayout<'a> {
+        wgpu::VertexBufferLayout {
+            array_stride: mem::size_of::<GuiVertex>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Vertex,
+            attributes: &[
+                wgpu::VertexAttribute {
+                    offset: 0,
+                    shader_location: 0,
+                    format: wgpu::VertexFormat::Float32x2,
+                },
+                wgpu::VertexAttribute {
+                    offset: mem::size_of::<[f32; 2]>() as wgpu::BufferAddress,
+                    shader_location: 1,
+                    format: wgpu::VertexFormat::Float32x2,
+                },
+                wgpu::VertexAttribute {
+                    offset: mem::size_of::<[f32; 4]>() as wgpu::BufferAddress,
+                    shader_location: 2,
+                    format: wgpu::VertexFormat::Unorm8x4,
+                },
+            ],
+        }
+    }
+}
+
+//= FUNCTIONS ======================================================================================
+
+// Remember the presses wanted by the user interface, a release is consumed only when its press
+// was consumed.
+fn track_consumed<T: Eq + Hash>(
+    consumed: &mut HashSet<T>,
+    input: T,
+    pressed: bool,
+    wants_input: bool,
+) -> bool {
+    if !pressed {
+        return consumed.remove(&input);
+    }
+    if wants_input {
+        let _ = consumed.insert(input);
+    } else {
+        let _ = consumed.remove(&input);
+    }
+    wants_input
+}
+
+// The keys used by egui to navigate and edit the text.
+fn key_from_virtual_keycode(keycode: winit::event::VirtualKeyCode) -> Option<egui::Key> {
+    use winit::event::VirtualKeyCode;
+
+    Some(match keycode {
+        VirtualKeyCode::Down => egui::Key::ArrowDown,
+        VirtualKeyCode::Left => egui::Key::ArrowLeft,
+        VirtualKeyCode::Right => egui::Key::ArrowRight,
+        VirtualKeyCode::Up => egui::Key::ArrowUp,
+        VirtualKeyCode::Escape => egui::Key::Escape,
+        VirtualKeyCode::Tab => egui::Key::Tab,
+        VirtualKeyCode::Back => egui::Key::Backspace,
+        VirtualKeyCode::Return | VirtualKeyCode::NumpadEnter => egui::Key::Enter,
+        VirtualKeyCode::Space => egui::Key::Space,
+        VirtualKeyCode::Insert => egui::Key::Insert,
+        VirtualKeyCode::Delete => egui::Key::Delete,
+        VirtualKeyCode::Home => egui::Key::Home,
+        VirtualKeyCode::End => egui::Key::End,
+        VirtualKeyCode::PageUp => egui::Key::PageUp,
+        VirtualKeyCode::PageDown => egui::Key::PageDown,
+        VirtualKeyCode::A => egui::Key::A,
+        VirtualKeyCode::C => egui::Key::C,
+        VirtualKeyCode::K => egui::Key::K,
+        VirtualKeyCode::U => egui::Key::U,
+        VirtualKeyCode::V => egui::Key::V,
+        VirtualKeyCode::W => egui::Key::W,
+        VirtualKeyCode::X => egui::Key::X,
+        VirtualKeyCode::Y => egui::Key::Y,
+        VirtualKeyCode::Z => egui::Key::Z,
+        _ => return None,
+    })
+}
@@ -16,6 +16,11 @@ impl Instance {
         Instance { position, rotation }
     }
 
+    ///
+    pub(crate) fn position(&self) -> cgmath::Vector3<f32> {
+        self.position
+    }
+
     /// Convert an Instance to a structure GPU readable.
     pub(crate) fn to_raw(&self) -> InstanceRaw {
         InstanceRaw {
@@ -39,7 +44,6 @@ pub(crate) struct InstanceRaw {
 
 impl InstanceRaw {
     ///
-    #[allow(dead_code)]
     pub(crate) fn desc<'a>() -> wgpu::VertexBufferLayout<'a> {
         use std::mem;
         wgpu::VertexBufferLayout {
@@ -37,23 +37,65 @@ clippy::pattern_type_mismatch,
 
 //= USES ===========================================================================================
 
+pub use egui;
+
+pub use self::adapter::{enumerate_adapters, AdapterDetails, AdapterError, AdapterSelector};
+pub use self::bind_group::*;
+pub use self::buffer::{BufferError, StorageBuffer, UniformBuffer};
 pub use self::camera::*;
+pub use self::capture::CaptureError;
+pub use self::compute::{ComputeDispatch, ComputeStage};
+pub use self::debug_view::DebugView;
+pub use self::depth::DepthConfig;
+pub use self::device::Device;
+pub use self::material::BlendMode;
+pub use self::particles::{ParticleEmitter, ParticleEmitterId};
 pub use self::pipeline::*;
+pub use self::profiler::PassTiming;
+pub use self::recorder::{FrameRecorder, RecordingError, RecordingFormat};
+pub use self::render_target::RenderTarget;
+pub use self::resource::{
+    Handle, Material, Mesh, Resource, ResourceError, ResourcePool, ResourceRegistry, Texture,
+};
 pub use self::renderer::*;
+pub use self::sprite::{Sprite, SpriteTextureId};
+pub use self::stats::FrameStats;
+pub use self::text::FontId;
+pub use self::viewport::{RenderLayers, Viewport, ViewportClear, ViewportId};
 
 //= MODS ===========================================================================================
 
 // Exposed externally through the uses above
+pub(crate) mod bind_group;
 pub(crate) mod camera;
 pub(crate) mod pipeline;
 pub(crate) mod renderer;
 
 // Used only internally
+mod adapter;
+mod buffer;
 mod camera_bind;
+mod capture;
+mod compute;
+mod debug_draw;
+mod debug_view;
+mod depth;
 mod device;
+mod gui;
 mod instance;
+mod material;
+mod particles;
+mod profiler;
 mod queue;
+mod recorder;
+mod render_target;
+mod resource;
 mod shader;
+mod skybox;
+mod sprite;
+mod stats;
 mod surface;
+mod text;
 mod texture_metadatas;
-mod utils;
+mod viewport;
+mod window_surface;
@@ -0,0 +1,83 @@
+//= USES ===========================================================================================
+
+use crate::depth::DepthConfig;
+
+//= BLEND MODE =====================================================================================
+
+/// How the colors of a material are combined with the ones already drawn.
+///
+/// All the modes except [BlendMode::Opaque] are transparent: their draws happen after the
+/// opaque geometry, sorted from the farthest to the nearest, and don't write the depth buffer.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum BlendMode {
+    /// Replace the colors behind, alpha is ignored.
+    #[default]
+    Opaque,
+    /// Classic transparency, the colors are weighted by their alpha.
+    Alpha,
+    /// Like [BlendMode::Alpha], for colors already multiplied by their alpha.
+    Premultiplied,
+    /// Add the colors, weighted by their alpha, to the ones behind. Useful for glows and fire.
+    Additive,
+    /// Multiply the colors behind, to darken them. Useful for shadows and stains.
+    Multiply,
+}
+
+impl BlendMode {
+    /// Every mode, a pipeline is created for each of them.
+    pub(crate) const ALL: [BlendMode; 5] = [
+        Self::Opaque,
+        Self::Alpha,
+        Self::Premultiplied,
+        Self::Additive,
+        Self::Multiply,
+    ];
+
+    //- Getters ------------------------------------------------------------------------------------
+
+    ///
+    pub fn is_transparent(self) -> bool {
+        self != Self::Opaque
+    }
+
+    //- Conversions --------------------------------------------------------------------------------
+
+    /// The blend state of the color targets drawn with this mode.
+    pub fn blend_state(self) -> wgpu::BlendState {
+        match self {
+            Self::Opaque => wgpu::BlendState::REPLACE,
+            Self::Alpha => wgpu::BlendState::ALPHA_BLENDING,
+            Self::Premultiplied => wgpu::BlendState::PREMULTIPLIED_ALPHA_BLENDING,
+            Self::Additive => wgpu::BlendState {
+                color: wgpu::BlendComponent {
+                    src_factor: wgpu::BlendFactor::SrcAlpha,
+                    dst_factor: wgpu::BlendFactor::One,
+                    operation: wgpu::BlendOperation::Add,
+                },
+                alpha: wgpu::BlendComponent {
+                    src_factor: wgpu::BlendFactor::Zero,
+                    dst_factor: wgpu::BlendFactor::One,
+                    operation: wgpu::BlendOperation::Add,
+                },
+            },
+            Self::Multiply => wgpu::BlendState {
+                color: wgpu::BlendComponent {
+                    src_factor: wgpu::BlendFactor::Dst,
+                    dst_factor: wgpu::BlendFactor::Zero,
+                    operation: wgpu::BlendOperation::Add,
+                },
+                alpha: wgpu::BlendComponent {
+                    src_factor: wgpu::BlendFactor::Zero,
+                    dst_factor: wgpu::BlendFactor::One,
+                    operation: wgpu::BlendOperation::Add,
+                },
+            },
+        }
+    }
+
+    /// The depth state of the pipelines drawn with this mode: the transparent ones are tested
+    /// against the depth buffer without writing it, to not hide what is drawn behind them.
+    pub fn depth_stencil_state(self, depth: &DepthConfig) -> wgpu::DepthStencilState {
+        depth.depth_stencil_state(!self.is_transparent(), wgpu::CompareFunction::Less)
+    }
+}
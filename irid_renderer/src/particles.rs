@@ -0,0 +1,684 @@
+//= USES ===========================================================================================
+
+use std::{mem, sync::Arc};
+
+use cgmath::{InnerSpace, Point3, Vector3};
+
+use crate::{
+    bind_group::{BindGroupBuilder, BindGroupLayoutBuilder},
+    camera_bind::CameraBindGroup,
+    compute::ComputeDispatch,
+    depth::DepthConfig,
+    device::Device,
+    pipeline::{ComputePipeline, ComputePipelineBuilder, PipelineLayoutBuilder},
+    pipeline::{RenderPipeline, RenderPipelineBuilder},
+    queue::Queue,
+    shader::{DEFAULT_FRAGMENT_ENTRY_POINT, DEFAULT_VERTEX_ENTRY_POINT},
+    shader::{PARTICLE_SHADER, PARTICLE_SIMULATION_SHADER},
+    stats::FrameStats,
+};
+
+//= CONSTS =========================================================================================
+
+// Must match the workgroup size declared by the simulation shader.
+const WORKGROUP_SIZE: u32 = 64;
+
+// Number of keys of every curve, evenly spaced over the particle life.
+const CURVE_KEYS: usize = 4;
+
+// Vertices of the two triangles of every billboard, generated by the shader.
+const BILLBOARD_VERTICES: u32 = 6;
+
+//= PARTICLE EMITTER ID ============================================================================
+
+/// Identifies an emitter added with
+/// [Renderer::add_particle_emitter](crate::Renderer::add_particle_emitter).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct ParticleEmitterId(usize);
+
+//= PARTICLE EMITTER ===============================================================================
+
+/// The settings of a source of particles, given to
+/// [Renderer::add_particle_emitter](crate::Renderer::add_particle_emitter).
+///
+/// The curves are sampled over the life of each particle, their keys are evenly spaced
+/// between its birth and its death.
+#[derive(Clone, Debug)]
+pub struct ParticleEmitter {
+    position: Point3<f32>,
+    max_particles: u32,
+    rate: f32,
+    burst: u32,
+    lifetime: [f32; 2],
+    velocity: Vector3<f32>,
+    velocity_spread: f32,
+    gravity: Vector3<f32>,
+    color_curve: [[f32; 4]; CURVE_KEYS],
+    size_curve: [f32; CURVE_KEYS],
+    velocity_curve: [f32; CURVE_KEYS],
+}
+
+impl Default for ParticleEmitter {
+    fn default() -> Self {
+        Self {
+            position: Point3::new(0.0, 0.0, 0.0),
+            max_particles: 1024,
+            rate: 50.0,
+            burst: 0,
+            lifetime: [1.0, 2.0],
+            velocity: Vector3::new(0.0, 2.0, 0.0),
+            velocity_spread: 0.5,
+            gravity: Vector3::new(0.0, -9.81, 0.0),
+            color_curve: [[1.0, 1.0, 1.0, 1.0]; CURVE_KEYS],
+            size_curve: [0.1; CURVE_KEYS],
+            velocity_curve: [1.0; CURVE_KEYS],
+        }
+    }
+}
+
+impl ParticleEmitter {
+    //- Constructors -------------------------------------------------------------------------------
+
+    ///
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    //- Setters ------------------------------------------------------------------------------------
+
+    /// World position where the particles are spawned.
+    #[inline]
+    pub fn with_position(mut self, position: Point3<f32>) -> Self {
+        self.position = position;
+        self
+    }
+
+    /// Number of particles alive at the same time, the oldest ones are replaced when exceeded.
+    #[inline]
+    pub fn with_max_particles(mut self, max_particles: u32) -> Self {
+        self.max_particles = max_particles.max(1);
+        self
+    }
+
+    /// Particles spawned every second.
+    #[inline]
+    pub fn with_rate(mut self, rate: f32) -> Self {
+        self.rate = rate.max(0.0);
+        self
+    }
+
+    /// Particles spawned all at once when the emitter is added.
+    #[inline]
+    pub fn with_burst(mut self, burst: u32) -> Self {
+        self.burst = burst;
+        self
+    }
+
+    /// Range, in seconds, of the random life of every particle.
+    #[inline]
+    pub fn with_lifetime(mut self, min: f32, max: f32) -> Self {
+        let min = min.max(f32::EPSILON);
+        self.lifetime = [min, max.max(min)];
+        self
+    }
+
+    /// Initial velocity, with a random deviation in every direction up to `spread`.
+    #[inline]
+    pub fn with_velocity(mut self, velocity: Vector3<f32>, spread: f32) -> Self {
+        self.velocity = velocity;
+        self.velocity_spread = spread.max(0.0);
+        self
+    }
+
+    /// Acceleration applied to every particle.
+    #[inline]
+    pub fn with_gravity(mut self, gravity: Vector3<f32>) -> Self {
+        self.gravity = gravity;
+        self
+    }
+
+    /// RGBA colors, alpha included, over the particle life. Ignored if empty.
+    pub fn with_color_curve(mut self, keys: &[[f32; 4]]) -> Self {
+        if !keys.is_empty() {
+            for channel in 0..4 {
+                let channel_keys = keys.iter().map(|key| key[channel]).collect::<Vec<_>>();
+                let resampled = resample_curve(&channel_keys);
+                for (color, value) in self.color_curve.iter_mut().zip(resampled.iter()) {
+                    color[channel] = *value;
+                }
+            }
+        }
+        self
+    }
+
+    /// Sizes of the billboards, in world units, over the particle life. Ignored if empty.
+    pub fn with_size_curve(mut self, keys: &[f32]) -> Self {
+        if !keys.is_empty() {
+            self.size_curve = resample_curve(keys);
+        }
+        self
+    }
+
+    /// Factors multiplying the particle velocity over its life. Ignored if empty.
+    pub fn with_velocity_curve(mut self, keys: &[f32]) -> Self {
+        if !keys.is_empty() {
+            self.velocity_curve = resample_curve(keys);
+        }
+        self
+    }
+
+    //- Getters ------------------------------------------------------------------------------------
+
+    ///
+    pub fn position(&self) -> Point3<f32> {
+        self.position
+    }
+
+    ///
+    pub fn max_particles(&self) -> u32 {
+        self.max_particles
+    }
+}
+
+// Resample the evenly spaced keys of a curve to the fixed number used by the shaders.
+fn resample_curve(keys: &[f32]) -> [f32; CURVE_KEYS] {
+    let mut resampled = [0.0; CURVE_KEYS];
+    for (i, value) in resampled.iter_mut().enumerate() {
+        *value = sample_curve(keys, i as f32 / (CURVE_KEYS - 1) as f32);
+    }
+    resampled
+}
+
+// Piecewise linear interpolation of evenly spaced keys, like the one done by the shaders.
+fn sample_curve(keys: &[f32], t: f32) -> f32 {
+    match keys.len() {
+        0 => 0.0,
+        1 => keys[0],
+        len => {
+            let x = t.clamp(0.0, 1.0) * (len - 1) as f32;
+            let index = (x.floor() as usize).min(len - 2);
+            let fraction = x - index as f32;
+            keys[index] + (keys[index + 1] - keys[index]) * fraction
+        }
+    }
+}
+
+//= PARTICLE SYSTEM ================================================================================
+
+// The GPU resources and the spawning state of an emitter.
+#[derive(Debug)]
+struct EmitterState {
+    emitter: ParticleEmitter,
+    particles_buffer: wgpu::Buffer,
+    appearance_buffer: wgpu::Buffer,
+    appearance_bind_group: wgpu::BindGroup,
+    simulation_buffer: Option<wgpu::Buffer>,
+    simulation_bind_group: Option<Arc<wgpu::BindGroup>>,
+    // The copy simulated by the CPU, when compute shaders are not available
+    cpu_particles: Option<Vec<ParticleRaw>>,
+    next_slot: u32,
+    emission_accumulator: f32,
+    pending_burst: u32,
+}
+
+// A xorshift generator, good enough to scatter the particles.
+#[derive(Debug)]
+struct Random(u32);
+
+impl Random {
+    fn next_f32(&mut self) -> f32 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 17;
+        self.0 ^= self.0 << 5;
+        (self.0 >> 8) as f32 / (1 << 24) as f32
+    }
+
+    fn range(&mut self, min: f32, max: f32) -> f32 {
+        min + (max - min) * self.next_f32()
+    }
+
+    // A random point inside the unit sphere.
+    fn in_unit_sphere(&mut self) -> Vector3<f32> {
+        loop {
+            let point = Vector3::new(
+                self.range(-1.0, 1.0),
+                self.range(-1.0, 1.0),
+                self.range(-1.0, 1.0),
+            );
+            if point.magnitude2() <= 1.0 {
+                return point;
+            }
+        }
+    }
+}
+
+/// Spawns the particles of the emitters on the CPU, simulates them with a compute shader, or
+/// on the CPU if the adapter lacks compute support, and draws them as billboards facing the
+/// camera.
+#[derive(Debug)]
+pub(crate) struct ParticleSystem {
+    emitters: Vec<Option<EmitterState>>,
+    appearance_bgl: wgpu::BindGroupLayout,
+    simulation_bgl: Option<wgpu::BindGroupLayout>,
+    simulation_pipeline: Option<Arc<ComputePipeline>>,
+    render_pipeline: RenderPipeline,
+    random: Random,
+}
+
+impl ParticleSystem {
+    //- Constructors -------------------------------------------------------------------------------
+
+    /// Without `use_compute` the particles are simulated by the CPU and uploaded every frame.
+    pub(crate) fn new(
+        device: &Device,
+        color_format: wgpu::TextureFormat,
+        depth: &DepthConfig,
+        use_compute: bool,
+    ) -> Self {
+        //- Simulation -----------------------------------------------------------------------------
+
+        let (simulation_bgl, simulation_pipeline) = if use_compute {
+            let shader_module = device.create_shader_module(&wgpu::ShaderModuleDescriptor {
+                label: Some("Particle Simulation Shader"),
+                source: wgpu::ShaderSource::Wgsl(std::borrow::Cow::Borrowed(
+                    PARTICLE_SIMULATION_SHADER,
+                )),
+            });
+
+            let simulation_bgl = BindGroupLayoutBuilder::new()
+                .with_label("Particle Simulation Bind Group Layout")
+                .with_storage(0, wgpu::ShaderStages::COMPUTE, false)
+                .with_uniform(1, wgpu::ShaderStages::COMPUTE)
+                .build(device);
+            let pipeline_layout = PipelineLayoutBuilder::new()
+                .with_label("Particle Simulation Pipeline Layout")
+                .with_bind_group_layouts(&[&simulation_bgl])
+                .build(device);
+
+            let pipeline = ComputePipelineBuilder::new(&shader_module)
+                .with_label("Particle Simulation Pipeline")
+                .with_layout(&pipeline_layout)
+                .build(device);
+            (Some(simulation_bgl), Some(Arc::new(pipeline)))
+        } else {
+            log::info!("Compute shaders are not supported, the particles are simulated by the CPU");
+            (None, None)
+        };
+
+        //- Rendering ------------------------------------------------------------------------------
+
+        let shader_module = device.create_shader_module(&wgpu::ShaderModuleDescriptor {
+            label: Some("Particle Shader"),
+            source: wgpu::ShaderSource::Wgsl(std::borrow::Cow::Borrowed(PARTICLE_SHADER)),
+        });
+
+        let appearance_bgl = BindGroupLayoutBuilder::new()
+            .with_label("Particle Appearance Bind Group Layout")
+            .with_uniform(0, wgpu::ShaderStages::VERTEX)
+            .build(device);
+        let camera_bgl = CameraBindGroup::create_bind_group_layout(device);
+        let pipeline_layout = PipelineLayoutBuilder::new()
+            .with_label("Particle Pipeline Layout")
+            .with_bind_group_layouts(&[&appearance_bgl, &camera_bgl])
+            .build(device);
+
+        let vertex_buffers = [ParticleRaw::desc()];
+
+        let color_targets = [wgpu::ColorTargetState {
+            format: color_format,
+            blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+            write_mask: wgpu::ColorWrites::ALL,
+        }];
+
+        // Tested against the depth buffer but without writing it, to not hide each other
+        let render_pipeline = RenderPipelineBuilder::new(wgpu::VertexState {
+            module: &shader_module,
+            entry_point: DEFAULT_VERTEX_ENTRY_POINT,
+            buffers: &vertex_buffers,
+        })
+        .with_label("Particle Render Pipeline")
+        .with_layout(&pipeline_layout)
+        .with_fragment(wgpu::FragmentState {
+            module: &shader_module,
+            entry_point: DEFAULT_FRAGMENT_ENTRY_POINT,
+            targets: &color_targets,
+        })
+        .with_depth_stencil(depth.depth_stencil_state(false, wgpu::CompareFunction::LessEqual))
+        .build(device);
+
+        Self {
+            emitters: vec![],
+            appearance_bgl,
+            simulation_bgl,
+            simulation_pipeline,
+            render_pipeline,
+            random: Random(0x2545_f491),
+        }
+    }
+
+    //- Getters ------------------------------------------------------------------------------------
+
+    /// If the particles are simulated by a compute shader.
+    pub(crate) fn is_simulated_on_gpu(&self) -> bool {
+        self.simulation_pipeline.is_some()
+    }
+
+    //- Emitters -----------------------------------------------------------------------------------
+
+    ///
+    pub(crate) fn add_emitter(
+        &mut self,
+        device: &Device,
+        emitter: ParticleEmitter,
+    ) -> ParticleEmitterId {
+        let particles = vec![ParticleRaw::default(); emitter.max_particles as usize];
+        let particles_buffer = if self.is_simulated_on_gpu() {
+            device.create_storage_buffer_init("Particles Buffer", &particles)
+        } else {
+            device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("Particles Buffer"),
+                contents: bytemuck::cast_slice(&particles),
+                usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+            })
+        };
+
+        let appearance_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Particle Appearance Buffer"),
+            contents: bytemuck::cast_slice(&[AppearanceUniform::default()]),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        });
+        let appearance_bind_group = BindGroupBuilder::new(&self.appearance_bgl)
+            .with_label("Particle Appearance Bind Group")
+            .with_buffer(0, &appearance_buffer)
+            .build(device);
+
+        let (simulation_buffer, simulation_bind_group) = match self.simulation_bgl.as_ref() {
+            Some(simulation_bgl) => {
+                let simulation_buffer =
+                    device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                        label: Some("Particle Simulation Buffer"),
+                        contents: bytemuck::cast_slice(&[SimulationUniform::default()]),
+                        usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+                    });
+                let simulation_bind_group = BindGroupBuilder::new(simulation_bgl)
+                    .with_label("Particle Simulation Bind Group")
+                    .with_buffer(0, &particles_buffer)
+                    .with_buffer(1, &simulation_buffer)
+                    .build(device);
+                (
+                    Some(simulation_buffer),
+                    Some(Arc::new(simulation_bind_group)),
+                )
+            }
+            None => (None, None),
+        };
+
+        let cpu_particles = if self.is_simulated_on_gpu() {
+            None
+        } else {
+            Some(particles)
+        };
+
+        let pending_burst = emitter.burst;
+        self.emitters.push(Some(EmitterState {
+            emitter,
+            particles_buffer,
+            appearance_buffer,
+            appearance_bind_group,
+            simulation_buffer,
+            simulation_bind_group,
+            cpu_particles,
+            next_slot: 0,
+            emission_accumulator: 0.0,
+            pending_burst,
+        }));
+        ParticleEmitterId(self.emitters.len() - 1)
+    }
+
+    /// Add again the emitters of a system whose device has been lost, with the same ids; their
+    /// particles are spawned from scratch.
+    pub(crate) fn restore_emitters(&mut self, device: &Device, lost: &mut ParticleSystem) {
+        for state in mem::take(&mut lost.emitters) {
+            match state {
+                Some(state) => {
+                    let _ = self.add_emitter(device, state.emitter);
+                }
+                None => self.emitters.push(None),
+            }
+        }
+    }
+
+    /// Remove the emitter and its particles, unknown ids are ignored.
+    pub(crate) fn remove_emitter(&mut self, id: ParticleEmitterId) {
+        if let Some(state) = self.emitters.get_mut(id.0) {
+            *state = None;
+        }
+    }
+
+    /// Move the emitter, the particles already spawned are not affected.
+    pub(crate) fn set_emitter_position(&mut self, id: ParticleEmitterId, position: Point3<f32>) {
+        if let Some(state) = self.emitters.get_mut(id.0).and_then(Option::as_mut) {
+            state.emitter.position = position;
+        }
+    }
+
+    /// Spawn `count` particles in the next frame, in addition to the ones of the rate.
+    pub(crate) fn burst(&mut self, id: ParticleEmitterId, count: u32) {
+        if let Some(state) = self.emitters.get_mut(id.0).and_then(Option::as_mut) {
+            state.pending_burst = state.pending_burst.saturating_add(count);
+        }
+    }
+
+    //- Simulation ---------------------------------------------------------------------------------
+
+    /// Spawn the new particles and upload the emitters data, returning the compute dispatches
+    /// that simulate the particles on the GPU. Must be called before [ParticleSystem::draw].
+    ///
+    /// The billboards face the camera of the bind group they are drawn with.
+    pub(crate) fn update(&mut self, queue: &Queue, dt: f32) -> Vec<ComputeDispatch> {
+        let random = &mut self.random;
+        let mut dispatches = vec![];
+        for state in self.emitters.iter_mut().flatten() {
+            let emitter = &state.emitter;
+
+            //- Spawning ---------------------------------------------------------------------------
+
+            state.emission_accumulator += emitter.rate * dt;
+            let emitted = state.emission_accumulator.floor();
+            state.emission_accumulator -= emitted;
+            let spawn_count = (emitted as u32)
+                .saturating_add(mem::take(&mut state.pending_burst))
+                .min(emitter.max_particles);
+
+            let spawned = (0..spawn_count)
+                .map(|_| {
+                    let velocity =
+                        emitter.velocity + random.in_unit_sphere() * emitter.velocity_spread;
+                    ParticleRaw {
+                        position: emitter.position.into(),
+                        age: 0.0,
+                        velocity: velocity.into(),
+                        lifetime: random.range(emitter.lifetime[0], emitter.lifetime[1]),
+                    }
+                })
+                .collect::<Vec<_>>();
+
+            // The spawned particles take the slots of the oldest ones, wrapping around
+            let first_slot = state.next_slot as usize;
+            let capacity = emitter.max_particles as usize;
+            let (head, tail) = spawned.split_at(spawned.len().min(capacity - first_slot));
+            state.next_slot = ((first_slot + spawned.len()) % capacity) as u32;
+
+            //- Simulating -------------------------------------------------------------------------
+
+            match state.cpu_particles.as_mut() {
+                Some(cpu_particles) => {
+                    cpu_particles[first_slot..first_slot + head.len()].copy_from_slice(head);
+                    cpu_particles[..tail.len()].copy_from_slice(tail);
+                    for particle in cpu_particles.iter_mut() {
+                        particle.simulate(emitter.gravity, &emitter.velocity_curve, dt);
+                    }
+                    queue.write_buffer(
+                        &state.particles_buffer,
+                        0,
+                        bytemuck::cast_slice(cpu_particles),
+                    );
+                }
+                None => {
+                    let stride = mem::size_of::<ParticleRaw>() as wgpu::BufferAddress;
+                    if !head.is_empty() {
+                        queue.write_buffer(
+                            &state.particles_buffer,
+                            first_slot as wgpu::BufferAddress * stride,
+                            bytemuck::cast_slice(head),
+                        );
+                    }
+                    if !tail.is_empty() {
+                        queue.write_buffer(&state.particles_buffer, 0, bytemuck::cast_slice(tail));
+                    }
+                }
+            }
+
+            if let (Some(simulation_buffer), Some(simulation_bind_group), Some(pipeline)) = (
+                state.simulation_buffer.as_ref(),
+                state.simulation_bind_group.as_ref(),
+                self.simulation_pipeline.as_ref(),
+            ) {
+                let simulation = SimulationUniform {
+                    gravity_dt: [emitter.gravity.x, emitter.gravity.y, emitter.gravity.z, dt],
+                    velocity_curve: emitter.velocity_curve,
+                    count: emitter.max_particles,
+                    _padding: [0; 3],
+                };
+                queue.write_buffer(simulation_buffer, 0, bytemuck::cast_slice(&[simulation]));
+
+                // div_ceil is more recent than the supported Rust versions
+                #[allow(clippy::manual_div_ceil)]
+                let workgroups = (emitter.max_particles + WORKGROUP_SIZE - 1) / WORKGROUP_SIZE;
+                dispatches.push(
+                    ComputeDispatch::new(Arc::clone(pipeline), [workgroups, 1, 1])
+                        .with_label("Particle Simulation Pass")
+                        .with_shared_bind_group(Arc::clone(simulation_bind_group)),
+                );
+            }
+
+            //- Appearance -------------------------------------------------------------------------
+
+            let appearance = AppearanceUniform {
+                colors: emitter.color_curve,
+                sizes: emitter.size_curve,
+            };
+            queue.write_buffer(
+                &state.appearance_buffer,
+                0,
+                bytemuck::cast_slice(&[appearance]),
+            );
+        }
+        dispatches
+    }
+
+    //- Rendering ----------------------------------------------------------------------------------
+
+    /// Record the draw calls of the emitters, nothing is drawn without a camera.
+    pub(crate) fn draw<'a>(
+        &'a self,
+        render_pass: &mut wgpu::RenderPass<'a>,
+        camera_bind_group: Option<&'a CameraBindGroup>,
+        stats: &mut FrameStats,
+    ) {
+        let camera_bind_group = match camera_bind_group {
+            Some(camera_bind_group) if self.emitters.iter().any(Option::is_some) => {
+                camera_bind_group
+            }
+            _ => return,
+        };
+
+        render_pass.set_pipeline(self.render_pipeline.expose_wrapped_render_pipeline());
+        render_pass.set_bind_group(1, camera_bind_group.bind_group(), &[]);
+        stats.record_pipeline();
+        stats.record_bind_group();
+
+        for state in self.emitters.iter().flatten() {
+            let instance_count = state.emitter.max_particles;
+            render_pass.set_bind_group(0, &state.appearance_bind_group, &[]);
+            render_pass.set_vertex_buffer(0, state.particles_buffer.slice(..));
+            render_pass.draw(0..BILLBOARD_VERTICES, 0..instance_count);
+            stats.record_bind_group();
+            stats.record_draw(
+                BILLBOARD_VERTICES,
+                instance_count,
+                wgpu::PrimitiveTopology::TriangleList,
+            );
+        }
+    }
+}
+
+//= PARTICLE FOR SHADERS ===========================================================================
+
+/// A particle as it goes into the storage buffer, read as instance data by the render shader.
+///
+/// The particles whose age reached their lifetime are dead.
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Default, bytemuck::Pod, bytemuck::Zeroable)]
+pub(crate) struct ParticleRaw {
+    position: [f32; 3],
+    age: f32,
+    velocity: [f32; 3],
+    lifetime: f32,
+}
+
+impl ParticleRaw {
+    ///
+    pub(crate) fn desc<'a>() -> wgpu::VertexBufferLayout<'a> {
+        wgpu::VertexBufferLayout {
+            array_stride: mem::size_of::<ParticleRaw>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Instance,
+            // Same locations used by the other instances, the vertices have no attributes
+            attributes: &[
+                wgpu::VertexAttribute {
+                    offset: 0,
+                    shader_location: 5,
+                    format: wgpu::VertexFormat::Float32x4,
+                },
+                wgpu::VertexAttribute {
+                    offset: mem::size_of::<[f32; 4]>() as wgpu::BufferAddress,
+                    shader_location: 6,
+                    format: wgpu::VertexFormat::Float32x4,
+                },
+            ],
+        }
+    }
+
+    // The CPU version of the simulation shader.
+    fn simulate(&mut self, gravity: Vector3<f32>, velocity_curve: &[f32], dt: f32) {
+        if self.age >= self.lifetime {
+            return;
+        }
+        let scale = sample_curve(velocity_curve, self.age / self.lifetime);
+        let velocity = Vector3::from(self.velocity) + gravity * dt;
+        let position = Point3::from(self.position) + velocity * scale * dt;
+        self.velocity = velocity.into();
+        self.position = position.into();
+        self.age += dt;
+    }
+}
+
+//= UNIFORMS FOR SHADERS ===========================================================================
+
+// The emitter data used by the simulation shader.
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Default, bytemuck::Pod, bytemuck::Zeroable)]
+struct SimulationUniform {
+    gravity_dt: [f32; 4],
+    velocity_curve: [f32; 4],
+    count: u32,
+    _padding: [u32; 3],
+}
+
+// The emitter data used by the render shader.
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Default, bytemuck::Pod, bytemuck::Zeroable)]
+struct AppearanceUniform {
+    colors: [[f32; 4]; CURVE_KEYS],
+    sizes: [f32; CURVE_KEYS],
+}
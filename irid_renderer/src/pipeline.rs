@@ -1,6 +1,8 @@
 //= USES ===========================================================================================
 
-use crate::{device::Device, texture_metadatas::TextureDepthMetadatas};
+use std::{collections::HashMap, sync::Arc};
+
+use crate::{depth::DepthConfig, device::Device, shader::DEFAULT_COMPUTE_ENTRY_POINT};
 
 //= RENDERER PIPELINE BUILDER ======================================================================
 
@@ -13,6 +15,8 @@ pub struct RenderPipelineBuilder<'a> {
     vertex: wgpu::VertexState<'a>,
     primitive: Option<wgpu::PrimitiveState>,
     depth_stencil: Option<wgpu::DepthStencilState>,
+    stencil: Option<wgpu::StencilState>,
+    depth_bias: Option<wgpu::DepthBiasState>,
     multisample: Option<wgpu::MultisampleState>,
     fragment: Option<wgpu::FragmentState<'a>>,
 }
@@ -27,6 +31,8 @@ impl<'a> RenderPipelineBuilder<'a> {
             vertex,
             primitive: None,
             depth_stencil: None,
+            stencil: None,
+            depth_bias: None,
             multisample: None,
             fragment: None,
         }
@@ -63,12 +69,30 @@ impl<'a> RenderPipelineBuilder<'a> {
         self
     }
 
-    ///
+    /// Without it the default [DepthConfig] is used, with depth writes and the Less compare.
     pub fn with_depth_stencil(mut self, depth_stencil: wgpu::DepthStencilState) -> Self {
         self.depth_stencil = Some(depth_stencil);
         self
     }
 
+    /// Set the stencil test and operations, the depth format must have a stencil aspect.
+    ///
+    /// It overrides the stencil of [RenderPipelineBuilder::with_depth_stencil], whatever the
+    /// order of the calls.
+    pub fn with_stencil(mut self, stencil: wgpu::StencilState) -> Self {
+        self.stencil = Some(stencil);
+        self
+    }
+
+    /// Set the depth bias, useful to draw decals and shadow maps without z-fighting.
+    ///
+    /// It overrides the bias of [RenderPipelineBuilder::with_depth_stencil], whatever the
+    /// order of the calls.
+    pub fn with_depth_bias(mut self, bias: wgpu::DepthBiasState) -> Self {
+        self.depth_bias = Some(bias);
+        self
+    }
+
     ///
     pub fn with_multisample(mut self, multisample: wgpu::MultisampleState) -> Self {
         self.multisample = Some(multisample);
@@ -83,21 +107,28 @@ impl<'a> RenderPipelineBuilder<'a> {
 
     //- Build --------------------------------------------------------------------------------------
 
-    fn create_default_depth_stencil() -> Option<wgpu::DepthStencilState> {
-        Some(wgpu::DepthStencilState {
-            format: TextureDepthMetadatas::DEPTH_FORMAT,
-            depth_write_enabled: true,
-            depth_compare: wgpu::CompareFunction::Less,
-            stencil: wgpu::StencilState::default(),
-            bias: wgpu::DepthBiasState::default(),
-        })
+    fn create_default_depth_stencil() -> wgpu::DepthStencilState {
+        DepthConfig::default().depth_stencil_state(true, wgpu::CompareFunction::Less)
+    }
+
+    /// The depth-stencil state with the stencil and the bias set apart merged in.
+    fn merged_depth_stencil(&self) -> wgpu::DepthStencilState {
+        let mut depth_stencil = self
+            .depth_stencil
+            .clone()
+            .unwrap_or_else(RenderPipelineBuilder::create_default_depth_stencil);
+        if let Some(stencil) = self.stencil.clone() {
+            depth_stencil.stencil = stencil;
+        }
+        if let Some(bias) = self.depth_bias {
+            depth_stencil.bias = bias;
+        }
+        depth_stencil
     }
 
     ///
     pub fn build(self, device: &Device) -> RenderPipeline {
-        let depth_stencil = self
-            .depth_stencil
-            .or_else(RenderPipelineBuilder::create_default_depth_stencil);
+        let depth_stencil = Some(self.merged_depth_stencil());
 
         let wgpu_render_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
             label: self.label,
@@ -114,6 +145,25 @@ impl<'a> RenderPipelineBuilder<'a> {
             wgpu_render_pipeline,
         }
     }
+
+    /// Return the pipeline of the cache with the same state, building it only the first time.
+    ///
+    /// The pipeline is cached only if its shader modules and layout have been created by
+    /// the cache, otherwise a new one is always built.
+    pub fn build_cached(self, device: &Device, cache: &mut PipelineCache) -> Arc<RenderPipeline> {
+        let key = match cache.key_of(&self) {
+            Some(key) => key,
+            None => return Arc::new(self.build(device)),
+        };
+
+        if let Some(pipeline) = cache.pipelines.get(&key) {
+            return Arc::clone(pipeline);
+        }
+
+        let pipeline = Arc::new(self.build(device));
+        let _ = cache.pipelines.insert(key, Arc::clone(&pipeline));
+        pipeline
+    }
 }
 
 //= RENDERER PIPELINE OBJECT =======================================================================
@@ -135,6 +185,321 @@ impl RenderPipeline {
     }
 }
 
+//= COMPUTE PIPELINE BUILDER =======================================================================
+
+///
+#[derive(Debug)]
+pub struct ComputePipelineBuilder<'a> {
+    label: wgpu::Label<'a>,
+    layout: Option<&'a wgpu::PipelineLayout>,
+    module: &'a wgpu::ShaderModule,
+    entry_point: &'a str,
+}
+
+impl<'a> ComputePipelineBuilder<'a> {
+    //- Constructors -------------------------------------------------------------------------------
+
+    /// The entry point is `cs_main` unless changed with [ComputePipelineBuilder::with_entry_point].
+    pub fn new(module: &'a wgpu::ShaderModule) -> Self {
+        Self {
+            label: None,
+            layout: None,
+            module,
+            entry_point: DEFAULT_COMPUTE_ENTRY_POINT,
+        }
+    }
+
+    //- Setters ------------------------------------------------------------------------------------
+
+    /// Set the debug label of the pipeline.
+    /// This will show up in graphics debuggers for easy identification.
+    pub fn with_label(mut self, label_text: &'a str) -> Self {
+        self.label = if label_text.is_empty() {
+            wgpu::Label::default()
+        } else {
+            Some(label_text)
+        };
+        self
+    }
+
+    /// Without a layout, it is derived from the shader.
+    pub fn with_layout(mut self, layout: &'a wgpu::PipelineLayout) -> Self {
+        self.layout = Some(layout);
+        self
+    }
+
+    ///
+    pub fn with_entry_point(mut self, entry_point: &'a str) -> Self {
+        self.entry_point = entry_point;
+        self
+    }
+
+    //- Build --------------------------------------------------------------------------------------
+
+    ///
+    pub fn build(self, device: &Device) -> ComputePipeline {
+        let wgpu_compute_pipeline =
+            device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+                label: self.label,
+                layout: self.layout,
+                module: self.module,
+                entry_point: self.entry_point,
+            });
+
+        ComputePipeline {
+            wgpu_compute_pipeline,
+        }
+    }
+}
+
+//= COMPUTE PIPELINE OBJECT ========================================================================
+
+/// Wrapper to the wgpu handle's compute pipeline.
+///
+/// See [`wgpu::ComputePipeline`](wgpu::ComputePipeline).
+#[derive(Debug)]
+pub struct ComputePipeline {
+    wgpu_compute_pipeline: wgpu::ComputePipeline,
+}
+
+impl ComputePipeline {
+    //- Getters ------------------------------------------------------------------------------------
+
+    /// Get the layout of a bind group, useful when the layout has been derived from the shader.
+    pub fn get_bind_group_layout(&self, index: u32) -> wgpu::BindGroupLayout {
+        self.wgpu_compute_pipeline.get_bind_group_layout(index)
+    }
+
+    //- Crate-Public Methods -----------------------------------------------------------------------
+
+    // This method MUST remains public at the crate level.
+    pub(crate) fn expose_wrapped_compute_pipeline(&self) -> &wgpu::ComputePipeline {
+        &self.wgpu_compute_pipeline
+    }
+}
+
+//= PIPELINE CACHE =================================================================================
+
+/// Shares the render pipelines built with the same shaders, layout and states.
+///
+/// Shader modules are deduplicated by their source and pipeline layouts by their bind group
+/// layouts and push constant ranges. Nothing is persisted between runs: wgpu gives no access
+/// to the compiled shaders nor to a driver pipeline cache.
+#[derive(Debug, Default)]
+pub struct PipelineCache {
+    // Kept alive so that their addresses, used in the keys, are never reused
+    shader_modules: HashMap<String, Arc<wgpu::ShaderModule>>,
+    // The bind group layouts are kept alive for the same reason
+    pipeline_layouts:
+        HashMap<PipelineLayoutKey, (Arc<wgpu::PipelineLayout>, Vec<Arc<wgpu::BindGroupLayout>>)>,
+    pipelines: HashMap<RenderPipelineKey, Arc<RenderPipeline>>,
+}
+
+impl PipelineCache {
+    //- Constructors -------------------------------------------------------------------------------
+
+    ///
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    //- Getters ------------------------------------------------------------------------------------
+
+    /// Number of cached pipelines.
+    pub fn pipelines_len(&self) -> usize {
+        self.pipelines.len()
+    }
+
+    /// Number of cached shader modules.
+    pub fn shader_modules_len(&self) -> usize {
+        self.shader_modules.len()
+    }
+
+    /// Number of cached pipeline layouts.
+    pub fn pipeline_layouts_len(&self) -> usize {
+        self.pipeline_layouts.len()
+    }
+
+    //- Resources ----------------------------------------------------------------------------------
+
+    /// Return the shader module created from the same WGSL source, or create a new one.
+    pub fn shader_module(
+        &mut self,
+        device: &Device,
+        label_text: &str,
+        wgsl_source: &str,
+    ) -> Arc<wgpu::ShaderModule> {
+        if let Some(shader_module) = self.shader_modules.get(wgsl_source) {
+            return Arc::clone(shader_module);
+        }
+
+        let shader_module = Arc::new(device.create_shader_module(&wgpu::ShaderModuleDescriptor {
+            label: Some(label_text),
+            source: wgpu::ShaderSource::Wgsl(std::borrow::Cow::Owned(wgsl_source.to_owned())),
+        }));
+        let _ = self
+            .shader_modules
+            .insert(wgsl_source.to_owned(), Arc::clone(&shader_module));
+        shader_module
+    }
+
+    /// Return the pipeline layout created with the same bind group layouts and push constant
+    /// ranges, or create a new one that can be used by the cached pipelines.
+    ///
+    /// The bind group layouts are identified by their address, the cache keeps them alive
+    /// together with the pipeline layout.
+    pub fn pipeline_layout(
+        &mut self,
+        device: &Device,
+        label_text: &str,
+        bind_group_layouts: &[&Arc<wgpu::BindGroupLayout>],
+        push_constant_ranges: &[wgpu::PushConstantRange],
+    ) -> Arc<wgpu::PipelineLayout> {
+        let key = PipelineLayoutKey {
+            bind_group_layouts: bind_group_layouts
+                .iter()
+                .map(|&bind_group_layout| Arc::as_ptr(bind_group_layout) as usize)
+                .collect(),
+            push_constant_ranges: push_constant_ranges.to_vec(),
+        };
+        if let Some(cached) = self.pipeline_layouts.get(&key) {
+            return Arc::clone(&cached.0);
+        }
+
+        let bind_group_layout_refs = bind_group_layouts
+            .iter()
+            .map(|&bind_group_layout| bind_group_layout.as_ref())
+            .collect::<Vec<_>>();
+        let pipeline_layout = Arc::new(
+            PipelineLayoutBuilder::new()
+                .with_label(label_text)
+                .with_bind_group_layouts(&bind_group_layout_refs)
+                .with_push_constant_ranges(push_constant_ranges)
+                .build(device),
+        );
+        let kept_alive = bind_group_layouts
+            .iter()
+            .map(|&bind_group_layout| Arc::clone(bind_group_layout));
+        let _ = self
+            .pipeline_layouts
+            .insert(key, (Arc::clone(&pipeline_layout), kept_alive.collect()));
+        pipeline_layout
+    }
+
+    /// Forget all the cached resources, those still in use are not destroyed.
+    pub fn clear(&mut self) {
+        self.shader_modules.clear();
+        self.pipeline_layouts.clear();
+        self.pipelines.clear();
+    }
+
+    //- Keys ---------------------------------------------------------------------------------------
+
+    // None if the builder uses a shader module or a layout that is not owned by the cache.
+    fn key_of(&self, builder: &RenderPipelineBuilder<'_>) -> Option<RenderPipelineKey> {
+        let layout = match builder.layout {
+            Some(layout) => Some(self.pipeline_layout_key(layout)?),
+            None => None,
+        };
+
+        let fragment = match builder.fragment.as_ref() {
+            Some(fragment) => Some((
+                self.shader_module_address(fragment.module)?,
+                fragment.entry_point.to_owned(),
+                fragment.targets.to_vec(),
+            )),
+            None => None,
+        };
+
+        let depth_stencil = builder.merged_depth_stencil();
+        let depth_stencil = Some(DepthStencilKey {
+            format: depth_stencil.format,
+            depth_write_enabled: depth_stencil.depth_write_enabled,
+            depth_compare: depth_stencil.depth_compare,
+            stencil: depth_stencil.stencil,
+            bias_constant: depth_stencil.bias.constant,
+            bias_slope_scale: depth_stencil.bias.slope_scale.to_bits(),
+            bias_clamp: depth_stencil.bias.clamp.to_bits(),
+        });
+
+        Some(RenderPipelineKey {
+            layout,
+            vertex_module: self.shader_module_address(builder.vertex.module)?,
+            vertex_entry_point: builder.vertex.entry_point.to_owned(),
+            vertex_buffers: builder
+                .vertex
+                .buffers
+                .iter()
+                .map(|buffer| {
+                    (
+                        buffer.array_stride,
+                        buffer.step_mode,
+                        buffer.attributes.to_vec(),
+                    )
+                })
+                .collect(),
+            primitive: builder.primitive.unwrap_or_default(),
+            depth_stencil,
+            multisample: builder.multisample.unwrap_or_default(),
+            fragment,
+        })
+    }
+
+    fn shader_module_address(&self, shader_module: &wgpu::ShaderModule) -> Option<usize> {
+        self.shader_modules
+            .values()
+            .find(|cached| std::ptr::eq(Arc::as_ptr(cached), shader_module))
+            .map(|cached| Arc::as_ptr(cached) as usize)
+    }
+
+    fn pipeline_layout_key(
+        &self,
+        pipeline_layout: &wgpu::PipelineLayout,
+    ) -> Option<PipelineLayoutKey> {
+        self.pipeline_layouts
+            .iter()
+            .find(|&(_, cached)| std::ptr::eq(Arc::as_ptr(&cached.0), pipeline_layout))
+            .map(|(key, _)| key.clone())
+    }
+}
+
+// What a pipeline layout is created with, the bind group layouts by address.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+struct PipelineLayoutKey {
+    bind_group_layouts: Vec<usize>,
+    push_constant_ranges: Vec<wgpu::PushConstantRange>,
+}
+
+// Everything that identifies a render pipeline, the shader modules by address and the layout
+// by what it has been created with.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+struct RenderPipelineKey {
+    layout: Option<PipelineLayoutKey>,
+    vertex_module: usize,
+    vertex_entry_point: String,
+    vertex_buffers: Vec<(
+        wgpu::BufferAddress,
+        wgpu::VertexStepMode,
+        Vec<wgpu::VertexAttribute>,
+    )>,
+    primitive: wgpu::PrimitiveState,
+    depth_stencil: Option<DepthStencilKey>,
+    multisample: wgpu::MultisampleState,
+    fragment: Option<(usize, String, Vec<wgpu::ColorTargetState>)>,
+}
+
+// The depth bias floats are compared by their bits.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+struct DepthStencilKey {
+    format: wgpu::TextureFormat,
+    depth_write_enabled: bool,
+    depth_compare: wgpu::CompareFunction,
+    stencil: wgpu::StencilState,
+    bias_constant: i32,
+    bias_slope_scale: u32,
+    bias_clamp: u32,
+}
+
 //= PIPELINE LAYOUT BUILDER ========================================================================
 
 ///
@@ -245,8 +610,8 @@ impl PrimitiveStateBuilder {
     }
 
     ///
-    pub fn with_cull_mode(mut self, cull_mode: wgpu::Face) -> Self {
-        self.primitive_state.cull_mode = Some(cull_mode);
+    pub fn with_cull_mode<F: Into<Option<wgpu::Face>>>(mut self, cull_mode: F) -> Self {
+        self.primitive_state.cull_mode = cull_mode.into();
         self
     }
 
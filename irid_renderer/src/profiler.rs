@@ -0,0 +1,269 @@
+//= USES ===========================================================================================
+
+use std::{
+    fmt,
+    future::Future,
+    mem,
+    pin::Pin,
+    ptr,
+    task::{Context, Poll, RawWaker, RawWakerVTable, Waker},
+};
+
+use crate::device::Device;
+
+//= CONSTS =========================================================================================
+
+// Maximum number of passes that can be timed in a single frame.
+const MAX_PASSES: u32 = 16;
+
+// Number of frames whose timestamps can wait to be read back at the same time.
+const FRAMES_IN_FLIGHT: usize = 3;
+
+// The readbacks are polled without being woken up, so their waker does nothing.
+const NOOP_WAKER_VTABLE: RawWakerVTable = RawWakerVTable::new(
+    clone_noop_waker,
+    noop_waker_fn,
+    noop_waker_fn,
+    noop_waker_fn,
+);
+
+//= PASS TIMING ====================================================================================
+
+/// The time spent by the GPU executing a render pass.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PassTiming {
+    label: String,
+    milliseconds: f64,
+}
+
+impl PassTiming {
+    ///
+    pub fn label(&self) -> &str {
+        &self.label
+    }
+
+    ///
+    pub fn milliseconds(&self) -> f64 {
+        self.milliseconds
+    }
+}
+
+//= GPU PROFILER ===================================================================================
+
+type MappingFuture = Pin<Box<dyn Future<Output = Result<(), wgpu::BufferAsyncError>> + Send>>;
+
+// A buffer where the timestamps of a frame are copied to be read back.
+struct Readback {
+    buffer: wgpu::Buffer,
+    labels: Vec<String>,
+    mapping: Option<MappingFuture>,
+}
+
+impl fmt::Debug for Readback {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Readback")
+            .field("buffer", &self.buffer)
+            .field("labels", &self.labels)
+            .field("is_mapping", &self.mapping.is_some())
+            .finish()
+    }
+}
+
+/// Writes timestamps before and after each render pass and reads them back some frames
+/// later, without stalling the rendering.
+///
+/// Needs the [TIMESTAMP_QUERY](wgpu::Features::TIMESTAMP_QUERY) feature.
+pub(crate) struct GpuProfiler {
+    query_set: wgpu::QuerySet,
+    readbacks: Vec<Readback>,
+    // Index of the readback used by the frame being recorded, if any was free
+    current: Option<usize>,
+    // Index of the pass begun and not ended yet, if it has been timed
+    open_pass: Option<u32>,
+    timestamp_period: f32,
+    pass_timings: Vec<PassTiming>,
+}
+
+// The query set does not implement Debug.
+impl fmt::Debug for GpuProfiler {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("GpuProfiler")
+            .field("readbacks", &self.readbacks)
+            .field("current", &self.current)
+            .field("open_pass", &self.open_pass)
+            .field("timestamp_period", &self.timestamp_period)
+            .field("pass_timings", &self.pass_timings)
+            .finish()
+    }
+}
+
+impl GpuProfiler {
+    //- Constructors -------------------------------------------------------------------------------
+
+    ///
+    pub(crate) fn new(device: &Device, timestamp_period: f32) -> Self {
+        let query_set = device.create_query_set(&wgpu::QuerySetDescriptor {
+            label: Some("Gpu Profiler Query Set"),
+            ty: wgpu::QueryType::Timestamp,
+            count: MAX_PASSES * 2,
+        });
+
+        let size = (MAX_PASSES * 2) as wgpu::BufferAddress * mem::size_of::<u64>() as u64;
+        let readbacks = (0..FRAMES_IN_FLIGHT)
+            .map(|_| Readback {
+                buffer: device.create_buffer(&wgpu::BufferDescriptor {
+                    label: Some("Gpu Profiler Readback Buffer"),
+                    size,
+                    usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+                    mapped_at_creation: false,
+                }),
+                labels: Vec::with_capacity(MAX_PASSES as usize),
+                mapping: None,
+            })
+            .collect();
+
+        Self {
+            query_set,
+            readbacks,
+            current: None,
+            open_pass: None,
+            timestamp_period,
+            pass_timings: vec![],
+        }
+    }
+
+    //- Getters ------------------------------------------------------------------------------------
+
+    /// The timings of the last frame that has been read back.
+    pub(crate) fn pass_timings(&self) -> &[PassTiming] {
+        &self.pass_timings
+    }
+
+    //- Profiling ----------------------------------------------------------------------------------
+
+    /// Collect the timestamps that are ready and choose where to write the ones of the new
+    /// frame, that is not profiled if all the readbacks are still in use.
+    pub(crate) fn begin_frame(&mut self, device: &Device) {
+        device.poll(wgpu::Maintain::Poll);
+        self.collect();
+        self.open_pass = None;
+
+        self.current = self
+            .readbacks
+            .iter()
+            .position(|readback| readback.mapping.is_none());
+        if let Some(current) = self.current {
+            self.readbacks[current].labels.clear();
+        }
+    }
+
+    /// Record the timestamp before a pass, must be followed by [GpuProfiler::end_pass].
+    ///
+    /// The passes after the first `MAX_PASSES` of the frame are not timed.
+    pub(crate) fn begin_pass(&mut self, encoder: &mut wgpu::CommandEncoder, label: &str) {
+        self.open_pass = None;
+        let readback = match self.current {
+            Some(current) => &mut self.readbacks[current],
+            None => return,
+        };
+        let pass_index = readback.labels.len() as u32;
+        if pass_index >= MAX_PASSES {
+            return;
+        }
+        readback.labels.push(label.to_owned());
+        encoder.write_timestamp(&self.query_set, pass_index * 2);
+        self.open_pass = Some(pass_index);
+    }
+
+    /// Record the timestamp after the pass started by the last [GpuProfiler::begin_pass], if
+    /// it has been timed.
+    pub(crate) fn end_pass(&mut self, encoder: &mut wgpu::CommandEncoder) {
+        if let Some(pass_index) = self.open_pass.take() {
+            encoder.write_timestamp(&self.query_set, pass_index * 2 + 1);
+        }
+    }
+
+    /// Record the resolution of the frame timestamps into its readback buffer.
+    pub(crate) fn resolve(&self, encoder: &mut wgpu::CommandEncoder) {
+        let readback = match self.current {
+            Some(current) => &self.readbacks[current],
+            None => return,
+        };
+        let query_count = readback.labels.len() as u32 * 2;
+        if query_count == 0 {
+            return;
+        }
+        encoder.resolve_query_set(&self.query_set, 0..query_count, &readback.buffer, 0);
+    }
+
+    /// Start reading back the frame timestamps, must be called after the submit.
+    pub(crate) fn end_frame(&mut self) {
+        let readback = match self.current.take() {
+            Some(current) => &mut self.readbacks[current],
+            None => return,
+        };
+        if readback.labels.is_empty() {
+            return;
+        }
+        readback.mapping = Some(Box::pin(
+            readback.buffer.slice(..).map_async(wgpu::MapMode::Read),
+        ));
+    }
+
+    // Read the timestamps of the readbacks already mapped, without waiting for the others.
+    fn collect(&mut self) {
+        let waker = noop_waker();
+        let mut context = Context::from_waker(&waker);
+        for readback in self.readbacks.iter_mut() {
+            let result = match readback.mapping.as_mut() {
+                Some(mapping) => match mapping.as_mut().poll(&mut context) {
+                    Poll::Ready(result) => result,
+                    Poll::Pending => continue,
+                },
+                None => continue,
+            };
+            readback.mapping = None;
+
+            if let Err(error) = result {
+                log::warn!("Unable to read back the GPU timestamps: {}", error);
+                continue;
+            }
+
+            {
+                let data = readback.buffer.slice(..).get_mapped_range();
+                let timestamps: &[u64] = bytemuck::cast_slice(&data);
+                let period = self.timestamp_period as f64;
+                self.pass_timings = readback
+                    .labels
+                    .iter()
+                    .enumerate()
+                    .map(|(i, label)| {
+                        let ticks = timestamps[i * 2 + 1].saturating_sub(timestamps[i * 2]);
+                        PassTiming {
+                            label: label.clone(),
+                            milliseconds: ticks as f64 * period / 1_000_000.0,
+                        }
+                    })
+                    .collect();
+            }
+            readback.buffer.unmap();
+
+            for timing in self.pass_timings.iter() {
+                log::debug!("GPU pass {}: {:.3} ms", timing.label, timing.milliseconds);
+            }
+        }
+    }
+}
+
+//= FUNCTIONS ======================================================================================
+
+fn noop_waker() -> Waker {
+    // SAFETY: the vtable functions ignore the data pointer
+    unsafe { Waker::from_raw(RawWaker::new(ptr::null(), &NOOP_WAKER_VTABLE)) }
+}
+
+fn clone_noop_waker(_: *const ()) -> RawWaker {
+    RawWaker::new(ptr::null(), &NOOP_WAKER_VTABLE)
+}
+
+fn noop_waker_fn(_: *const ()) {}
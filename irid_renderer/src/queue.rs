@@ -3,13 +3,13 @@
 use std::future::Future;
 use thiserror::Error;
 
-use irid_assets_interface::{Image, ImageSize};
-use irid_assets::DiffuseTexture;
+use irid_assets_interface::Image;
+use irid_assets::{CubemapFace, CubemapTexture, DiffuseTexture, GlyphBitmap};
 
 use crate::camera::Camera;
-use crate::camera_bind::CameraBindGroup;
-use crate::texture_metadatas::TextureImageMetadatas;
-use crate::utils::log2;
+use crate::camera_bind::{CameraBindGroup, CameraUniform};
+use crate::skybox::SkyboxView;
+use crate::texture_metadatas::{TextureCubeMetadatas, TextureImageMetadatas};
 
 //= ERRORS =========================================================================================
 
@@ -49,29 +49,22 @@ impl Queue {
         camera: &C,
         camera_metadatas: &CameraBindGroup,
     ) {
-        let mut camera_uniform = *camera_metadatas.uniform();
-        camera_uniform.update_view_proj(camera);
-        self.wgpu_queue.write_buffer(
-            camera_metadatas.buffer(),
-            0,
-            bytemuck::cast_slice(&[camera_uniform]),
-        );
+        camera_metadatas
+            .uniform_buffer()
+            .write(self, &CameraUniform::new(camera));
     }
 
-    /// Schedule a data write into texture.
+    /// Schedule a data write into the texture of the given metadatas, which must have the
+    /// same size of the texture image.
     ///
     /// This method is intended to have low performance costs.
     /// As such, the write is not immediately submitted, and instead enqueued
     /// internally to happen at the start of the next `submit()` call.
-    pub fn write_texture(
+    pub(crate) fn write_texture(
         &self,
-        texture_image_metadatas: &[Vec<TextureImageMetadatas>],
-        texture: DiffuseTexture,
+        metadatas: &TextureImageMetadatas,
+        texture: &DiffuseTexture,
     ) -> Result<(), QueueError> {
-        // TODO: better add a ref to metas inside irid Texture structs
-        let metadatas = &texture_image_metadatas[log2(texture.size().width() as i32) as usize]
-            [log2(texture.size().height() as i32) as usize];
-
         let bytes = texture.image().as_rgba8_bytes().ok_or(
             // It's ok to have a clone here, is only called if an error occurs
             QueueError::RgbaTextureNoneBytes {
@@ -89,6 +82,109 @@ impl Queue {
         Ok(())
     }
 
+    /// Schedule a data write into the skybox buffer.
+    ///
+    /// This method is intended to have low performance costs.
+    /// As such, the write is not immediately submitted, and instead enqueued
+    /// internally to happen at the start of the next `submit()` call.
+    pub(crate) fn write_skybox_buffer<C: Camera>(&self, camera: &C, skybox_view: &SkyboxView) {
+        let mut skybox_uniform = *skybox_view.uniform();
+        skybox_uniform.update_inv_view_proj(camera);
+        self.wgpu_queue.write_buffer(
+            skybox_view.buffer(),
+            0,
+            bytemuck::cast_slice(&[skybox_uniform]),
+        );
+    }
+
+    /// Schedule a data write of all the six faces into a cube texture.
+    ///
+    /// This method is intended to have low performance costs.
+    /// As such, the write is not immediately submitted, and instead enqueued
+    /// internally to happen at the start of the next `submit()` call.
+    pub(crate) fn write_cubemap(
+        &self,
+        texture_cube_metadatas: &TextureCubeMetadatas,
+        cubemap: &CubemapTexture,
+    ) {
+        for face in CubemapFace::ALL.iter() {
+            self.wgpu_queue.write_texture(
+                texture_cube_metadatas.create_image_copy(face.layer()),
+                cubemap.face(*face),
+                *texture_cube_metadatas.image_data_layout(),
+                *texture_cube_metadatas.face_size(),
+            );
+        }
+    }
+
+    /// Schedule a data write of a glyph coverage into a region of a single channel texture.
+    ///
+    /// This method is intended to have low performance costs.
+    /// As such, the write is not immediately submitted, and instead enqueued
+    /// internally to happen at the start of the next `submit()` call.
+    pub(crate) fn write_glyph(
+        &self,
+        texture: &wgpu::Texture,
+        origin: [u32; 2],
+        glyph: &GlyphBitmap,
+    ) {
+        self.write_texture_region(
+            texture,
+            wgpu::Origin3d {
+                x: origin[0],
+                y: origin[1],
+                z: 0,
+            },
+            &glyph.coverage,
+            1,
+            wgpu::Extent3d {
+                width: glyph.width,
+                height: glyph.height,
+                depth_or_array_layers: 1,
+            },
+        );
+    }
+
+    /// Schedule a data write of tightly packed texels into a region of the first mip level
+    /// of a texture.
+    ///
+    /// This method is intended to have low performance costs.
+    /// As such, the write is not immediately submitted, and instead enqueued
+    /// internally to happen at the start of the next `submit()` call.
+    pub(crate) fn write_texture_region(
+        &self,
+        texture: &wgpu::Texture,
+        origin: wgpu::Origin3d,
+        data: &[u8],
+        bytes_per_texel: u32,
+        size: wgpu::Extent3d,
+    ) {
+        self.wgpu_queue.write_texture(
+            wgpu::ImageCopyTexture {
+                texture,
+                mip_level: 0,
+                origin,
+                aspect: wgpu::TextureAspect::All,
+            },
+            data,
+            wgpu::ImageDataLayout {
+                offset: 0,
+                bytes_per_row: std::num::NonZeroU32::new(size.width * bytes_per_texel),
+                rows_per_image: std::num::NonZeroU32::new(size.height),
+            },
+            size,
+        );
+    }
+
+    /// Schedule a data write into `buffer` starting at `offset`.
+    ///
+    /// This method is intended to have low performance costs.
+    /// As such, the write is not immediately submitted, and instead enqueued
+    /// internally to happen at the start of the next `submit()` call.
+    pub fn write_buffer(&self, buffer: &wgpu::Buffer, offset: wgpu::BufferAddress, data: &[u8]) {
+        self.wgpu_queue.write_buffer(buffer, offset, data);
+    }
+
     /// Submits a series of finished command buffers for execution.
     pub fn submit<I: IntoIterator<Item = wgpu::CommandBuffer>>(&self, command_buffers: I) {
         self.wgpu_queue.submit(command_buffers);
@@ -0,0 +1,241 @@
+//= USES ===========================================================================================
+
+use std::{
+    convert::TryFrom,
+    fs::File,
+    io::{BufWriter, Write},
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use thiserror::Error;
+
+use crate::capture::CaptureError;
+
+//= ERRORS =========================================================================================
+
+#[non_exhaustive]
+#[derive(Debug, Error)]
+pub enum RecordingError {
+    #[error("unable to write the recording output")]
+    Io {
+        #[from]
+        source: std::io::Error,
+    },
+    #[error("unable to save a recorded frame")]
+    SaveImage {
+        #[from]
+        source: image::ImageError,
+    },
+    #[error("unable to capture a recorded frame")]
+    CaptureFrame {
+        #[from]
+        source: CaptureError,
+    },
+    #[error(
+        "the frame size changed from {expected:?} to {found:?}, a Y4M stream needs a fixed one"
+    )]
+    FrameSizeChanged {
+        expected: (u32, u32),
+        found: (u32, u32),
+    },
+}
+
+//= RECORDING FORMAT ===============================================================================
+
+/// How the recorded frames are written.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RecordingFormat {
+    /// A directory of PNG files numbered from zero.
+    PngSequence,
+    /// A single uncompressed YUV4MPEG2 file, with 4:4:4 chroma, that can be read by most
+    /// video encoders.
+    Y4m,
+}
+
+//= FRAME RECORDER =================================================================================
+
+/// Captures the frames rendered by [Renderer::redraw](crate::Renderer::redraw) while it is
+/// passed to [Renderer::start_recording](crate::Renderer::start_recording), the frame capture
+/// must be enabled with
+/// [RendererConfig::with_frame_capture](crate::RendererConfig::with_frame_capture).
+///
+/// The recording is deterministic if the game logic advances of [FrameRecorder::timestep]
+/// every frame, instead of using the wall-clock time: every captured frame is then exactly
+/// one frame of the output at its frame rate.
+#[derive(Debug)]
+pub struct FrameRecorder {
+    output: PathBuf,
+    format: RecordingFormat,
+    frame_rate: u32,
+    every_nth_frame: u32,
+    rendered_frames: u64,
+    written_frames: u64,
+    y4m_stream: Option<Y4mStream>,
+}
+
+impl FrameRecorder {
+    //- Constructors -------------------------------------------------------------------------------
+
+    /// The output is a directory for the PNG sequence and a file for the Y4M stream.
+    pub fn new<P: AsRef<Path>>(output: P, format: RecordingFormat) -> Self {
+        Self {
+            output: output.as_ref().to_path_buf(),
+            format,
+            frame_rate: 60,
+            every_nth_frame: 1,
+            rendered_frames: 0,
+            written_frames: 0,
+            y4m_stream: None,
+        }
+    }
+
+    //- Setters ------------------------------------------------------------------------------------
+
+    /// Frames per second of the output, 60 by default.
+    #[inline]
+    pub fn with_frame_rate(mut self, frame_rate: u32) -> Self {
+        self.frame_rate = frame_rate.max(1);
+        self
+    }
+
+    /// Capture only one frame every `n` rendered ones, 1 by default.
+    #[inline]
+    pub fn with_every_nth_frame(mut self, n: u32) -> Self {
+        self.every_nth_frame = n.max(1);
+        self
+    }
+
+    //- Getters ------------------------------------------------------------------------------------
+
+    /// The simulated time between two rendered frames, that the game logic should use instead
+    /// of the elapsed time.
+    pub fn timestep(&self) -> Duration {
+        Duration::from_secs(1) / self.frame_rate.saturating_mul(self.every_nth_frame)
+    }
+
+    /// The simulated time elapsed since the start of the recording.
+    pub fn simulated_time(&self) -> Duration {
+        u32::try_from(self.rendered_frames)
+            .ok()
+            .and_then(|rendered_frames| self.timestep().checked_mul(rendered_frames))
+            .unwrap_or(Duration::MAX)
+    }
+
+    /// Number of frames written to the output.
+    pub fn written_frames(&self) -> u64 {
+        self.written_frames
+    }
+
+    ///
+    pub fn output(&self) -> &PathBuf {
+        &self.output
+    }
+
+    ///
+    pub fn format(&self) -> RecordingFormat {
+        self.format
+    }
+
+    //- Recording ----------------------------------------------------------------------------------
+
+    /// Prepare the output, so that a wrong path is reported before the first frame.
+    pub(crate) fn start(&mut self) -> Result<(), RecordingError> {
+        match self.format {
+            RecordingFormat::PngSequence => std::fs::create_dir_all(&self.output)?,
+            RecordingFormat::Y4m => {
+                self.y4m_stream = Some(Y4mStream {
+                    writer: BufWriter::new(File::create(&self.output)?),
+                    frame_size: None,
+                })
+            }
+        }
+        Ok(())
+    }
+
+    /// Count a new rendered frame, returns true if it has to be captured.
+    pub(crate) fn begin_frame(&mut self) -> bool {
+        // is_multiple_of is more recent than the supported Rust versions
+        #[allow(clippy::manual_is_multiple_of)]
+        let is_captured = self.rendered_frames % self.every_nth_frame as u64 == 0;
+        self.rendered_frames += 1;
+        is_captured
+    }
+
+    ///
+    pub(crate) fn write_frame(&mut self, frame: &image::RgbaImage) -> Result<(), RecordingError> {
+        match self.format {
+            RecordingFormat::PngSequence => {
+                let filepath = self
+                    .output
+                    .join(format!("frame_{:06}.png", self.written_frames));
+                frame.save_with_format(filepath, image::ImageFormat::Png)?;
+            }
+            RecordingFormat::Y4m => {
+                if let Some(stream) = self.y4m_stream.as_mut() {
+                    stream.write_frame(frame, self.frame_rate)?;
+                }
+            }
+        }
+        self.written_frames += 1;
+        Ok(())
+    }
+
+    /// Flush what has not been written yet.
+    pub(crate) fn finish(&mut self) -> Result<(), RecordingError> {
+        if let Some(stream) = self.y4m_stream.as_mut() {
+            stream.writer.flush()?;
+        }
+        Ok(())
+    }
+}
+
+//= Y4M STREAM =====================================================================================
+
+// The stream header is written with the first frame, when the frame size is known.
+#[derive(Debug)]
+struct Y4mStream {
+    writer: BufWriter<File>,
+    frame_size: Option<(u32, u32)>,
+}
+
+impl Y4mStream {
+    fn write_frame(
+        &mut self,
+        frame: &image::RgbaImage,
+        frame_rate: u32,
+    ) -> Result<(), RecordingError> {
+        let size = frame.dimensions();
+        match self.frame_size {
+            Some(expected) if expected != size => {
+                return Err(RecordingError::FrameSizeChanged {
+                    expected,
+                    found: size,
+                });
+            }
+            Some(_) => {}
+            None => {
+                writeln!(
+                    self.writer,
+                    "YUV4MPEG2 W{} H{} F{}:1 Ip A1:1 C444",
+                    size.0, size.1, frame_rate
+                )?;
+                self.frame_size = Some(size);
+            }
+        }
+
+        // BT.601 limited range, one plane after the other
+        let pixel_count = (size.0 * size.1) as usize;
+        let mut planes = vec![0_u8; pixel_count * 3];
+        for (i, pixel) in frame.pixels().enumerate() {
+            let [r, g, b] = [pixel[0] as i32, pixel[1] as i32, pixel[2] as i32];
+            planes[i] = (((66 * r + 129 * g + 25 * b + 128) >> 8) + 16) as u8;
+            planes[pixel_count + i] = (((-38 * r - 74 * g + 112 * b + 128) >> 8) + 128) as u8;
+            planes[pixel_count * 2 + i] = (((112 * r - 94 * g - 18 * b + 128) >> 8) + 128) as u8;
+        }
+
+        self.writer.write_all(b"FRAME\n")?;
+        self.writer.write_all(&planes)?;
+        Ok(())
+    }
+}
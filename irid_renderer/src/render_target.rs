@@ -0,0 +1,154 @@
+//= USES ===========================================================================================
+
+use crate::{
+    camera_bind::CameraBindGroup,
+    depth::DepthConfig,
+    device::Device,
+    skybox::{Skybox, SkyboxView},
+    texture_metadatas::TextureDepthMetadatas,
+    Camera, OrthographicCamera,
+};
+
+//= RENDER TARGET ==================================================================================
+
+/// An offscreen color texture, with its own depth buffer, where the scene is rendered by
+/// [Renderer::render_to_target](crate::Renderer::render_to_target) from the point of view of
+/// another camera. Useful for mirrors, screens, minimaps and portals.
+///
+/// Its color can then be sampled by the mesh material, see
+/// [Renderer::set_material_texture](crate::Renderer::set_material_texture), or by the sprites,
+/// see [Renderer::load_sprite_render_target](crate::Renderer::load_sprite_render_target).
+#[derive(Debug)]
+pub struct RenderTarget {
+    // Assigned by the renderer, to recognize the target sampled by the material
+    id: usize,
+    color_texture: wgpu::Texture,
+    color_view: wgpu::TextureView,
+    depth_metadatas: TextureDepthMetadatas,
+    // The uniforms of the camera rendering into the target, apart from the window ones
+    camera_bind_group: CameraBindGroup,
+    skybox_view: Option<SkyboxView>,
+    size: winit::dpi::PhysicalSize<u32>,
+    format: wgpu::TextureFormat,
+    clear_color: wgpu::Color,
+}
+
+impl RenderTarget {
+    //- Constructors -------------------------------------------------------------------------------
+
+    /// The formats must be the ones of the window, so that the renderer pipelines can draw on
+    /// the target too.
+    pub(crate) fn new(
+        id: usize,
+        device: &Device,
+        size: winit::dpi::PhysicalSize<u32>,
+        color_format: wgpu::TextureFormat,
+        depth: &DepthConfig,
+        skybox: Option<&Skybox>,
+        clear_color: wgpu::Color,
+    ) -> Self {
+        // Zero sized textures are not valid
+        let size = winit::dpi::PhysicalSize::new(size.width.max(1), size.height.max(1));
+
+        let color_texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Render Target Color Texture"),
+            size: wgpu::Extent3d {
+                width: size.width,
+                height: size.height,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: color_format,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT
+                | wgpu::TextureUsages::TEXTURE_BINDING
+                | wgpu::TextureUsages::COPY_SRC,
+        });
+
+        let color_view = color_texture.create_view(&wgpu::TextureViewDescriptor {
+            label: Some("Render Target Color View"),
+            ..Default::default()
+        });
+
+        let depth_metadatas = TextureDepthMetadatas::new(device, size, depth.format());
+
+        // Written with the camera given at every render
+        let camera = OrthographicCamera::new(1.0, 1.0);
+        let camera_bind_group = CameraBindGroup::new(&camera, device);
+        let skybox_view = skybox.map(|skybox| skybox.create_view(device, &camera));
+
+        Self {
+            id,
+            color_texture,
+            color_view,
+            depth_metadatas,
+            camera_bind_group,
+            skybox_view,
+            size,
+            format: color_format,
+            clear_color,
+        }
+    }
+
+    //- Setters ------------------------------------------------------------------------------------
+
+    /// The color the target is cleared with before every render, the renderer one by default.
+    #[inline]
+    pub fn with_clear_color(mut self, clear_color: wgpu::Color) -> Self {
+        self.clear_color = clear_color;
+        self
+    }
+
+    //- Getters ------------------------------------------------------------------------------------
+
+    /// The size in pixels, the cameras rendering into the target should use its aspect ratio.
+    pub fn size(&self) -> winit::dpi::PhysicalSize<u32> {
+        self.size
+    }
+
+    ///
+    pub fn calc_aspect_ratio(&self) -> f32 {
+        self.size.width as f32 / self.size.height as f32
+    }
+
+    ///
+    pub fn format(&self) -> wgpu::TextureFormat {
+        self.format
+    }
+
+    ///
+    pub fn clear_color(&self) -> wgpu::Color {
+        self.clear_color
+    }
+
+    /// The color texture, it can be sampled and copied.
+    pub fn color_texture(&self) -> &wgpu::Texture {
+        &self.color_texture
+    }
+
+    ///
+    pub fn color_view(&self) -> &wgpu::TextureView {
+        &self.color_view
+    }
+
+    ///
+    pub(crate) fn id(&self) -> usize {
+        self.id
+    }
+
+    ///
+    pub(crate) fn depth_view(&self) -> &wgpu::TextureView {
+        self.depth_metadatas.view()
+    }
+
+    ///
+    pub(crate) fn camera_bind_group(&self) -> &CameraBindGroup {
+        &self.camera_bind_group
+    }
+
+    ///
+    pub(crate) fn skybox_view(&self) -> Option<&SkyboxView> {
+        self.skybox_view.as_ref()
+    }
+}
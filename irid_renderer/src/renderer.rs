@@ -1,24 +1,51 @@
 //= USES ===========================================================================================
 
-use std::{fmt::Debug, fs::read_to_string, path::Path};
+use std::{
+    cmp::Ordering,
+    collections::HashMap,
+    fmt::{Debug, Display},
+    fs::read_to_string,
+    mem,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
 
 use bytemuck::Pod;
+use pollster::FutureExt;
 use thiserror::Error;
 
-use irid_assets_interface::{Index, Vertex};
-use irid_assets::DiffuseTexture;
+use irid_assets_interface::{ImageSize, Index, Vertex};
+use irid_assets::{CubemapTexture, DiffuseTexture, Font, TextStyle};
 
 use crate::{
+    adapter::{AdapterDetails, AdapterError, AdapterRequest, AdapterSelector},
     camera::Camera,
     camera_bind::CameraBindGroup,
+    capture::{CaptureError, FrameCapture},
+    compute::{ComputeDispatch, ComputeStage},
+    debug_draw::DebugDraw,
+    debug_view::{DebugView, DebugViewPipelines},
+    depth::DepthConfig,
     device::Device,
-    instance::Instance,
+    gui::Gui,
+    instance::{Instance, InstanceRaw},
+    material::BlendMode,
+    particles::{ParticleEmitter, ParticleEmitterId, ParticleSystem},
+    profiler::{GpuProfiler, PassTiming},
     queue::{Queue, QueueError},
+    recorder::{FrameRecorder, RecordingError},
+    render_target::RenderTarget,
+    resource::{Handle, Material, Mesh, ResourceError, ResourceRegistry, Texture},
     shader::{DEFAULT_FRAGMENT_ENTRY_POINT, DEFAULT_VERTEX_ENTRY_POINT},
-    surface::Surface,
+    skybox::{Skybox, SkyboxSource, SkyboxView},
+    sprite::{Sprite, SpriteBatch, SpriteTextureId},
+    stats::{FrameStats, FrameStatsCollector},
+    surface::{Surface, SurfaceError},
+    text::{FontId, TextRenderer},
     texture_metadatas::{TextureBindGroupMetadatas, TextureDepthMetadatas, TextureImageMetadatas},
-    utils::log2,
-    CameraController, PipelineLayoutBuilder, RenderPipeline, RenderPipelineBuilder,
+    viewport::{RenderLayers, Viewport, ViewportId, ViewportSlot, Viewports},
+    window_surface::WindowSurface,
+    CameraController, OrthographicCamera, PipelineCache, RenderPipeline, RenderPipelineBuilder,
 };
 
 //= ERRORS =========================================================================================
@@ -29,11 +56,36 @@ use crate::{
 pub enum RendererError {
     #[error("unable to get a Surface or Adapter")]
     SurfaceAdapterRequest,
+    #[error("unable to create the surface of the window")]
+    WindowSurfaceRequest,
+    #[error("the surface cannot be configured with the {format:?} format")]
+    SurfaceFormat { format: wgpu::TextureFormat },
+    #[error("unable to get a suitable Adapter")]
+    Adapter {
+        #[from]
+        source: AdapterError,
+    },
     #[error("unable to get a Device")]
     DeviceRequest {
         #[from]
         source: wgpu::RequestDeviceError,
     },
+    #[error("unable to read the shader {path:?}")]
+    ShaderRead {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+    #[error("the shader is not valid: {message}")]
+    ShaderValidation { message: String },
+    #[error("the {feature} needs a camera")]
+    MissingCamera { feature: &'static str },
+    #[error("the depth format {format:?} has no depth aspect")]
+    DepthFormat { format: wgpu::TextureFormat },
+    #[error("the stencil needs a depth format with a stencil aspect, not {format:?}")]
+    StencilFormat { format: wgpu::TextureFormat },
+    #[error("the texture size {width}x{height} is not supported")]
+    TextureSize { width: u32, height: u32 },
     #[error("unable to load the texture")]
     LoadTexture {
         #[from]
@@ -44,10 +96,41 @@ pub enum RendererError {
         #[from]
         source: QueueError,
     },
+    #[error("unable to get the next frame")]
+    SurfaceTexture {
+        #[from]
+        source: wgpu::SurfaceError,
+    },
+    #[error("unable to capture the frame")]
+    CaptureFrame {
+        #[from]
+        source: CaptureError,
+    },
+    #[error("unable to read back the buffer")]
+    ReadBuffer {
+        #[from]
+        source: wgpu::BufferAsyncError,
+    },
+    #[error("unable to record the frames")]
+    RecordFrames {
+        #[from]
+        source: RecordingError,
+    },
+    #[error("unable to use the resource")]
+    Resource {
+        #[from]
+        source: ResourceError,
+    },
 }
 
 //= CONSTS =========================================================================================
 
+// Longest time step of the particle simulation, to not scatter them after a hitch.
+const MAX_PARTICLES_TIMESTEP: f32 = 0.1;
+
+// Number of frame capture buffers kept to be reused by the next captures.
+const FRAME_CAPTURE_POOL_SIZE: usize = 2;
+
 const NUM_INSTANCES_PER_ROW: u32 = 10;
 const INSTANCE_DISPLACEMENT: cgmath::Vector3<f32> = cgmath::Vector3::new(
     NUM_INSTANCES_PER_ROW as f32 * 0.5,
@@ -71,26 +154,32 @@ pub struct RendererConfig<
     backends: wgpu::Backends,
 
     // Options for the Adapter request
-    power_preference: wgpu::PowerPreference,
-    force_fallback_adapter: bool,
+    adapter_request: AdapterRequest,
 
     // Options for Swap Chain creation
     preferred_format: Option<wgpu::TextureFormat>,
+    srgb: bool,
     // Fifo is "vsync on". Immediate is "vsync off".
     // Mailbox is a hybrid between the two (gpu doesn't block if running faster
     // than the display, but screen tearing doesn't happen)
     present_mode: wgpu::PresentMode,
+    frame_capture: bool,
 
     // Options for the Device request
     features: wgpu::Features,
     limits: wgpu::Limits,
+    gpu_profiler: bool,
 
     camera: Option<C>,
     shader_path: Option<PS>,
     texture_path: Option<PT>,
+    skybox_source: Option<SkyboxSource<PT>>,
     // TODO: Probably better to encapsulate the [ModelVertex] logic or use an Into
     vertices: Option<&'a [V]>,
     indices: Option<&'a [I]>,
+    blend_mode: BlendMode,
+    depth: DepthConfig,
+    stencil: Option<wgpu::StencilState>,
     clear_color: Option<wgpu::Color>,
 }
 
@@ -105,17 +194,27 @@ where
     fn default() -> Self {
         Self {
             backends: wgpu::Backends::VULKAN | wgpu::Backends::DX12 | wgpu::Backends::METAL,
-            power_preference: wgpu::PowerPreference::HighPerformance,
-            force_fallback_adapter: false,
+            adapter_request: AdapterRequest {
+                power_preference: wgpu::PowerPreference::HighPerformance,
+                force_fallback_adapter: false,
+                selector: None,
+            },
             preferred_format: None,
+            srgb: true,
             present_mode: wgpu::PresentMode::Fifo,
+            frame_capture: false,
             features: wgpu::Features::empty(),
             limits: wgpu::Limits::downlevel_defaults(),
+            gpu_profiler: false,
             camera: None,
             shader_path: None,
             texture_path: None,
+            skybox_source: None,
             vertices: None,
             indices: None,
+            blend_mode: BlendMode::default(),
+            depth: DepthConfig::default(),
+            stencil: None,
             clear_color: None,
         }
     }
@@ -148,30 +247,49 @@ where
     ///
     #[inline]
     pub fn with_power_preference(mut self, power_preference: wgpu::PowerPreference) -> Self {
-        self.power_preference = power_preference;
+        self.adapter_request.power_preference = power_preference;
         self
     }
 
     ///
     #[inline]
     pub fn with_force_fallback_adapter(mut self, force_fallback_adapter: bool) -> Self {
-        self.force_fallback_adapter = force_fallback_adapter;
+        self.adapter_request.force_fallback_adapter = force_fallback_adapter;
         self
     }
 
+    /// Pick the adapter with the selector, among the ones listed by
+    /// [enumerate_adapters](crate::enumerate_adapters), in place of the one chosen by the power
+    /// preference. None to go back to the power preference.
+    #[inline]
+    pub fn with_adapter<S: Into<Option<AdapterSelector>>>(mut self, selector: S) -> Self {
+        self.adapter_request.selector = selector.into();
+        self
+    }
+
+    /// The color format of the surface, used if the adapter can render to it, otherwise the
+    /// one preferred by the surface is chosen; if the adapter cannot render to that one either
+    /// the build returns [RendererError::SurfaceFormat].
     ///
-    // TODO: Will be implemented, it was paused because we have doubts about how to convert images
-    //  from Rgb to Bgr format without affecting the performance and ergonomics of the code
+    /// The textures keep their own RGBA format, whatever the surface one is.
     #[inline]
     pub fn with_preferred_format<F: Into<Option<wgpu::TextureFormat>>>(
-        /*mut*/ self,
-        _preferred_format: F,
+        mut self,
+        preferred_format: F,
     ) -> Self {
-        unimplemented!(
-            "Search for wgpu::TextureFormat::Rgba8UnormSrgb on surface.rs file for more info"
-        );
-        //self.preferred_format = preferred_format.into();
-        //self
+        self.preferred_format = preferred_format.into();
+        self
+    }
+
+    /// Choose between the sRGB and the linear variant of the format preferred by the surface,
+    /// sRGB by default. Ignored if a preferred format is given.
+    ///
+    /// The shaders output linear colors, so on a linear surface they must be encoded by the
+    /// application, or they will look darker.
+    #[inline]
+    pub fn with_srgb(mut self, srgb: bool) -> Self {
+        self.srgb = srgb;
+        self
     }
 
     ///
@@ -181,6 +299,14 @@ where
         self
     }
 
+    /// Allow the frames to be captured, by [Renderer::capture_next_frame] or a recording,
+    /// disabled by default. Ignored if the surface format cannot be copied.
+    #[inline]
+    pub fn with_frame_capture(mut self, frame_capture: bool) -> Self {
+        self.frame_capture = frame_capture;
+        self
+    }
+
     ///
     #[inline]
     pub fn with_features(mut self, features: wgpu::Features) -> Self {
@@ -195,6 +321,14 @@ where
         self
     }
 
+    /// Measure the GPU time of every render pass, if the adapter supports the
+    /// [TIMESTAMP_QUERY](wgpu::Features::TIMESTAMP_QUERY) feature.
+    #[inline]
+    pub fn with_gpu_profiler(mut self, gpu_profiler: bool) -> Self {
+        self.gpu_profiler = gpu_profiler;
+        self
+    }
+
     ///
     #[inline]
     pub fn with_camera<IC: Into<Option<C>>>(mut self, camera: IC) -> Self {
@@ -209,13 +343,37 @@ where
         self
     }
 
-    ///
+    /// The sides of the texture cannot be empty nor bigger than the limits allow.
     #[inline]
     pub fn with_texture_path(mut self, texture_path: PT) -> Self {
         self.texture_path = Some(texture_path);
         self
     }
 
+    /// Set the six images, ordered as [CubemapFace::ALL](irid_assets::CubemapFace::ALL),
+    /// used to draw the skybox.
+    ///
+    /// The skybox needs a camera, see [RendererConfig::with_camera].
+    #[inline]
+    pub fn with_skybox_faces(mut self, face_paths: [PT; 6]) -> Self {
+        self.skybox_source = Some(SkyboxSource::Faces(face_paths));
+        self
+    }
+
+    /// Set an equirectangular HDR image, projected on a cube with faces of `face_size` texels,
+    /// used to draw the skybox.
+    ///
+    /// The skybox needs a camera, see [RendererConfig::with_camera].
+    #[inline]
+    pub fn with_skybox_equirectangular<S: Into<Option<u32>>>(
+        mut self,
+        path: PT,
+        face_size: S,
+    ) -> Self {
+        self.skybox_source = Some(SkyboxSource::Equirectangular(path, face_size.into()));
+        self
+    }
+
     ///
     #[inline]
     pub fn with_vertices(mut self, vertices: &'a [V]) -> Self {
@@ -230,6 +388,32 @@ where
         self
     }
 
+    /// How the material of the vertices, made of the shader and the texture, is blended.
+    /// The materials set with [Renderer::set_material] are drawn with their own mode.
+    ///
+    /// With a transparent mode the instances are drawn after the opaque geometry, sorted from
+    /// the farthest to the nearest to the camera.
+    #[inline]
+    pub fn with_blend_mode(mut self, blend_mode: BlendMode) -> Self {
+        self.blend_mode = blend_mode;
+        self
+    }
+
+    /// The format of the depth buffer, reverse-Z and the depth prepass.
+    #[inline]
+    pub fn with_depth(mut self, depth: DepthConfig) -> Self {
+        self.depth = depth;
+        self
+    }
+
+    /// The stencil test and operations of the mesh color pipelines, the depth format must have
+    /// a stencil aspect. The reference value is set by [Renderer::set_stencil_reference].
+    #[inline]
+    pub fn with_stencil<S: Into<Option<wgpu::StencilState>>>(mut self, stencil: S) -> Self {
+        self.stencil = stencil.into();
+        self
+    }
+
     /// Set a clear color with rgb channels as arguments.
     /// The alpha channel is set to 1.0 by default.
     /// See also the method [with_clear_color_rgba].
@@ -272,177 +456,117 @@ where
         &self,
         window: &'a winit::window::Window
     ) -> Result<Renderer<C>, RendererError> {
-        //- Surface, Device, Queue -----------------------------------------------------------------
-
-        let window_size = window.inner_size();
-
-        let (surface, adapter) = Surface::new(
-            self.backends,
-            window,
-            self.power_preference,
-            self.force_fallback_adapter,
-            self.preferred_format,
-            self.present_mode,
-        )
-        .map_err(|_| RendererError::SurfaceAdapterRequest)?;
+        //- Description ----------------------------------------------------------------------------
 
-        // TODO: better find a way to remove the limits.clone()
-        let (device, queue) = Device::new(&adapter, self.features, self.limits.clone())?;
+        if !self.depth.has_depth() {
+            return Err(RendererError::DepthFormat {
+                format: self.depth.format(),
+            });
+        }
+        if self.stencil.is_some() && !self.depth.has_stencil() {
+            return Err(RendererError::StencilFormat {
+                format: self.depth.format(),
+            });
+        }
 
-        surface.configure(&device);
+        // The cameras follow the reverse-Z of the depth buffer
+        let camera = self.camera.clone().map(|mut camera| {
+            camera.set_reverse_z(self.depth.is_reverse_z());
+            camera
+        });
 
-        //- Camera ---------------------------------------------------------------------------------
+        // The relative paths are resolved from the current directory
+        let shader = self
+            .shader_path
+            .as_ref()
+            .map(|shader_path| {
+                read_to_string(shader_path).map_err(|source| RendererError::ShaderRead {
+                    path: shader_path.as_ref().to_path_buf(),
+                    source,
+                })
+            })
+            .transpose()?;
 
-        let (camera_metadatas, camera_controller) = if self.camera.is_some() {
-            (
-                Some(CameraBindGroup::new(self.camera.as_ref().unwrap(), &device)),
-                Some(CameraController::new(0.2)),
-            )
-        } else {
-            (None, None)
+        let skybox = match (self.skybox_source.as_ref(), self.camera.as_ref()) {
+            (Some(skybox_source), Some(_)) => Some(skybox_source.load()?),
+            (Some(_), None) => return Err(RendererError::MissingCamera { feature: "skybox" }),
+            (None, _) => None,
         };
 
-        //- Texture Metadatas ----------------------------------------------------------------------
-
-        let texture_image_metadatas = if self.texture_path.is_some() {
-            RendererConfig::<'a, C, PS, PT, V, I>::create_texture_image_metadatas(&device)
-        } else {
-            vec![]
-        };
+        let texture = self
+            .texture_path
+            .as_ref()
+            .map(DiffuseTexture::load)
+            .transpose()?;
+        if let Some(texture) = texture.as_ref() {
+            let size = texture.size();
+            let max_size = self.limits.max_texture_dimension_2d;
+            let is_valid = |side: u32| side > 0 && side <= max_size;
+            if !is_valid(size.width()) || !is_valid(size.height()) {
+                return Err(RendererError::TextureSize {
+                    width: size.width(),
+                    height: size.height(),
+                });
+            }
+        }
 
-        let texture_bind_group_metadatas = if self.texture_path.is_some() {
-            RendererConfig::<'a, C, PS, PT, V, I>::create_texture_bind_group_metadatas(
-                &device,
-                &texture_image_metadatas,
-            )
-        } else {
-            vec![]
+        let description = RendererDescription {
+            backends: self.backends,
+            adapter_request: self.adapter_request.clone(),
+            features: self.features,
+            // TODO: better find a way to remove the limits.clone()
+            limits: self.limits.clone(),
+            gpu_profiler: self.gpu_profiler,
+            shader,
+            texture,
+            skybox,
+            vertex_layout: self.vertices.map(|_| V::desc()),
+            depth: self.depth,
+            stencil: self.stencil.clone(),
         };
 
-        let texture_depth_metadatas = TextureDepthMetadatas::new(&device, window_size);
-
-        //- Pipeline -------------------------------------------------------------------------------
-
-        let renderer_pipeline = if self.shader_path.is_some() {
-            let path = std::env::current_dir()
-                .unwrap()
-                .as_path()
-                .join(&self.shader_path.as_ref().unwrap());
-            let content = match read_to_string(&path) {
-                Ok(content) => content,
-                Err(err) => panic!("Couldn't open {:?} file: {}", path, err),
-            };
-
-            let source = wgpu::ShaderSource::Wgsl(std::borrow::Cow::Owned(content));
-            //#[cfg(feature = "glsl")]
-            //wgpu::ShaderSource::Glsl(std::borrow::Cow::Owned(shader_key))
-
-            let shader_module = device.create_shader_module(&wgpu::ShaderModuleDescriptor {
-                label: None,
-                source,
-            });
-
-            // TODO: no good...
-            let vertex_buffers = [V::desc()];
-            // TODO: raw instances must be optional
-            //let vertex_buffers = [V::desc(), InstanceRaw::desc()];
-
-            let vertex_state = if self.vertices.is_some() {
-                wgpu::VertexState {
-                    module: &shader_module,
-                    entry_point: DEFAULT_VERTEX_ENTRY_POINT,
-                    buffers: &vertex_buffers,
-                }
-            } else {
-                wgpu::VertexState {
-                    module: &shader_module,
-                    entry_point: DEFAULT_VERTEX_ENTRY_POINT,
-                    buffers: &[],
-                }
-            };
-
-            let color_targets = [wgpu::ColorTargetState {
-                format: surface.format(),
-                blend: Some(wgpu::BlendState {
-                    color: wgpu::BlendComponent::REPLACE,
-                    alpha: wgpu::BlendComponent::REPLACE,
-                }),
-                write_mask: wgpu::ColorWrites::ALL,
-            }];
-
-            let fragment_states = wgpu::FragmentState {
-                module: &shader_module,
-                entry_point: DEFAULT_FRAGMENT_ENTRY_POINT,
-                targets: &color_targets,
-            };
-
-            let pipeline_layout = if texture_bind_group_metadatas.is_empty() {
-                let plb = PipelineLayoutBuilder::new();
-                if self.camera.is_some() {
-                    let camera_bgl = camera_metadatas.as_ref().unwrap().bind_group_layout(); // TODO: remove unwrap
-                    plb.with_bind_group_layouts(&[camera_bgl]).build(&device)
-                } else {
-                    plb.build(&device)
-                }
-            } else {
-                // TODO: 256x256 texture, hardcoded for now :(
-                let texture_bgl = texture_bind_group_metadatas[8][8].bind_group_layout();
-
-                let plb = PipelineLayoutBuilder::new();
-                if self.camera.is_some() {
-                    let camera_bgl = camera_metadatas.as_ref().unwrap().bind_group_layout(); // TODO: remove unwrap
-                    plb.with_bind_group_layouts(&[texture_bgl, camera_bgl])
-                        .build(&device)
-                } else {
-                    plb.with_bind_group_layouts(&[texture_bgl]).build(&device)
-                }
-            };
-
-            Some(
-                RenderPipelineBuilder::new(vertex_state)
-                    .with_fragment(fragment_states)
-                    .with_layout(&pipeline_layout)
-                    .build(&device),
-            )
-        } else {
-            None
-        };
+        //- Surface, Device, Queue -----------------------------------------------------------------
 
-        //- Queue Schedule -------------------------------------------------------------------------
+        let window_size = window.inner_size();
 
-        if self.texture_path.is_some() {
-            // TODO: here we use unwrap because texture loading will probably not be done at this point
-            //  and therefore it is useless to add a new type of error
-            queue.write_texture(
-                &texture_image_metadatas,
-                DiffuseTexture::load(self.texture_path.as_ref().unwrap())?,
-            )?
-        }
+        let (surface, instance, adapter) = Surface::new(
+            self.backends,
+            window,
+            &self.adapter_request,
+            self.preferred_format,
+            self.srgb,
+            self.present_mode,
+            self.frame_capture,
+        )
+        .map_err(|err| match err {
+            SurfaceError::Adapter { source } => RendererError::Adapter { source },
+            SurfaceError::IncompatibleSurface => RendererError::SurfaceAdapterRequest,
+            SurfaceError::Format { format } => RendererError::SurfaceFormat { format },
+        })?;
+
+        let gpu = RendererResources::new(
+            &description,
+            &adapter,
+            &surface,
+            window_size,
+            window.scale_factor(),
+            camera.as_ref(),
+        )?;
+        surface.configure(&gpu.device);
 
-        //- Vertex and Index Buffers ---------------------------------------------------------------
+        //- Resources ------------------------------------------------------------------------------
 
-        let vertex_buffer = self
+        // The mesh of the configured vertices and indices, referred by the renderer
+        let mut resources = ResourceRegistry::new();
+        let mesh = self
             .vertices
-            .map(|v| device.create_vertex_buffer_init("Vertex Buffer", v));
-
-        let index_buffer = self
-            .indices
-            .map(|i| device.create_indices_buffer_init("Index Buffer", i));
-
-        let num_indices = if self.indices.is_some() {
-            self.indices.unwrap().len() as u32
-        } else {
-            0_u32
-        };
+            .map(|vertices| resources.insert(Mesh::new(&gpu.device, vertices, self.indices)));
 
         //- Instances ------------------------------------------------------------------------------
 
         let (instances, instances_buffer) = if self.vertices.is_some() {
             let instances = RendererConfig::<'a, C, PS, PT, V, I>::create_instances();
-            let instances_buffer =
-                RendererConfig::<'a, C, PS, PT, V, I>::create_instances_buffer(
-                    &device, &instances,
-                );
+            let instances_buffer = create_instances_buffer(&gpu.device, &instances, 1);
             (Some(instances), Some(instances_buffer))
         } else {
             (None, None)
@@ -454,66 +578,56 @@ where
             window_size,
             clear_color: self.clear_color.unwrap_or(wgpu::Color::WHITE),
             surface,
-            device,
-            queue,
-
-            camera: self.camera.clone(),
-            camera_metadatas,
-            camera_controller,
-
-            texture_image_metadatas,
-            texture_bind_group_metadatas,
-            texture_depth_metadatas,
-
-            renderer_pipeline,
-            vertex_buffer,
-            index_buffer,
-            num_indices,
+            instance,
+            adapter,
+            device: gpu.device,
+            queue: gpu.queue,
+            description,
+
+            camera_controller: camera.as_ref().map(|_| CameraController::new(0.2)),
+            camera,
+            camera_metadatas: gpu.camera_metadatas,
+
+            texture_image_metadatas: gpu.texture_image_metadatas,
+            texture_bind_group_metadatas: gpu.texture_bind_group_metadatas,
+            texture_depth_metadatas: gpu.texture_depth_metadatas,
+            material_target: None,
+            render_target_count: 0,
+
+            skybox: gpu.skybox,
+            sprite_batch: gpu.sprite_batch,
+            text_renderer: gpu.text_renderer,
+            debug_draw: gpu.debug_draw,
+            particle_system: gpu.particle_system,
+            viewports: gpu.viewports,
+            windows: vec![],
+            gui: gpu.gui,
+            recorder: None,
+            is_capture_requested: false,
+            captured_frame: None,
+            capture_pool: vec![],
+            gpu_profiler: gpu.gpu_profiler,
+            frame_stats: FrameStatsCollector::default(),
+            render_target_stats: FrameStats::default(),
+            compute_dispatches: vec![],
+
+            renderer_pipelines: gpu.renderer_pipelines,
+            prepass_pipeline: gpu.prepass_pipeline,
+            debug_view: DebugView::Shaded,
+            debug_view_pipelines: gpu.debug_view_pipelines,
+            pipeline_cache: gpu.pipeline_cache,
+            blend_mode: self.blend_mode,
+            depth: self.depth,
+            stencil_reference: 0,
+            resources,
+            mesh,
+            material: None,
             instances,
             instances_buffer,
+            instances_copies: 1,
         })
     }
 
-    ///
-    ///
-    /// It can't cache zero sized textures.
-    pub fn create_texture_image_metadatas(device: &Device) -> Vec<Vec<TextureImageMetadatas>> {
-        let qty = log2(wgpu::Limits::downlevel_defaults().max_texture_dimension_2d as i32) as usize;
-        let mut vec_w = Vec::<Vec<TextureImageMetadatas>>::with_capacity(qty);
-        for width in 0..qty {
-            let mut vec_h = Vec::<TextureImageMetadatas>::with_capacity(qty);
-            for height in 0..qty {
-                vec_h.push(TextureImageMetadatas::new(
-                    device,
-                    2_u32.pow(width as u32),
-                    2_u32.pow(height as u32),
-                ));
-            }
-            vec_w.push(vec_h);
-        }
-        vec_w
-    }
-
-    ///
-    pub fn create_texture_bind_group_metadatas(
-        device: &Device,
-        texture_image_metadatas: &[Vec<TextureImageMetadatas>],
-    ) -> Vec<Vec<TextureBindGroupMetadatas>> {
-        let qty = texture_image_metadatas.len();
-        let mut vec_w = Vec::<Vec<TextureBindGroupMetadatas>>::with_capacity(qty);
-        for vec_width_metadatas in texture_image_metadatas.iter() {
-            let mut vec_h = Vec::<TextureBindGroupMetadatas>::with_capacity(qty);
-            for metadata_element in vec_width_metadatas.iter() {
-                vec_h.push(TextureBindGroupMetadatas::new(
-                    device,
-                    metadata_element.texture(),
-                ));
-            }
-            vec_w.push(vec_h);
-        }
-        vec_w
-    }
-
     fn create_instances() -> Vec<Instance> {
         (0..NUM_INSTANCES_PER_ROW)
             .flat_map(|z| {
@@ -545,20 +659,333 @@ where
             })
             .collect::<Vec<_>>()
     }
+}
+
+//= RENDERER DESCRIPTION ===========================================================================
+
+// What the config asks for, loaded on the CPU and retained by the renderer to recreate its GPU
+// resources after the device has been lost.
+#[derive(Debug)]
+struct RendererDescription {
+    backends: wgpu::Backends,
+    adapter_request: AdapterRequest,
+    features: wgpu::Features,
+    limits: wgpu::Limits,
+    gpu_profiler: bool,
+    shader: Option<String>,
+    texture: Option<DiffuseTexture>,
+    // Loaded only if there is a camera to draw it
+    skybox: Option<CubemapTexture>,
+    vertex_layout: Option<wgpu::VertexBufferLayout<'static>>,
+    depth: DepthConfig,
+    stencil: Option<wgpu::StencilState>,
+}
+
+//= RENDERER RESOURCES =============================================================================
+
+// The device, the queue and the GPU resources created from the description.
+struct RendererResources<C: Camera> {
+    device: Device,
+    queue: Queue,
+    camera_metadatas: Option<CameraBindGroup>,
+    texture_image_metadatas: Option<TextureImageMetadatas>,
+    texture_bind_group_metadatas: Option<TextureBindGroupMetadatas>,
+    texture_depth_metadatas: TextureDepthMetadatas,
+    skybox: Option<Skybox>,
+    sprite_batch: SpriteBatch,
+    text_renderer: TextRenderer,
+    debug_draw: DebugDraw,
+    particle_system: ParticleSystem,
+    viewports: Viewports<C>,
+    gui: Gui,
+    gpu_profiler: Option<GpuProfiler>,
+    renderer_pipelines: HashMap<BlendMode, Arc<RenderPipeline>>,
+    prepass_pipeline: Option<Arc<RenderPipeline>>,
+    debug_view_pipelines: Option<DebugViewPipelines>,
+    pipeline_cache: PipelineCache,
+}
+
+impl<C: Camera> RendererResources<C> {
+    // Request the device from the adapter, the surface must then be configured for it.
+    fn new(
+        description: &RendererDescription,
+        adapter: &wgpu::Adapter,
+        surface: &Surface,
+        window_size: winit::dpi::PhysicalSize<u32>,
+        scale_factor: f64,
+        camera: Option<&C>,
+    ) -> Result<Self, RendererError> {
+        //- Device, Queue --------------------------------------------------------------------------
+
+        // Fail here, requesting the device would panic
+        AdapterDetails::new(adapter).check_support(description.features, &description.limits)?;
+
+        let mut features = description.features;
+        let is_profiling = description.gpu_profiler
+            && adapter
+                .features()
+                .contains(wgpu::Features::TIMESTAMP_QUERY);
+        if is_profiling {
+            features |= wgpu::Features::TIMESTAMP_QUERY;
+        } else if description.gpu_profiler {
+            log::warn!("The adapter does not support timestamp queries, GPU profiling is disabled");
+        }
+        // Used by the wireframe debug view, otherwise drawn by a slower fallback
+        features |= adapter.features() & wgpu::Features::POLYGON_MODE_LINE;
+
+        let (device, queue) = Device::new(adapter, features, description.limits.clone())?;
+
+        // Shared by the pipelines created below
+        let mut pipeline_cache = PipelineCache::new();
+
+        let depth = &description.depth;
+
+        //- Camera ---------------------------------------------------------------------------------
+
+        let camera_metadatas = camera.map(|camera| CameraBindGroup::new(camera, &device));
+
+        //- Skybox ---------------------------------------------------------------------------------
+
+        let skybox = match (description.skybox.as_ref(), camera) {
+            (Some(cubemap), Some(camera)) => Some(Skybox::new(
+                &device,
+                &queue,
+                camera,
+                cubemap,
+                surface.format(),
+                depth,
+            )),
+            _ => None,
+        };
+
+        //- Sprites --------------------------------------------------------------------------------
+
+        let sprite_batch = SpriteBatch::new(&device, window_size, surface.format(), depth);
+
+        //- Texts ----------------------------------------------------------------------------------
+
+        let text_renderer = TextRenderer::new(
+            &device,
+            &mut pipeline_cache,
+            window_size,
+            surface.format(),
+            depth,
+        );
+
+        //- Debug Draw -----------------------------------------------------------------------------
+
+        let debug_draw = DebugDraw::new(&device, surface.format(), depth);
 
-    fn create_instances_buffer(device: &Device, instances: &[Instance]) -> wgpu::Buffer {
-        let instance_data = instances.iter().map(Instance::to_raw).collect::<Vec<_>>();
+        //- Particles ------------------------------------------------------------------------------
 
-        // TODO: When we will create the generics about Vertices we will use the
-        //  Device.create_vertex_buffer_init instead
-        device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-            label: Some("Instance Buffer"),
-            contents: bytemuck::cast_slice(&instance_data),
-            usage: wgpu::BufferUsages::VERTEX,
+        let supports_compute = adapter
+            .get_downlevel_properties()
+            .flags
+            .contains(wgpu::DownlevelFlags::COMPUTE_SHADERS)
+            && description.limits.max_storage_buffers_per_shader_stage > 0;
+        let particle_system =
+            ParticleSystem::new(&device, surface.format(), depth, supports_compute);
+
+        //- Viewports ------------------------------------------------------------------------------
+
+        let viewports = Viewports::new(&device, surface.format(), depth);
+
+        //- Gui ------------------------------------------------------------------------------------
+
+        let gui = Gui::new(&device, scale_factor, surface.format());
+
+        //- Gpu Profiler ---------------------------------------------------------------------------
+
+        let gpu_profiler = if is_profiling {
+            Some(GpuProfiler::new(&device, queue.get_timestamp_period()))
+        } else {
+            None
+        };
+
+        //- Texture Metadatas ----------------------------------------------------------------------
+
+        // Created of the size of the loaded texture
+        let texture_image_metadatas = description.texture.as_ref().map(|texture| {
+            let size = texture.size();
+            TextureImageMetadatas::new(&device, size.width(), size.height())
+        });
+
+        let texture_bind_group_metadatas = texture_image_metadatas
+            .as_ref()
+            .map(|metadatas| TextureBindGroupMetadatas::new(&device, metadatas.texture()));
+
+        let texture_depth_metadatas =
+            TextureDepthMetadatas::new(&device, window_size, depth.format());
+
+        //- Pipeline -------------------------------------------------------------------------------
+
+        // The depth prepass is only used by the opaque materials
+        let has_prepass = depth.has_prepass();
+
+        // The errors of the shader, or of its bindings, are returned instead of panicking
+        device.push_error_scope(wgpu::ErrorFilter::Validation);
+
+        let (renderer_pipelines, prepass_pipeline, debug_view_pipelines) = match description
+            .shader
+            .as_ref()
+        {
+            Some(shader) => {
+                //#[cfg(feature = "glsl")]
+                //wgpu::ShaderSource::Glsl(std::borrow::Cow::Owned(shader_key))
+                let shader_module =
+                    pipeline_cache.shader_module(&device, "Renderer Shader", shader);
+
+                // TODO: raw instances must be optional
+                //let vertex_buffers = [V::desc(), InstanceRaw::desc()];
+                let vertex_buffers = description
+                    .vertex_layout
+                    .iter()
+                    .cloned()
+                    .collect::<Vec<_>>();
+
+                let vertex_state = wgpu::VertexState {
+                    module: &shader_module,
+                    entry_point: DEFAULT_VERTEX_ENTRY_POINT,
+                    buffers: &vertex_buffers,
+                };
+
+                // The bind groups are ordered as: texture, camera and then the environment
+                // cube texture used for reflections; the missing ones are skipped
+                let mut bind_group_layouts = Vec::with_capacity(3);
+                if let Some(texture_bind_group_metadatas) = texture_bind_group_metadatas.as_ref() {
+                    bind_group_layouts.push(texture_bind_group_metadatas.bind_group_layout());
+                }
+                if let Some(camera_metadatas) = camera_metadatas.as_ref() {
+                    bind_group_layouts.push(camera_metadatas.bind_group_layout());
+                }
+                if let Some(skybox) = skybox.as_ref() {
+                    bind_group_layouts
+                        .push(skybox.cube_bind_group_metadatas().bind_group_layout());
+                }
+
+                let pipeline_layout = pipeline_cache.pipeline_layout(
+                    &device,
+                    "Renderer Pipeline Layout",
+                    &bind_group_layouts,
+                    &[],
+                );
+
+                let prepass_pipeline = if has_prepass {
+                    Some(
+                        RenderPipelineBuilder::new(vertex_state.clone())
+                            .with_label("Depth Prepass Pipeline")
+                            .with_layout(&pipeline_layout)
+                            .with_depth_stencil(
+                                depth.depth_stencil_state(true, wgpu::CompareFunction::Less),
+                            )
+                            .build_cached(&device, &mut pipeline_cache),
+                    )
+                } else {
+                    None
+                };
+
+                // Every blend mode has its own pipeline, the one of the material drawn is used
+                let mut renderer_pipelines = HashMap::with_capacity(BlendMode::ALL.len());
+                for &blend_mode in BlendMode::ALL.iter() {
+                    let color_targets = [wgpu::ColorTargetState {
+                        format: surface.format(),
+                        blend: Some(blend_mode.blend_state()),
+                        write_mask: wgpu::ColorWrites::ALL,
+                    }];
+
+                    // After the prepass the depth buffer already contains the visible fragments
+                    let depth_stencil = if has_prepass && !blend_mode.is_transparent() {
+                        depth.depth_stencil_state(false, wgpu::CompareFunction::LessEqual)
+                    } else {
+                        blend_mode.depth_stencil_state(depth)
+                    };
+
+                    let mut builder = RenderPipelineBuilder::new(vertex_state.clone())
+                        .with_fragment(wgpu::FragmentState {
+                            module: &shader_module,
+                            entry_point: DEFAULT_FRAGMENT_ENTRY_POINT,
+                            targets: &color_targets,
+                        })
+                        .with_layout(&pipeline_layout)
+                        .with_depth_stencil(depth_stencil);
+                    if let Some(stencil) = description.stencil.clone() {
+                        builder = builder.with_stencil(stencil);
+                    }
+                    let renderer_pipeline = builder.build_cached(&device, &mut pipeline_cache);
+                    let _ = renderer_pipelines.insert(blend_mode, renderer_pipeline);
+                }
+
+                // The debug views draw the vertices with the camera only
+                let debug_view_pipelines =
+                    match (description.vertex_layout.as_ref(), camera_metadatas.as_ref()) {
+                        (Some(vertex_layout), Some(camera_metadatas)) => DebugViewPipelines::new(
+                            &device,
+                            &mut pipeline_cache,
+                            vertex_layout,
+                            camera_metadatas.bind_group_layout(),
+                            surface.format(),
+                            depth,
+                        ),
+                        _ => None,
+                    };
+
+                (renderer_pipelines, prepass_pipeline, debug_view_pipelines)
+            }
+            None => (HashMap::new(), None, None),
+        };
+
+        if let Some(error) = device.pop_error_scope().block_on() {
+            return Err(RendererError::ShaderValidation {
+                message: error.to_string(),
+            });
+        }
+
+        //- Queue Schedule -------------------------------------------------------------------------
+
+        if let (Some(texture), Some(texture_image_metadatas)) = (
+            description.texture.as_ref(),
+            texture_image_metadatas.as_ref(),
+        ) {
+            queue.write_texture(texture_image_metadatas, texture)?;
+        }
+
+        Ok(Self {
+            device,
+            queue,
+            camera_metadatas,
+            texture_image_metadatas,
+            texture_bind_group_metadatas,
+            texture_depth_metadatas,
+            skybox,
+            sprite_batch,
+            text_renderer,
+            debug_draw,
+            particle_system,
+            viewports,
+            gui,
+            gpu_profiler,
+            renderer_pipelines,
+            prepass_pipeline,
+            debug_view_pipelines,
+            pipeline_cache,
         })
     }
 }
 
+//= SCENE VIEW =====================================================================================
+
+// The uniforms of the cameras a scene is drawn with, its copy of the instances and the layers
+// drawn. Without the sprite and the screen cameras the ones of the window are used.
+#[derive(Clone, Copy, Debug)]
+struct SceneView<'a> {
+    camera_bind_group: Option<&'a CameraBindGroup>,
+    sprite_camera_bind_group: Option<&'a CameraBindGroup>,
+    screen_camera_bind_group: Option<&'a CameraBindGroup>,
+    skybox_view: Option<&'a SkyboxView>,
+    instances: Option<wgpu::BufferSlice<'a>>,
+    layers: RenderLayers,
+}
+
 //= RENDERER OBJECT ================================================================================
 
 ///
@@ -567,25 +994,57 @@ pub struct Renderer<C: Camera> {
     window_size: winit::dpi::PhysicalSize<u32>,
     clear_color: wgpu::Color,
     surface: Surface,
+    instance: wgpu::Instance,
+    adapter: wgpu::Adapter,
     device: Device,
     queue: Queue,
+    description: RendererDescription,
 
     camera: Option<C>,
     camera_metadatas: Option<CameraBindGroup>,
     camera_controller: Option<CameraController>,
 
     #[allow(dead_code)]
-    texture_image_metadatas: Vec<Vec<TextureImageMetadatas>>,
-    texture_bind_group_metadatas: Vec<Vec<TextureBindGroupMetadatas>>,
+    texture_image_metadatas: Option<TextureImageMetadatas>,
+    texture_bind_group_metadatas: Option<TextureBindGroupMetadatas>,
     texture_depth_metadatas: TextureDepthMetadatas,
-
-    renderer_pipeline: Option<RenderPipeline>,
+    // The render target sampled by the material in place of the loaded texture
+    material_target: Option<(usize, TextureBindGroupMetadatas)>,
+    render_target_count: usize,
+
+    skybox: Option<Skybox>,
+    sprite_batch: SpriteBatch,
+    text_renderer: TextRenderer,
+    debug_draw: DebugDraw,
+    particle_system: ParticleSystem,
+    viewports: Viewports<C>,
+    windows: Vec<WindowSurface<C>>,
+    gui: Gui,
+    recorder: Option<FrameRecorder>,
+    is_capture_requested: bool,
+    captured_frame: Option<Result<image::RgbaImage, CaptureError>>,
+    capture_pool: Vec<FrameCapture>,
+    gpu_profiler: Option<GpuProfiler>,
+    frame_stats: FrameStatsCollector,
+    render_target_stats: FrameStats,
+    compute_dispatches: Vec<(ComputeStage, ComputeDispatch)>,
+
+    renderer_pipelines: HashMap<BlendMode, Arc<RenderPipeline>>,
+    prepass_pipeline: Option<Arc<RenderPipeline>>,
+    debug_view: DebugView,
+    debug_view_pipelines: Option<DebugViewPipelines>,
+    pipeline_cache: PipelineCache,
+    blend_mode: BlendMode,
+    depth: DepthConfig,
+    stencil_reference: u32,
     // TODO: maybe these fields below are better to move inside the render_pass or pipeline object
-    vertex_buffer: Option<wgpu::Buffer>,
-    index_buffer: Option<wgpu::Buffer>,
-    num_indices: u32,
+    resources: ResourceRegistry,
+    mesh: Option<Handle<Mesh>>,
+    material: Option<Handle<Material>>,
     instances: Option<Vec<Instance>>,
     instances_buffer: Option<wgpu::Buffer>,
+    // The copies of the instances held by the buffer, one for each view sorting them
+    instances_copies: usize,
 }
 
 impl<C> Renderer<C>
@@ -599,17 +1058,42 @@ where
         self.window_size
     }
 
+    /// The adapter the renderer has been created with.
+    pub fn adapter_details(&self) -> AdapterDetails {
+        AdapterDetails::new(&self.adapter)
+    }
+
     /// Calculate the aspect ratio of the window's inner size.
     pub fn calc_aspect_ratio(&self) -> f32 {
         self.window_size.width as f32 / self.window_size.height as f32
     }
 
-    /// Resize the renderer window.
-    pub fn resize(&mut self, new_size: winit::dpi::PhysicalSize<u32>) {
-        self.window_size = new_size;
+    /// The color format negotiated with the surface, needed by the pipelines drawing on it.
+    pub fn surface_format(&self) -> wgpu::TextureFormat {
+        self.surface.format()
+    }
+
+    /// The depth buffer configuration, the cameras follow its reverse-Z.
+    pub fn depth_config(&self) -> DepthConfig {
+        self.depth
+    }
+
+    /// The reference value compared by the stencil set with [RendererConfig::with_stencil],
+    /// 0 by default.
+    pub fn set_stencil_reference(&mut self, reference: u32) {
+        self.stencil_reference = reference;
+    }
+
+    /// Resize the renderer window.
+    pub fn resize(&mut self, new_size: winit::dpi::PhysicalSize<u32>) {
+        self.window_size = new_size;
         if new_size.width > 0 && new_size.height > 0 {
             self.texture_depth_metadatas =
-                TextureDepthMetadatas::new(&self.device, self.window_size);
+                TextureDepthMetadatas::new(&self.device, self.window_size, self.depth.format());
+            self.sprite_batch
+                .camera_mut()
+                .resize(new_size.width as f32, new_size.height as f32);
+            self.text_renderer.resize(new_size);
             self.refresh_current_size();
         }
     }
@@ -619,18 +1103,717 @@ where
         self.surface.update(&self.device, self.window_size);
     }
 
+    //- Device Recovery ----------------------------------------------------------------------------
+
+    /// Recreate the device and the queue, once the device has been lost or has run out of
+    /// memory, together with the GPU resources, from the data the renderer keeps on the CPU.
+    ///
+    /// The resources of the config, the viewports, the additional windows, the fonts, the
+    /// particle emitters, the sprite textures loaded from images and the meshes, textures and
+    /// materials of the registry are recreated: their ids and handles stay valid. The particles
+    /// spawned, the timed debug shapes and the GUI state start over.
+    ///
+    /// The render targets cannot be recreated, nor the sprite textures and the material texture
+    /// sampling them; the pipelines of the registry and its materials whose texture has been
+    /// destroyed are destroyed, their handles become stale, and the enqueued compute dispatches
+    /// are dropped. They must be created again afterwards. The frame capture requested, if any,
+    /// is dropped and the recording in progress is stopped.
+    ///
+    /// On error nothing is replaced, and the renderer keeps the lost device.
+    pub fn recover(&mut self) -> Result<(), RendererError> {
+        log::warn!("Recreating the device and the GPU resources");
+
+        let adapter = self.description.adapter_request.request(
+            &self.instance,
+            self.description.backends,
+            self.surface.expose_wrapped_surface(),
+        )?;
+        log::info!("Picked Adapter: {}", AdapterDetails::new(&adapter));
+
+        let RendererResources {
+            device,
+            queue,
+            camera_metadatas,
+            texture_image_metadatas,
+            texture_bind_group_metadatas,
+            texture_depth_metadatas,
+            skybox,
+            mut sprite_batch,
+            mut text_renderer,
+            debug_draw,
+            mut particle_system,
+            mut viewports,
+            gui,
+            gpu_profiler,
+            renderer_pipelines,
+            prepass_pipeline,
+            debug_view_pipelines,
+            pipeline_cache,
+        } = RendererResources::new(
+            &self.description,
+            &adapter,
+            &self.surface,
+            self.window_size,
+            self.gui.scale_factor(),
+            self.camera.as_ref(),
+        )?;
+
+        // The uploads that can fail are done before anything is replaced, so that on error the
+        // renderer is left as it was
+        let textures = self.resources.upload_textures(&device, &queue)?;
+        sprite_batch.restore(&device, &queue, &self.sprite_batch)?;
+
+        //- Carried Over ---------------------------------------------------------------------------
+
+        self.surface.configure(&device);
+
+        self.resources.recreate(&device, textures);
+        text_renderer.restore_fonts(&mut self.text_renderer);
+        particle_system.restore_emitters(&device, &mut self.particle_system);
+        viewports.restore(&device, skybox.as_ref(), &mut self.viewports);
+
+        let windows = mem::take(&mut self.windows);
+        self.windows = windows
+            .into_iter()
+            .map(|window| window.recreate(&device, &self.depth, &viewports, skybox.as_ref()))
+            .collect();
+
+        self.instances_buffer = self
+            .instances
+            .as_ref()
+            .map(|instances| create_instances_buffer(&device, instances, 1));
+        self.instances_copies = 1;
+        self.material_target = None;
+        self.compute_dispatches.clear();
+
+        // The captures read from the lost device, the frames a recording misses break its pace
+        self.is_capture_requested = false;
+        self.captured_frame = None;
+        self.capture_pool.clear();
+        if self.recorder.is_some() {
+            self.abort_recording("the device has been lost");
+        }
+
+        //- Replaced -------------------------------------------------------------------------------
+
+        self.adapter = adapter;
+        self.device = device;
+        self.queue = queue;
+        self.camera_metadatas = camera_metadatas;
+        self.texture_image_metadatas = texture_image_metadatas;
+        self.texture_bind_group_metadatas = texture_bind_group_metadatas;
+        self.texture_depth_metadatas = texture_depth_metadatas;
+        self.skybox = skybox;
+        self.sprite_batch = sprite_batch;
+        self.text_renderer = text_renderer;
+        self.debug_draw = debug_draw;
+        self.particle_system = particle_system;
+        self.viewports = viewports;
+        self.gui = gui;
+        self.gpu_profiler = gpu_profiler;
+        self.renderer_pipelines = renderer_pipelines;
+        self.prepass_pipeline = prepass_pipeline;
+        self.debug_view_pipelines = debug_view_pipelines;
+        self.pipeline_cache = pipeline_cache;
+        Ok(())
+    }
+
     //- Camera -------------------------------------------------------------------------------------
 
+    /// The camera moved by the camera controller, if the renderer has one.
+    pub fn camera(&self) -> Option<&C> {
+        self.camera.as_ref()
+    }
+
     ///
     pub fn process_camera_events(&mut self, input: winit::event::KeyboardInput) -> bool {
-        if self.camera_controller.is_some() {
-            self.camera_controller
-                .as_mut()
-                .unwrap()
-                .process_events(input) // TODO: remove Unwrap
-        } else {
-            true
+        match self.camera_controller.as_mut() {
+            Some(camera_controller) => camera_controller.process_events(input),
+            None => true,
+        }
+    }
+
+    //- Sprites ------------------------------------------------------------------------------------
+
+    /// Upload a texture, of any size supported by the device, that can be used to draw sprites.
+    pub fn load_sprite_texture(
+        &mut self,
+        texture: &DiffuseTexture,
+    ) -> Result<SpriteTextureId, RendererError> {
+        self.check_texture_size(texture)?;
+        Ok(self
+            .sprite_batch
+            .load_texture(&self.device, &self.queue, texture)?)
+    }
+
+    /// Enqueue a sprite to be drawn in the next frame.
+    ///
+    /// Sprites are batched by texture, so every texture used costs a single draw call.
+    pub fn draw_sprite(&mut self, texture: SpriteTextureId, sprite: &Sprite) {
+        self.sprite_batch.push(texture, sprite);
+    }
+
+    /// The camera used to draw the sprites.
+    ///
+    /// By default one world unit is one pixel and the origin is the center of the window;
+    /// its visible area follows the window size.
+    pub fn sprite_camera(&self) -> &OrthographicCamera {
+        self.sprite_batch.camera()
+    }
+
+    /// The camera used to draw the sprites, mutable to move or zoom it.
+    pub fn sprite_camera_mut(&mut self) -> &mut OrthographicCamera {
+        self.sprite_batch.camera_mut()
+    }
+
+    /// Make the color of the render target usable by the sprites, like a loaded texture.
+    pub fn load_sprite_render_target(&mut self, target: &RenderTarget) -> SpriteTextureId {
+        self.sprite_batch
+            .add_texture(&self.device, target.color_texture())
+    }
+
+    //- Texts --------------------------------------------------------------------------------------
+
+    /// Make a font available to the text draw calls.
+    pub fn load_font(&mut self, font: Font) -> FontId {
+        self.text_renderer.load_font(font)
+    }
+
+    /// Enqueue a text to be drawn in the next frame over everything else, with its top-left
+    /// corner at the given position in pixels from the top-left corner of the window.
+    ///
+    /// The glyphs are rasterized on demand, the first time they are used with a given size.
+    pub fn draw_text(
+        &mut self,
+        font: FontId,
+        text: &str,
+        position: cgmath::Point2<f32>,
+        style: &TextStyle,
+    ) {
+        self.text_renderer.push_screen(font, text, position, style);
+    }
+
+    /// Enqueue a text to be drawn in the next frame on the XY plane of the scene, with its
+    /// top-left corner at the given world position.
+    ///
+    /// Each pixel of the text style is `units_per_pixel` world units wide.
+    /// World-space texts are drawn only if the renderer has a camera.
+    pub fn draw_world_text(
+        &mut self,
+        font: FontId,
+        text: &str,
+        position: cgmath::Point3<f32>,
+        units_per_pixel: f32,
+        style: &TextStyle,
+    ) {
+        self.text_renderer
+            .push_world(font, text, position, units_per_pixel, style);
+    }
+
+    //- Debug Draw ---------------------------------------------------------------------------------
+
+    /// Enqueue a line drawn until `duration` has elapsed, or only in the next frame if it is
+    /// None.
+    ///
+    /// The debug shapes are drawn only if the renderer has a camera.
+    pub fn draw_debug_line<D: Into<Option<std::time::Duration>>>(
+        &mut self,
+        start: cgmath::Point3<f32>,
+        end: cgmath::Point3<f32>,
+        color: [f32; 4],
+        duration: D,
+    ) {
+        self.debug_draw.line(start, end, color, duration.into());
+    }
+
+    /// Enqueue the edges of an axis-aligned bounding box.
+    pub fn draw_debug_aabb<D: Into<Option<std::time::Duration>>>(
+        &mut self,
+        min: cgmath::Point3<f32>,
+        max: cgmath::Point3<f32>,
+        color: [f32; 4],
+        duration: D,
+    ) {
+        self.debug_draw.aabb(min, max, color, duration.into());
+    }
+
+    /// Enqueue a wireframe sphere made of three circles, one for each axis plane.
+    pub fn draw_debug_sphere<D: Into<Option<std::time::Duration>>>(
+        &mut self,
+        center: cgmath::Point3<f32>,
+        radius: f32,
+        color: [f32; 4],
+        duration: D,
+    ) {
+        self.debug_draw.sphere(center, radius, color, duration.into());
+    }
+
+    /// Enqueue a line with an arrow head on its end.
+    pub fn draw_debug_arrow<D: Into<Option<std::time::Duration>>>(
+        &mut self,
+        start: cgmath::Point3<f32>,
+        end: cgmath::Point3<f32>,
+        color: [f32; 4],
+        duration: D,
+    ) {
+        self.debug_draw.arrow(start, end, color, duration.into());
+    }
+
+    /// Enqueue a square grid on the XZ plane with `cells` cells of `spacing` size per side.
+    pub fn draw_debug_grid<D: Into<Option<std::time::Duration>>>(
+        &mut self,
+        center: cgmath::Point3<f32>,
+        cells: u32,
+        spacing: f32,
+        color: [f32; 4],
+        duration: D,
+    ) {
+        self.debug_draw
+            .grid(center, cells, spacing, color, duration.into());
+    }
+
+    /// Enqueue the X, Y and Z axes as red, green and blue arrows.
+    pub fn draw_debug_axes<D: Into<Option<std::time::Duration>>>(
+        &mut self,
+        origin: cgmath::Point3<f32>,
+        length: f32,
+        duration: D,
+    ) {
+        self.debug_draw.axes(origin, length, duration.into());
+    }
+
+    //- Debug Views --------------------------------------------------------------------------------
+
+    /// Draw the meshes with a debug view in place of their shaders, or go back to their
+    /// shaders with [DebugView::Shaded].
+    ///
+    /// The debug views need the vertices and a camera, see [RendererConfig::with_vertices] and
+    /// [RendererConfig::with_camera]: without them the meshes stay shaded. The depth prepass
+    /// is skipped while a debug view is active.
+    pub fn set_debug_view(&mut self, debug_view: DebugView) {
+        self.debug_view = debug_view;
+    }
+
+    ///
+    pub fn debug_view(&self) -> DebugView {
+        self.debug_view
+    }
+
+    /// If the debug views can be drawn, otherwise [Renderer::set_debug_view] has no effect.
+    pub fn has_debug_views(&self) -> bool {
+        self.debug_view_pipelines.is_some()
+    }
+
+    //- Particles ----------------------------------------------------------------------------------
+
+    /// Start spawning the particles of the emitter, drawn only if the renderer has a camera.
+    pub fn add_particle_emitter(&mut self, emitter: ParticleEmitter) -> ParticleEmitterId {
+        self.particle_system.add_emitter(&self.device, emitter)
+    }
+
+    /// Remove the emitter together with its particles still alive.
+    pub fn remove_particle_emitter(&mut self, id: ParticleEmitterId) {
+        self.particle_system.remove_emitter(id);
+    }
+
+    ///
+    pub fn set_particle_emitter_position(
+        &mut self,
+        id: ParticleEmitterId,
+        position: cgmath::Point3<f32>,
+    ) {
+        self.particle_system.set_emitter_position(id, position);
+    }
+
+    /// Spawn `count` particles in the next frame, in addition to the ones of the emitter rate.
+    pub fn burst_particles(&mut self, id: ParticleEmitterId, count: u32) {
+        self.particle_system.burst(id, count);
+    }
+
+    /// If the particles are simulated by a compute shader, otherwise the CPU is used.
+    pub fn is_simulating_particles_on_gpu(&self) -> bool {
+        self.particle_system.is_simulated_on_gpu()
+    }
+
+    //- Render Targets -----------------------------------------------------------------------------
+
+    /// Create an offscreen target of the given size in pixels, with the color and depth formats
+    /// of the window and cleared with the renderer clear color.
+    pub fn create_render_target(&mut self, width: u32, height: u32) -> RenderTarget {
+        self.render_target_count += 1;
+        RenderTarget::new(
+            self.render_target_count,
+            &self.device,
+            winit::dpi::PhysicalSize::new(width, height),
+            self.surface.format(),
+            &self.depth,
+            self.skybox.as_ref(),
+            self.clear_color,
+        )
+    }
+
+    /// Render the scene seen by `camera` into the target, submitted immediately so that the
+    /// next [Renderer::redraw] can sample it.
+    ///
+    /// The mesh, the skybox and the particles are drawn; the sprites, texts, debug shapes and
+    /// GUI belong to the window only. The camera should use the aspect ratio of the target, its
+    /// reverse-Z follows the [DepthConfig] of the renderer.
+    ///
+    /// The draws are not counted in the [Renderer::frame_stats], but in the
+    /// [Renderer::render_target_stats].
+    pub fn render_to_target(&mut self, target: &RenderTarget, camera: &C) {
+        let mut camera = camera.clone();
+        camera.set_reverse_z(self.depth.is_reverse_z());
+        let camera = &camera;
+        self.queue
+            .write_camera_buffer(camera, target.camera_bind_group());
+        if let Some(skybox_view) = target.skybox_view() {
+            self.queue.write_skybox_buffer(camera, skybox_view);
+        }
+        if self.drawn_blend_mode().is_transparent() {
+            self.sort_transparent_instances(camera, 0);
+        }
+
+        let mut stats = FrameStats::default();
+
+        // A texture cannot be sampled while it is rendered to
+        let mesh_pipeline = match self.material_target.as_ref() {
+            Some(material_target) if material_target.0 == target.id() => None,
+            _ => self.mesh_pipeline(),
+        };
+        let prepass_pipeline = mesh_pipeline
+            .and(self.prepass_pipeline.as_deref())
+            .filter(|_| self.has_prepass());
+
+        let mut encoder = self.create_command_encoder("Render Target Encoder");
+
+        if let Some(prepass_pipeline) = prepass_pipeline {
+            self.record_depth_prepass(
+                &mut encoder,
+                prepass_pipeline,
+                target.depth_view(),
+                &[],
+                target.size(),
+                &mut stats,
+            );
+        }
+
+        {
+            let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("Render Target Pass"),
+                color_attachments: &[wgpu::RenderPassColorAttachment {
+                    view: target.color_view(),
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(target.clear_color()),
+                        store: true,
+                    },
+                }],
+                depth_stencil_attachment: Some(
+                    self.depth_stencil_attachment(target.depth_view(), prepass_pipeline.is_none()),
+                ),
+            });
+
+            let view = SceneView {
+                camera_bind_group: Some(target.camera_bind_group()),
+                sprite_camera_bind_group: None,
+                screen_camera_bind_group: None,
+                skybox_view: target.skybox_view(),
+                instances: self.instances_slice(0),
+                layers: RenderLayers::MESH | RenderLayers::SKYBOX | RenderLayers::PARTICLES,
+            };
+            self.draw_layers(&mut render_pass, mesh_pipeline, view, &mut stats);
         }
+        self.render_target_stats = stats;
+
+        self.queue.submit(std::iter::once(encoder.finish()));
+    }
+
+    /// Sample the color of the target in the mesh material in place of the loaded texture, or
+    /// go back to the loaded texture with None.
+    ///
+    /// The material must have a texture, see [RendererConfig::with_texture_path]. While the
+    /// mesh samples a target it is not drawn into that same target.
+    pub fn set_material_texture(&mut self, target: Option<&RenderTarget>) {
+        self.material_target = target.map(|target| {
+            (
+                target.id(),
+                TextureBindGroupMetadatas::new(&self.device, target.color_texture()),
+            )
+        });
+    }
+
+    //- Viewports ----------------------------------------------------------------------------------
+
+    /// Draw the scene seen by the camera of the viewport, after the viewports already added.
+    ///
+    /// While there are viewports the renderer camera is not drawn, each viewport draws its
+    /// own: the transparent instances are sorted for it and the particles face it. The sprites
+    /// and the screen texts keep one world unit per pixel of the viewport.
+    pub fn add_viewport(&mut self, viewport: Viewport<C>) -> ViewportId {
+        self.viewports
+            .add(&self.device, self.skybox.as_ref(), viewport)
+    }
+
+    /// Stop drawing the viewport and give it back.
+    pub fn remove_viewport(&mut self, id: ViewportId) -> Option<Viewport<C>> {
+        self.viewports.remove(id)
+    }
+
+    ///
+    pub fn viewport(&self, id: ViewportId) -> Option<&Viewport<C>> {
+        self.viewports.get(id)
+    }
+
+    /// The viewport, mutable to move its camera or change its rect between the frames.
+    pub fn viewport_mut(&mut self, id: ViewportId) -> Option<&mut Viewport<C>> {
+        self.viewports.get_mut(id)
+    }
+
+    //- Windows ------------------------------------------------------------------------------------
+
+    /// Draw the viewport on another window, with its own surface sharing the device, the
+    /// pipelines and the resources of the renderer. Adding a window again replaces its viewport.
+    ///
+    /// The window must be removed with [Renderer::remove_window] before being dropped.
+    pub fn add_window(
+        &mut self,
+        window: &winit::window::Window,
+        viewport: Viewport<C>,
+    ) -> Result<(), RendererError> {
+        let surface = Surface::with_format(
+            &self.instance,
+            &self.adapter,
+            window,
+            self.surface.format(),
+            self.surface.present_mode(),
+        )
+        .map_err(|_| RendererError::WindowSurfaceRequest)?;
+        let slot = self
+            .viewports
+            .create_slot(&self.device, self.skybox.as_ref(), viewport);
+
+        let _ = self.remove_window(window.id());
+        self.windows
+            .push(WindowSurface::new(window, surface, &self.device, &self.depth, slot));
+        Ok(())
+    }
+
+    /// Stop drawing on the window and give back its viewport.
+    pub fn remove_window(&mut self, id: winit::window::WindowId) -> Option<Viewport<C>> {
+        let index = self.windows.iter().position(|window| window.id() == id)?;
+        Some(self.windows.remove(index).into_slot().into_viewport())
+    }
+
+    /// The ids of the windows added with [Renderer::add_window].
+    pub fn window_ids(&self) -> impl Iterator<Item = winit::window::WindowId> + '_ {
+        self.windows.iter().map(WindowSurface::id)
+    }
+
+    ///
+    pub fn window_viewport(&self, id: winit::window::WindowId) -> Option<&Viewport<C>> {
+        self.windows
+            .iter()
+            .find(|window| window.id() == id)
+            .map(|window| window.slot().viewport())
+    }
+
+    /// The viewport of the window, mutable to move its camera between the frames.
+    pub fn window_viewport_mut(
+        &mut self,
+        id: winit::window::WindowId,
+    ) -> Option<&mut Viewport<C>> {
+        self.windows
+            .iter_mut()
+            .find(|window| window.id() == id)
+            .map(|window| window.slot_mut().viewport_mut())
+    }
+
+    /// Resize the surface of an additional window, see [Renderer::resize] for the main one.
+    pub fn resize_window(
+        &mut self,
+        id: winit::window::WindowId,
+        new_size: winit::dpi::PhysicalSize<u32>,
+    ) {
+        if let Some(window) = self.windows.iter_mut().find(|window| window.id() == id) {
+            window.resize(&self.device, &self.depth, new_size);
+        }
+    }
+
+    /// Render a new frame on an additional window, after [Renderer::redraw] so that the
+    /// particles, debug shapes and texts prepared for the frame are drawn on it too.
+    pub fn redraw_window(&mut self, id: winit::window::WindowId) -> Result<(), wgpu::SurfaceError> {
+        let index = match self.windows.iter().position(|window| window.id() == id) {
+            Some(index) => index,
+            None => return Ok(()),
+        };
+
+        let clear_color = self.frame_clear_color();
+        let window_size = self.windows[index].size();
+        self.viewports.prepare_slot(
+            &self.queue,
+            self.windows[index].slot_mut(),
+            window_size,
+            self.sprite_batch.camera(),
+            clear_color,
+        );
+        let window = &self.windows[index];
+        if self.drawn_blend_mode().is_transparent() {
+            self.sort_transparent_instances(window.slot().viewport().camera(), 0);
+        }
+
+        let frame = window.surface().get_current_texture()?;
+        let frame_view = frame
+            .texture
+            .create_view(&wgpu::TextureViewDescriptor::default());
+
+        let mut encoder = self.create_command_encoder("Window Render Encoder");
+        let mut stats = std::mem::take(self.frame_stats.current_mut());
+
+        if let Some(prepass_pipeline) = self
+            .prepass_pipeline
+            .as_deref()
+            .filter(|_| self.has_prepass())
+        {
+            self.record_depth_prepass(
+                &mut encoder,
+                prepass_pipeline,
+                window.depth_view(),
+                &[window.slot()],
+                window.size(),
+                &mut stats,
+            );
+        }
+
+        {
+            let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("Window Render Pass"),
+                color_attachments: &[wgpu::RenderPassColorAttachment {
+                    view: &frame_view,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(self.frame_clear_color()),
+                        store: true,
+                    },
+                }],
+                depth_stencil_attachment: Some(self.depth_stencil_attachment(
+                    window.depth_view(),
+                    !self.has_prepass(),
+                )),
+            });
+
+            self.draw_viewport(
+                &mut render_pass,
+                window.slot(),
+                self.instances_slice(0),
+                window.size(),
+                self.has_prepass(),
+                &mut stats,
+            );
+        }
+        *self.frame_stats.current_mut() = stats;
+
+        self.queue.submit(std::iter::once(encoder.finish()));
+        frame.present();
+        Ok(())
+    }
+
+    //- Resources ----------------------------------------------------------------------------------
+
+    /// The meshes, textures, materials and pipelines referred by handles.
+    pub fn resources(&self) -> &ResourceRegistry {
+        &self.resources
+    }
+
+    /// The registry, mutable to retain, release and destroy the resources.
+    pub fn resources_mut(&mut self) -> &mut ResourceRegistry {
+        &mut self.resources
+    }
+
+    /// Upload the vertices and the indices, the returned handle holds the only reference.
+    pub fn create_mesh<V: Vertex + Pod, I: Index + Pod>(
+        &mut self,
+        vertices: &[V],
+        indices: Option<&[I]>,
+    ) -> Handle<Mesh> {
+        self.resources
+            .insert(Mesh::new(&self.device, vertices, indices))
+    }
+
+    /// Upload the texture, to be sampled through a material.
+    pub fn create_texture(
+        &mut self,
+        texture: &DiffuseTexture,
+    ) -> Result<Handle<Texture>, RendererError> {
+        self.check_texture_size(texture)?;
+        let texture = Texture::new(&self.device, &self.queue, texture.clone())?;
+        Ok(self.resources.insert(texture))
+    }
+
+    /// A material sampling the texture, which is kept alive by the material, drawn with the
+    /// pipeline of its blend mode.
+    pub fn create_material(
+        &mut self,
+        texture: Handle<Texture>,
+        blend_mode: BlendMode,
+    ) -> Result<Handle<Material>, RendererError> {
+        let material = Material::new(&self.device, &mut self.resources, texture, blend_mode)?;
+        Ok(self.resources.insert(material))
+    }
+
+    /// Build the pipeline through the [Renderer::pipeline_cache], shared with the identical
+    /// pipelines already built, and register it.
+    pub fn create_pipeline(
+        &mut self,
+        builder: RenderPipelineBuilder<'_>,
+    ) -> Handle<Arc<RenderPipeline>> {
+        let pipeline = builder.build_cached(&self.device, &mut self.pipeline_cache);
+        self.resources.insert(pipeline)
+    }
+
+    /// The mesh of the configured vertices and indices, or the one set with
+    /// [Renderer::set_mesh].
+    pub fn mesh(&self) -> Option<Handle<Mesh>> {
+        self.mesh
+    }
+
+    /// Draw another mesh with the renderer pipeline, its vertices must have the layout of the
+    /// configured ones. The renderer keeps a reference to it until it is replaced.
+    pub fn set_mesh(&mut self, mesh: Option<Handle<Mesh>>) -> Result<(), RendererError> {
+        Ok(self.resources.replace(&mut self.mesh, mesh)?)
+    }
+
+    /// Sample the texture of the material in place of the loaded one, or go back to the loaded
+    /// texture with None. The renderer keeps a reference to it until it is replaced.
+    ///
+    /// The material must have a texture, see [RendererConfig::with_texture_path].
+    pub fn set_material(
+        &mut self,
+        material: Option<Handle<Material>>,
+    ) -> Result<(), RendererError> {
+        Ok(self.resources.replace(&mut self.material, material)?)
+    }
+
+    //- Gui ----------------------------------------------------------------------------------------
+
+    /// Feed a window event to the immediate-mode GUI.
+    ///
+    /// Returns true if the GUI wants the event for itself, for example a click over a window
+    /// or a key typed into a text field, so that it is not used by the game logic too.
+    pub fn handle_gui_event(&mut self, event: &winit::event::WindowEvent<'_>) -> bool {
+        self.gui.handle_event(event)
+    }
+
+    /// Build the user interface with the input received since the previous call.
+    ///
+    /// The GUI is drawn over the scene by the next redraws, until this method is called again.
+    pub fn run_gui<F: FnOnce(&egui::Context)>(&mut self, run_ui: F) {
+        self.gui.run(self.window_size, run_ui);
+    }
+
+    /// The egui context, useful to change the GUI style or fonts.
+    pub fn gui_context(&self) -> &egui::Context {
+        self.gui.context()
     }
 
     //- Command Encoder ----------------------------------------------------------------------------
@@ -645,26 +1828,372 @@ where
 
     //- Rendering ----------------------------------------------------------------------------------
 
-    ///
+    /// Render a new frame, that is also captured if a recording is in progress or if
+    /// [Renderer::capture_next_frame] has been called.
     pub fn redraw(&mut self) -> Result<(), wgpu::SurfaceError> {
-        // TODO: remove clone (and probably) also unwraps
-        if self.camera.is_some() {
-            let camera = self.camera.as_mut().unwrap();
-            self.camera_controller
-                .as_ref()
-                .unwrap()
-                .update_camera(camera);
-            self.queue.write_camera_buffer(
-                self.camera.as_ref().unwrap(),
-                self.camera_metadatas.as_ref().unwrap(),
+        let is_recorded = match self.recorder.as_mut() {
+            Some(recorder) => recorder.begin_frame(),
+            None => false,
+        };
+        let is_requested = mem::take(&mut self.is_capture_requested);
+        if !is_recorded && !is_requested {
+            return self.render(None);
+        }
+
+        // The frame is copied before being presented, so no other frame is rendered for it
+        let capture = self.acquire_frame_capture();
+        let rendered = match capture.as_ref() {
+            Ok(capture) => self.render(Some(capture)),
+            Err(_) => self.render(None),
+        };
+        if let Err(error) = rendered {
+            self.is_capture_requested = is_requested;
+            if let Ok(capture) = capture {
+                self.release_frame_capture(capture);
+            }
+            return Err(error);
+        }
+
+        let image = capture.and_then(|capture| {
+            let image = capture.read(&self.device);
+            self.release_frame_capture(capture);
+            image
+        });
+        if is_recorded {
+            let result = match (image.as_ref(), self.recorder.as_mut()) {
+                (Ok(image), Some(recorder)) => recorder.write_frame(image),
+                _ => Ok(()),
+            };
+            match (result, image.as_ref()) {
+                (Err(error), _) => self.abort_recording(&error),
+                (Ok(_), Err(error)) => self.abort_recording(error),
+                (Ok(_), Ok(_)) => (),
+            }
+        }
+        if is_requested {
+            self.captured_frame = Some(image);
+        }
+        Ok(())
+    }
+
+    /// Copy the next frame rendered by [Renderer::redraw] before it is presented, the copy is
+    /// then returned by [Renderer::take_captured_frame].
+    ///
+    /// The frame capture must be enabled with [RendererConfig::with_frame_capture].
+    pub fn capture_next_frame(&mut self) -> Result<(), RendererError> {
+        if !self.surface.is_capturable() {
+            return Err(CaptureError::Disabled.into());
+        }
+        self.is_capture_requested = true;
+        Ok(())
+    }
+
+    /// The frame captured after a call to [Renderer::capture_next_frame], if it has already
+    /// been rendered.
+    pub fn take_captured_frame(&mut self) -> Option<Result<image::RgbaImage, RendererError>> {
+        self.captured_frame
+            .take()
+            .map(|result| result.map_err(RendererError::from))
+    }
+
+    /// Save the frame captured after a call to [Renderer::capture_next_frame] as an image, its
+    /// format deduced from the extension of the path; false if it has not been rendered yet.
+    pub fn save_captured_frame<P: AsRef<Path>>(&mut self, path: P) -> Result<bool, RendererError> {
+        match self.take_captured_frame() {
+            Some(image) => {
+                image?.save(path).map_err(CaptureError::from)?;
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+
+    //- Recording ----------------------------------------------------------------------------------
+
+    /// Capture the frames rendered by [Renderer::redraw] until [Renderer::stop_recording] is
+    /// called, a recording already in progress is stopped first.
+    pub fn start_recording(&mut self, mut recorder: FrameRecorder) -> Result<(), RendererError> {
+        if !self.surface.is_capturable() {
+            return Err(CaptureError::Disabled.into());
+        }
+        self.stop_recording()?;
+        recorder.start()?;
+        log::info!("Recording the frames into {:?}", recorder.output());
+        self.recorder = Some(recorder);
+        Ok(())
+    }
+
+    /// Stop the recording in progress, if any, and flush its output.
+    pub fn stop_recording(&mut self) -> Result<(), RendererError> {
+        if let Some(mut recorder) = self.recorder.take() {
+            recorder.finish()?;
+            log::info!(
+                "Recorded {} frames into {:?}",
+                recorder.written_frames(),
+                recorder.output()
             );
         }
+        Ok(())
+    }
+
+    ///
+    pub fn is_recording(&self) -> bool {
+        self.recorder.is_some()
+    }
+
+    /// The recording in progress, its [FrameRecorder::timestep] is the time the game logic
+    /// should advance every frame to obtain a deterministic recording.
+    pub fn recorder(&self) -> Option<&FrameRecorder> {
+        self.recorder.as_ref()
+    }
+
+    /// The time the game logic should advance every frame: the [FrameRecorder::timestep]
+    /// while recording, the duration of the last frame otherwise.
+    pub fn timestep(&self) -> std::time::Duration {
+        match self.recorder.as_ref() {
+            Some(recorder) => recorder.timestep(),
+            None => self.frame_stats.last().frame_time(),
+        }
+    }
+
+    // A buffer of the pool that fits the current frame, or a new one.
+    fn acquire_frame_capture(&mut self) -> Result<FrameCapture, CaptureError> {
+        let (window_size, format) = (self.window_size, self.surface.format());
+        self.capture_pool
+            .retain(|capture| capture.fits(window_size, format));
+        match self.capture_pool.pop() {
+            Some(capture) => Ok(capture),
+            None => FrameCapture::new(&self.device, window_size, format),
+        }
+    }
+
+    // Keep the buffer to be reused by the next captures, if the pool is not full.
+    fn release_frame_capture(&mut self, capture: FrameCapture) {
+        if self.capture_pool.len() < FRAME_CAPTURE_POOL_SIZE {
+            self.capture_pool.push(capture);
+        }
+    }
+
+    // Errors while recording cannot be returned by redraw, so the recording is just stopped.
+    fn abort_recording<E: Display>(&mut self, error: E) {
+        log::error!("The recording has been stopped: {}", error);
+        if let Some(mut recorder) = self.recorder.take() {
+            if let Err(error) = recorder.finish() {
+                log::error!("Unable to flush the recording: {}", error);
+            }
+        }
+    }
+
+    //- Profiling ----------------------------------------------------------------------------------
+
+    /// The GPU time of each render pass in the last frame whose timestamps have been read back,
+    /// empty if the GPU profiler is disabled.
+    pub fn gpu_pass_timings(&self) -> &[PassTiming] {
+        match self.gpu_profiler.as_ref() {
+            Some(gpu_profiler) => gpu_profiler.pass_timings(),
+            None => &[],
+        }
+    }
+
+    ///
+    pub fn is_gpu_profiling(&self) -> bool {
+        self.gpu_profiler.is_some()
+    }
+
+    //- Compute ------------------------------------------------------------------------------------
+
+    /// The device, to create the pipelines, buffers and bind groups of the compute dispatches.
+    pub fn device(&self) -> &Device {
+        &self.device
+    }
+
+    /// Record the dispatch in the next [Renderer::redraw], before or after the render passes.
+    pub fn enqueue_compute(&mut self, stage: ComputeStage, dispatch: ComputeDispatch) {
+        self.compute_dispatches.push((stage, dispatch));
+    }
+
+    /// Record and submit the dispatch immediately, outside of any frame.
+    pub fn dispatch_compute(&self, dispatch: &ComputeDispatch) {
+        let mut encoder = self.create_command_encoder("Compute Encoder");
+        dispatch.record(&mut encoder);
+        self.queue.submit(std::iter::once(encoder.finish()));
+    }
+
+    /// Wait for the submitted work and read back `count` elements of the buffer from its
+    /// beginning, the buffer must have the [COPY_SRC](wgpu::BufferUsages::COPY_SRC) usage.
+    ///
+    /// The copy is rounded up to a multiple of 4 bytes, the buffer must be at least that big.
+    pub fn read_buffer<T: Pod>(
+        &self,
+        buffer: &wgpu::Buffer,
+        count: usize,
+    ) -> Result<Vec<T>, RendererError> {
+        let size = count * std::mem::size_of::<T>();
+        if size == 0 {
+            return Ok(vec![]);
+        }
+
+        // The copies must be aligned, the extra bytes are dropped after the read; div_ceil is more
+        // recent than the supported Rust versions
+        let alignment = wgpu::COPY_BUFFER_ALIGNMENT;
+        #[allow(clippy::manual_div_ceil)]
+        let copy_size = (size as wgpu::BufferAddress + alignment - 1) / alignment * alignment;
+        let staging_buffer = self.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Readback Buffer"),
+            size: copy_size,
+            usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        let mut encoder = self.create_command_encoder("Readback Encoder");
+        encoder.copy_buffer_to_buffer(buffer, 0, &staging_buffer, 0, copy_size);
+        self.queue.submit(std::iter::once(encoder.finish()));
+
+        let slice = staging_buffer.slice(..);
+        let mapping = slice.map_async(wgpu::MapMode::Read);
+        self.device.poll(wgpu::Maintain::Wait);
+        mapping.block_on()?;
+
+        // Copied into a vector of T so that the elements are correctly aligned
+        let mut elements = vec![T::zeroed(); count];
+        bytemuck::cast_slice_mut::<T, u8>(&mut elements)
+            .copy_from_slice(&slice.get_mapped_range()[..size]);
+        staging_buffer.unmap();
+        Ok(elements)
+    }
+
+    //- Pipelines ----------------------------------------------------------------------------------
+
+    /// The cache of the pipelines built by the renderer.
+    pub fn pipeline_cache(&self) -> &PipelineCache {
+        &self.pipeline_cache
+    }
+
+    //- Statistics ---------------------------------------------------------------------------------
+
+    /// The statistics of the last rendered frame.
+    pub fn frame_stats(&self) -> &FrameStats {
+        self.frame_stats.last()
+    }
+
+    /// The draws of the last [Renderer::render_to_target], the timings and the memory are not
+    /// measured.
+    pub fn render_target_stats(&self) -> &FrameStats {
+        &self.render_target_stats
+    }
+
+    //- Frame Rendering ----------------------------------------------------------------------------
+
+    // The textures cannot be empty nor bigger than the device allows.
+    fn check_texture_size(&self, texture: &DiffuseTexture) -> Result<(), RendererError> {
+        let size = texture.size();
+        let max_size = self.device.limits().max_texture_dimension_2d;
+        let is_valid = |side: u32| side > 0 && side <= max_size;
+        if is_valid(size.width()) && is_valid(size.height()) {
+            Ok(())
+        } else {
+            Err(RendererError::TextureSize {
+                width: size.width(),
+                height: size.height(),
+            })
+        }
+    }
+
+    fn render(&mut self, capture: Option<&FrameCapture>) -> Result<(), wgpu::SurfaceError> {
+        self.frame_stats.begin_frame();
+
+        if let Some(camera) = self.camera.as_mut() {
+            if let Some(camera_controller) = self.camera_controller.as_ref() {
+                camera_controller.update_camera(camera);
+            }
+            if let Some(camera_metadatas) = self.camera_metadatas.as_ref() {
+                self.queue.write_camera_buffer(camera, camera_metadatas);
+            }
+            if let Some(skybox) = self.skybox.as_ref() {
+                self.queue.write_skybox_buffer(camera, skybox.view());
+            }
+        }
+        let clear_color = self.frame_clear_color();
+        self.viewports.prepare(
+            &self.queue,
+            self.window_size,
+            self.sprite_batch.camera(),
+            clear_color,
+        );
+
+        self.sprite_batch.prepare(&self.device, &self.queue);
+        self.text_renderer.prepare(&self.device, &self.queue);
+        self.debug_draw.prepare(&self.device, &self.queue);
+
+        // While recording the simulated time advances at the recorder pace
+        let timestep = match self.recorder.as_ref() {
+            Some(recorder) => recorder.timestep(),
+            None => self.frame_stats.current_mut().frame_time(),
+        };
+        let particle_dispatches = self.particle_system.update(
+            &self.queue,
+            timestep.as_secs_f32().min(MAX_PARTICLES_TIMESTEP),
+        );
+
+        self.gui
+            .prepare(&self.device, &self.queue, self.window_size);
+
+        // Every viewport draws its own copy of the instances, sorted for its camera
+        if self.drawn_blend_mode().is_transparent() {
+            let viewports_count = self.viewports.iter().count();
+            self.reserve_instances_copies(viewports_count.max(1));
+            if let (true, Some(camera)) = (self.viewports.is_empty(), self.camera.as_ref()) {
+                self.sort_transparent_instances(camera, 0);
+            }
+            for (index, slot) in self.viewports.iter().enumerate() {
+                self.sort_transparent_instances(slot.viewport().camera(), index);
+            }
+        }
 
         let frame = self.surface.get_current_texture()?;
         let texture = &frame.texture;
         let frame_view = texture.create_view(&wgpu::TextureViewDescriptor::default());
 
         let mut encoder = self.create_command_encoder("Render Encoder");
+        if let Some(gpu_profiler) = self.gpu_profiler.as_mut() {
+            gpu_profiler.begin_frame(&self.device);
+        }
+
+        // The particles are simulated before the dispatches enqueued by the user
+        let mut compute_dispatches = particle_dispatches
+            .into_iter()
+            .map(|dispatch| (ComputeStage::BeforeRender, dispatch))
+            .collect::<Vec<_>>();
+        compute_dispatches.append(&mut self.compute_dispatches);
+        self.record_compute(&mut encoder, &compute_dispatches, ComputeStage::BeforeRender);
+
+        // Taken to be recorded while the render passes borrow the renderer
+        let mut stats = std::mem::take(self.frame_stats.current_mut());
+
+        if let Some(prepass_pipeline) = self
+            .prepass_pipeline
+            .as_deref()
+            .filter(|_| self.has_prepass())
+        {
+            if let Some(gpu_profiler) = self.gpu_profiler.as_mut() {
+                gpu_profiler.begin_pass(&mut encoder, "Depth Prepass");
+            }
+            self.record_depth_prepass(
+                &mut encoder,
+                prepass_pipeline,
+                self.texture_depth_metadatas.view(),
+                &self.viewports.iter().collect::<Vec<_>>(),
+                self.window_size,
+                &mut stats,
+            );
+            if let Some(gpu_profiler) = self.gpu_profiler.as_mut() {
+                gpu_profiler.end_pass(&mut encoder);
+            }
+        }
+
+        if let Some(gpu_profiler) = self.gpu_profiler.as_mut() {
+            gpu_profiler.begin_pass(&mut encoder, "Render Pass");
+        }
 
         {
             let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
@@ -673,79 +2202,587 @@ where
                     view: &frame_view,
                     resolve_target: None,
                     ops: wgpu::Operations {
-                        load: wgpu::LoadOp::Clear(self.clear_color),
+                        load: wgpu::LoadOp::Clear(self.frame_clear_color()),
                         store: true,
                     },
                 }],
-                depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
-                    view: self.texture_depth_metadatas.view(),
-                    depth_ops: Some(wgpu::Operations {
-                        load: wgpu::LoadOp::Clear(1.0),
+                depth_stencil_attachment: Some(self.depth_stencil_attachment(
+                    self.texture_depth_metadatas.view(),
+                    !self.has_prepass(),
+                )),
+            });
+
+            if self.viewports.is_empty() {
+                self.draw_layers(
+                    &mut render_pass,
+                    self.mesh_pipeline(),
+                    self.camera_scene_view(RenderLayers::ALL),
+                    &mut stats,
+                );
+            }
+
+            // The viewports are drawn in order, each one over the ones before
+            let after_prepass = self.has_prepass();
+            for (index, slot) in self.viewports.iter().enumerate() {
+                self.draw_viewport(
+                    &mut render_pass,
+                    slot,
+                    self.instances_slice(index),
+                    self.window_size,
+                    after_prepass,
+                    &mut stats,
+                );
+            }
+        }
+        *self.frame_stats.current_mut() = stats;
+
+        if let Some(gpu_profiler) = self.gpu_profiler.as_mut() {
+            gpu_profiler.end_pass(&mut encoder);
+            gpu_profiler.begin_pass(&mut encoder, "Gui Render Pass");
+        }
+
+        // The GUI overlay has its own pass, without the depth buffer
+        {
+            let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("Gui Render Pass"),
+                color_attachments: &[wgpu::RenderPassColorAttachment {
+                    view: &frame_view,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Load,
                         store: true,
-                    }),
-                    stencil_ops: None,
-                }),
+                    },
+                }],
+                depth_stencil_attachment: None,
             });
 
-            if self.renderer_pipeline.is_some() {
-                let pipeline = self.renderer_pipeline.as_ref().unwrap();
-                // TODO: remove this expose call creating an RenderPass wrapper
-                render_pass.set_pipeline(pipeline.expose_wrapped_render_pipeline());
-
-                if self.texture_bind_group_metadatas.is_empty() {
-                    if self.camera.is_some() {
-                        render_pass.set_bind_group(
-                            0,
-                            self.camera_metadatas.as_ref().unwrap().bind_group(), // TODO: Remove unwrap()
-                            &[],
-                        );
-                    }
-                } else {
-                    render_pass.set_bind_group(
-                        0,
-                        // TODO: hardcoded :(
-                        self.texture_bind_group_metadatas[8][8].bind_group(),
-                        &[],
-                    );
-                    if self.camera.is_some() {
-                        render_pass.set_bind_group(
-                            1,
-                            self.camera_metadatas.as_ref().unwrap().bind_group(), // TODO: Remove unwrap()
-                            &[],
-                        );
-                    }
-                }
+            self.gui.draw(
+                &mut render_pass,
+                self.window_size,
+                self.frame_stats.current_mut(),
+            );
+        }
 
-                if self.vertex_buffer.is_some() {
-                    render_pass
-                        .set_vertex_buffer(0, self.vertex_buffer.as_ref().unwrap().slice(..));
-                }
+        if let Some(gpu_profiler) = self.gpu_profiler.as_mut() {
+            gpu_profiler.end_pass(&mut encoder);
+        }
 
-                if self.instances_buffer.is_some() {
-                    render_pass
-                        .set_vertex_buffer(1, self.instances_buffer.as_ref().unwrap().slice(..));
-                }
+        self.record_compute(&mut encoder, &compute_dispatches, ComputeStage::AfterRender);
 
-                if self.index_buffer.is_some() {
-                    render_pass.set_index_buffer(
-                        self.index_buffer.as_ref().unwrap().slice(..),
-                        wgpu::IndexFormat::Uint16,
-                    );
-                    render_pass.draw_indexed(
-                        0..self.num_indices,
-                        0,
-                        0..self.instances.as_ref().unwrap().len() as _,
-                    );
-                } else {
-                    // TODO: uhm, sound like a bug. Probably too tied with lw_03_example and vertices
-                    render_pass.draw(0..3, 0..1);
-                }
-            }
+        if let Some(gpu_profiler) = self.gpu_profiler.as_mut() {
+            gpu_profiler.resolve(&mut encoder);
+        }
+
+        if let Some(capture) = capture {
+            capture.copy_from(&mut encoder, texture);
         }
 
         self.queue.submit(std::iter::once(encoder.finish()));
         frame.present();
 
+        if let Some(gpu_profiler) = self.gpu_profiler.as_mut() {
+            gpu_profiler.end_frame();
+        }
+
+        self.gui.free_textures();
+
+        self.frame_stats.end_frame(
+            self.device.cumulative_buffer_memory(),
+            self.device.cumulative_texture_memory(),
+        );
+
         Ok(())
     }
+
+    // The pipeline of the active debug view, None when the meshes are drawn by their shaders.
+    fn debug_view_pipeline(&self) -> Option<&RenderPipeline> {
+        self.debug_view_pipelines
+            .as_ref()
+            .and_then(|pipelines| pipelines.pipeline(self.debug_view))
+    }
+
+    // The blend mode of the material drawn, or the configured one for the loaded texture and
+    // the sampled render targets.
+    fn drawn_blend_mode(&self) -> BlendMode {
+        let material = self
+            .material
+            .and_then(|material| self.resources.get(material).ok());
+        match (self.material_target.as_ref(), material) {
+            (None, Some(material)) => material.blend_mode(),
+            _ => self.blend_mode,
+        }
+    }
+
+    // The pipeline drawing the mesh with the blend mode of its material.
+    fn mesh_pipeline(&self) -> Option<&RenderPipeline> {
+        self.renderer_pipelines
+            .get(&self.drawn_blend_mode())
+            .map(Arc::as_ref)
+    }
+
+    // If the depth prepass is drawn, it is skipped by the debug views that have their own
+    // depth states, and by the transparent materials.
+    fn has_prepass(&self) -> bool {
+        self.prepass_pipeline.is_some()
+            && !self.drawn_blend_mode().is_transparent()
+            && self.debug_view_pipeline().is_none()
+    }
+
+    // The overdraw is counted from black.
+    fn frame_clear_color(&self) -> wgpu::Color {
+        match self.debug_view_pipeline() {
+            Some(_) if self.debug_view == DebugView::Overdraw => wgpu::Color::BLACK,
+            _ => self.clear_color,
+        }
+    }
+
+    // The opaque geometry fills the depth buffer first, so that the color pass shades only its
+    // visible fragments.
+    fn record_depth_prepass<'a>(
+        &'a self,
+        encoder: &'a mut wgpu::CommandEncoder,
+        prepass_pipeline: &'a RenderPipeline,
+        depth_view: &'a wgpu::TextureView,
+        viewports: &[&'a ViewportSlot<C>],
+        target_size: winit::dpi::PhysicalSize<u32>,
+        stats: &mut FrameStats,
+    ) {
+        let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Depth Prepass"),
+            color_attachments: &[],
+            depth_stencil_attachment: Some(self.depth_stencil_attachment(depth_view, true)),
+        });
+
+        // Without viewports the renderer camera is drawn, the opaque instances are not sorted
+        let instances = self.instances_slice(0);
+        if viewports.is_empty() {
+            let camera_bind_group = self.camera_metadatas.as_ref();
+            self.draw_mesh(
+                &mut render_pass,
+                prepass_pipeline,
+                camera_bind_group,
+                instances,
+                stats,
+            );
+            return;
+        }
+
+        for &slot in viewports {
+            if !self.set_viewport(&mut render_pass, slot, target_size) {
+                continue;
+            }
+            let viewport = slot.viewport();
+            if let Some(pipeline) = self.viewports.prepass_clear_pipeline(viewport.clear()) {
+                slot.draw_clear(&mut render_pass, pipeline, stats);
+            }
+            if viewport.layers().contains(RenderLayers::MESH) {
+                let camera_bind_group = Some(slot.camera_bind_group());
+                self.draw_mesh(
+                    &mut render_pass,
+                    prepass_pipeline,
+                    camera_bind_group,
+                    instances,
+                    stats,
+                );
+            }
+        }
+    }
+
+    // The renderer camera, used when there are no viewports and by the render targets.
+    fn camera_scene_view(&self, layers: RenderLayers) -> SceneView<'_> {
+        SceneView {
+            camera_bind_group: self.camera_metadatas.as_ref(),
+            sprite_camera_bind_group: None,
+            screen_camera_bind_group: None,
+            skybox_view: self.skybox.as_ref().map(Skybox::view),
+            instances: self.instances_slice(0),
+            layers,
+        }
+    }
+
+    // Clear and draw a viewport over the ones drawn before it.
+    fn draw_viewport<'a>(
+        &'a self,
+        render_pass: &mut wgpu::RenderPass<'a>,
+        slot: &'a ViewportSlot<C>,
+        instances: Option<wgpu::BufferSlice<'a>>,
+        target_size: winit::dpi::PhysicalSize<u32>,
+        after_prepass: bool,
+        stats: &mut FrameStats,
+    ) {
+        if !self.set_viewport(render_pass, slot, target_size) {
+            return;
+        }
+        let viewport = slot.viewport();
+        if let Some(pipeline) = self
+            .viewports
+            .clear_pipeline(viewport.clear(), after_prepass)
+        {
+            slot.draw_clear(render_pass, pipeline, stats);
+        }
+        let scene_view = SceneView {
+            camera_bind_group: Some(slot.camera_bind_group()),
+            sprite_camera_bind_group: Some(slot.sprite_camera_bind_group()),
+            screen_camera_bind_group: Some(slot.screen_camera_bind_group()),
+            skybox_view: slot.skybox_view(),
+            instances,
+            layers: viewport.layers(),
+        };
+        self.draw_layers(
+            render_pass,
+            self.mesh_pipeline(),
+            scene_view,
+            stats,
+        );
+    }
+
+    // Limit the drawing to the viewport on a target of the given size, false if it is not
+    // visible.
+    fn set_viewport(
+        &self,
+        render_pass: &mut wgpu::RenderPass<'_>,
+        slot: &ViewportSlot<C>,
+        target_size: winit::dpi::PhysicalSize<u32>,
+    ) -> bool {
+        let viewport = slot.viewport();
+        let (rect, scissor) = match (
+            viewport.pixel_rect(target_size),
+            viewport.pixel_scissor(target_size),
+        ) {
+            (Some(rect), Some(scissor)) => (rect, scissor),
+            _ => return false,
+        };
+        render_pass.set_viewport(rect[0], rect[1], rect[2], rect[3], 0.0, 1.0);
+        render_pass.set_scissor_rect(scissor[0], scissor[1], scissor[2], scissor[3]);
+        true
+    }
+
+    // Draw the layers of the scene seen by a camera, on the window or on a render target.
+    fn draw_layers<'a>(
+        &'a self,
+        render_pass: &mut wgpu::RenderPass<'a>,
+        mesh_pipeline: Option<&'a RenderPipeline>,
+        view: SceneView<'a>,
+        stats: &mut FrameStats,
+    ) {
+        let mesh_pipeline = mesh_pipeline.filter(|_| view.layers.contains(RenderLayers::MESH));
+
+        // The debug views replace the mesh shaders and are drawn as opaque
+        let debug_view_pipeline = mesh_pipeline.and(self.debug_view_pipeline());
+        let mesh_pipeline = mesh_pipeline.filter(|_| debug_view_pipeline.is_none());
+        if let Some(pipeline) = debug_view_pipeline {
+            self.draw_debug_view(
+                render_pass,
+                pipeline,
+                view.camera_bind_group,
+                view.instances,
+                stats,
+            );
+        }
+
+        // Opaque first, so that the skybox and the transparent draws are tested against it
+        if !self.drawn_blend_mode().is_transparent() {
+            if let Some(pipeline) = mesh_pipeline {
+                self.draw_mesh(
+                    render_pass,
+                    pipeline,
+                    view.camera_bind_group,
+                    view.instances,
+                    stats,
+                );
+            }
+        }
+
+        // Drawn after the opaque geometry so that its fragments are discarded by the
+        // depth test wherever something has already been drawn
+        // The overdraw would be covered by the skybox, the meshes don't write the depth buffer
+        let is_overdraw = debug_view_pipeline.is_some() && self.debug_view == DebugView::Overdraw;
+        if let (Some(skybox), Some(skybox_view), true) = (
+            self.skybox.as_ref(),
+            view.skybox_view,
+            view.layers.contains(RenderLayers::SKYBOX) && !is_overdraw,
+        ) {
+            skybox.draw(render_pass, skybox_view, stats);
+        }
+
+        if self.drawn_blend_mode().is_transparent() {
+            if let Some(pipeline) = mesh_pipeline {
+                self.draw_mesh(
+                    render_pass,
+                    pipeline,
+                    view.camera_bind_group,
+                    view.instances,
+                    stats,
+                );
+            }
+        }
+
+        if view.layers.contains(RenderLayers::SPRITES) {
+            self.sprite_batch
+                .draw(render_pass, view.sprite_camera_bind_group, stats);
+        }
+        if view.layers.contains(RenderLayers::PARTICLES) {
+            self.particle_system
+                .draw(render_pass, view.camera_bind_group, stats);
+        }
+        if view.layers.contains(RenderLayers::DEBUG) {
+            self.debug_draw
+                .draw(render_pass, view.camera_bind_group, stats);
+        }
+        if view.layers.contains(RenderLayers::TEXTS) {
+            self.text_renderer.draw(
+                render_pass,
+                view.camera_bind_group,
+                view.screen_camera_bind_group,
+                stats,
+            );
+        }
+    }
+
+    // Draw the vertices with the configured texture and the given copy of the instances, using
+    // the pipeline of the color pass or the one of the depth prepass.
+    fn draw_mesh<'a>(
+        &'a self,
+        render_pass: &mut wgpu::RenderPass<'a>,
+        pipeline: &'a RenderPipeline,
+        camera_bind_group: Option<&'a CameraBindGroup>,
+        instances: Option<wgpu::BufferSlice<'a>>,
+        stats: &mut FrameStats,
+    ) {
+        // TODO: remove this expose call creating an RenderPass wrapper
+        render_pass.set_pipeline(pipeline.expose_wrapped_render_pipeline());
+        render_pass.set_stencil_reference(self.stencil_reference);
+        stats.record_pipeline();
+
+        // Same order used for the pipeline layout creation
+        let mut bind_group_index = 0;
+        if let Some(texture_bind_group_metadatas) = self.texture_bind_group_metadatas.as_ref() {
+            // A sampled render target first, then the material and finally the loaded texture
+            let material = self
+                .material
+                .and_then(|material| self.resources.get(material).ok());
+            let texture_bind_group = match (self.material_target.as_ref(), material) {
+                (Some(material_target), _) => material_target.1.bind_group(),
+                (None, Some(material)) => material.bind_group(),
+                (None, None) => texture_bind_group_metadatas.bind_group(),
+            };
+            render_pass.set_bind_group(bind_group_index, texture_bind_group, &[]);
+            stats.record_bind_group();
+            bind_group_index += 1;
+        }
+        if let Some(camera_bind_group) = camera_bind_group {
+            render_pass.set_bind_group(
+                bind_group_index,
+                camera_bind_group.bind_group(),
+                &[],
+            );
+            stats.record_bind_group();
+            bind_group_index += 1;
+        }
+        if let Some(skybox) = self.skybox.as_ref() {
+            render_pass.set_bind_group(
+                bind_group_index,
+                skybox.cube_bind_group_metadatas().bind_group(),
+                &[],
+            );
+            stats.record_bind_group();
+        }
+
+        if let Some(instances) = instances {
+            render_pass.set_vertex_buffer(1, instances);
+        }
+
+        match self.mesh.map(|mesh| self.resources.get(mesh)) {
+            Some(Ok(mesh)) => {
+                let instance_count = self
+                    .instances
+                    .as_ref()
+                    .map_or(1, |instances| instances.len() as u32);
+                mesh.draw(render_pass, 0..instance_count, stats);
+            }
+            // The mesh has been destroyed, the pipeline would miss its vertex buffer
+            Some(Err(_)) => (),
+            None => {
+                // TODO: uhm, sound like a bug. Probably too tied with lw_03_example and vertices
+                render_pass.draw(0..3, 0..1);
+                stats.record_draw(3, 1, wgpu::PrimitiveTopology::TriangleList);
+            }
+        }
+    }
+
+    // Draw the vertices with the pipeline of a debug view, which needs the camera only.
+    fn draw_debug_view<'a>(
+        &'a self,
+        render_pass: &mut wgpu::RenderPass<'a>,
+        pipeline: &'a RenderPipeline,
+        camera_bind_group: Option<&'a CameraBindGroup>,
+        instances: Option<wgpu::BufferSlice<'a>>,
+        stats: &mut FrameStats,
+    ) {
+        let (camera_bind_group, mesh, debug_view_pipelines) = match (
+            camera_bind_group,
+            self.mesh.and_then(|mesh| self.resources.get(mesh).ok()),
+            self.debug_view_pipelines.as_ref(),
+        ) {
+            (Some(camera_bind_group), Some(mesh), Some(pipelines)) => {
+                (camera_bind_group, mesh, pipelines)
+            }
+            _ => return,
+        };
+
+        render_pass.set_pipeline(pipeline.expose_wrapped_render_pipeline());
+        render_pass.set_bind_group(0, camera_bind_group.bind_group(), &[]);
+        stats.record_pipeline();
+        stats.record_bind_group();
+
+        // The same instances of the shaded meshes, for the overdraw to be the same
+        let (instances, instance_count) = match (instances, self.instances.as_ref()) {
+            (Some(instances), Some(raw_instances)) => (instances, raw_instances.len() as u32),
+            _ => (debug_view_pipelines.identity_instance(), 1),
+        };
+        render_pass.set_vertex_buffer(1, instances);
+        if debug_view_pipelines.is_unindexed(self.debug_view) {
+            mesh.draw_unindexed(render_pass, 0..instance_count, stats);
+        } else {
+            mesh.draw(render_pass, 0..instance_count, stats);
+        }
+    }
+
+    // The depth buffer, cleared or kept as left by a previous pass.
+    fn depth_stencil_attachment<'a>(
+        &self,
+        view: &'a wgpu::TextureView,
+        clear: bool,
+    ) -> wgpu::RenderPassDepthStencilAttachment<'a> {
+        let (depth_load, stencil_load) = if clear {
+            (wgpu::LoadOp::Clear(self.depth.clear_depth()), wgpu::LoadOp::Clear(0))
+        } else {
+            (wgpu::LoadOp::Load, wgpu::LoadOp::Load)
+        };
+        wgpu::RenderPassDepthStencilAttachment {
+            view,
+            depth_ops: Some(wgpu::Operations {
+                load: depth_load,
+                store: true,
+            }),
+            stencil_ops: if self.depth.has_stencil() {
+                Some(wgpu::Operations {
+                    load: stencil_load,
+                    store: true,
+                })
+            } else {
+                None
+            },
+        }
+    }
+
+    // Grow the instances buffer to hold a copy of the instances for every view drawn in the
+    // same frame, the copies are uploaded before the frame is submitted.
+    fn reserve_instances_copies(&mut self, copies: usize) {
+        if let (Some(instances), true) = (self.instances.as_ref(), copies > self.instances_copies) {
+            self.instances_buffer = Some(create_instances_buffer(&self.device, instances, copies));
+            self.instances_copies = copies;
+        }
+    }
+
+    // The copy of the instances drawn by the view at the index, the transparent instances are
+    // sorted for each view while the opaque ones share the first copy.
+    fn instances_slice(&self, index: usize) -> Option<wgpu::BufferSlice<'_>> {
+        let (instances, instances_buffer) =
+            match (self.instances.as_ref(), self.instances_buffer.as_ref()) {
+                (Some(instances), Some(instances_buffer)) => (instances, instances_buffer),
+                _ => return None,
+            };
+        let copy_size = instances_copy_size(instances);
+        if copy_size == 0 {
+            return Some(instances_buffer.slice(..));
+        }
+        let index = match self.drawn_blend_mode().is_transparent() {
+            true if index < self.instances_copies => index,
+            _ => 0,
+        };
+        let offset = copy_size * index as wgpu::BufferAddress;
+        Some(instances_buffer.slice(offset..offset + copy_size))
+    }
+
+    // Upload the instances sorted from the farthest to the nearest to the camera, in the copy
+    // drawn by the view at the index, so that the transparent ones are blended over the ones
+    // behind them.
+    fn sort_transparent_instances(&self, camera: &C, index: usize) {
+        use cgmath::{EuclideanSpace, InnerSpace};
+
+        let (instances, instances_buffer) =
+            match (self.instances.as_ref(), self.instances_buffer.as_ref()) {
+                (Some(instances), Some(instances_buffer)) => (instances, instances_buffer),
+                _ => return,
+            };
+        if index >= self.instances_copies {
+            return;
+        }
+        let offset = instances_copy_size(instances) * index as wgpu::BufferAddress;
+
+        let eye = camera.eye().to_vec();
+        let mut transparent_queue = instances
+            .iter()
+            .map(|instance| ((instance.position() - eye).magnitude2(), instance.to_raw()))
+            .collect::<Vec<_>>();
+        transparent_queue.sort_by(|a, b| b.0.partial_cmp(&a.0).unwrap_or(Ordering::Equal));
+
+        let instance_data = transparent_queue
+            .into_iter()
+            .map(|(_, raw)| raw)
+            .collect::<Vec<_>>();
+        self.queue.write_buffer(
+            instances_buffer,
+            offset,
+            bytemuck::cast_slice(&instance_data),
+        );
+    }
+
+    fn record_compute(
+        &mut self,
+        encoder: &mut wgpu::CommandEncoder,
+        compute_dispatches: &[(ComputeStage, ComputeDispatch)],
+        stage: ComputeStage,
+    ) {
+        for dispatch in compute_dispatches
+            .iter()
+            .filter(|pair| pair.0 == stage)
+            .map(|pair| &pair.1)
+        {
+            if let Some(gpu_profiler) = self.gpu_profiler.as_mut() {
+                gpu_profiler.begin_pass(encoder, dispatch.label());
+            }
+            dispatch.record(encoder);
+            if let Some(gpu_profiler) = self.gpu_profiler.as_mut() {
+                gpu_profiler.end_pass(encoder);
+            }
+        }
+    }
+}
+
+//= FUNCTIONS ======================================================================================
+
+// The buffer holds `copies` copies of the instances, one for each view sorting them.
+fn create_instances_buffer(device: &Device, instances: &[Instance], copies: usize) -> wgpu::Buffer {
+    let instance_data = instances
+        .iter()
+        .map(Instance::to_raw)
+        .cycle()
+        .take(instances.len() * copies)
+        .collect::<Vec<_>>();
+
+    // TODO: When we will create the generics about Vertices we will use the
+    //  Device.create_vertex_buffer_init instead
+    device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+        label: Some("Instance Buffer"),
+        contents: bytemuck::cast_slice(&instance_data),
+        // Rewritten every frame when the instances are sorted for transparency
+        usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+    })
+}
+
+// The size in bytes of one copy of the instances in the instances buffer.
+fn instances_copy_size(instances: &[Instance]) -> wgpu::BufferAddress {
+    (instances.len() * mem::size_of::<InstanceRaw>()) as wgpu::BufferAddress
 }
@@ -0,0 +1,795 @@
+//= USES ===========================================================================================
+
+use std::{
+    fmt::{self, Debug},
+    hash::{Hash, Hasher},
+    marker::PhantomData,
+    mem,
+    sync::Arc,
+};
+
+use bytemuck::Pod;
+use thiserror::Error;
+
+use irid_assets::DiffuseTexture;
+use irid_assets_interface::{ImageSize, Index, Vertex};
+
+use crate::{
+    device::Device,
+    material::BlendMode,
+    pipeline::RenderPipeline,
+    queue::{Queue, QueueError},
+    stats::FrameStats,
+    texture_metadatas::{TextureBindGroupMetadatas, TextureImageMetadatas},
+};
+
+//= ERRORS =========================================================================================
+
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, Error, PartialEq, Eq)]
+pub enum ResourceError {
+    #[error("the resource of the handle has already been destroyed")]
+    StaleHandle,
+}
+
+//= HANDLE =========================================================================================
+
+/// A typed reference to a resource owned by a [ResourceRegistry].
+///
+/// Copying a handle does not keep its resource alive, see [ResourceRegistry::retain]. Once the
+/// resource is destroyed its handles are stale: the registry refuses them, even if the slot is
+/// reused by a newer resource.
+pub struct Handle<T> {
+    index: u32,
+    generation: u32,
+    marker: PhantomData<fn() -> T>,
+}
+
+impl<T> Handle<T> {
+    ///
+    pub fn index(&self) -> u32 {
+        self.index
+    }
+
+    ///
+    pub fn generation(&self) -> u32 {
+        self.generation
+    }
+}
+
+// Implemented by hand because the derives would require the same traits on T.
+
+impl<T> Clone for Handle<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for Handle<T> {}
+
+impl<T> PartialEq for Handle<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.index == other.index && self.generation == other.generation
+    }
+}
+
+impl<T> Eq for Handle<T> {}
+
+impl<T> Hash for Handle<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.index.hash(state);
+        self.generation.hash(state);
+    }
+}
+
+impl<T> Debug for Handle<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Handle")
+            .field("type", &std::any::type_name::<T>())
+            .field("index", &self.index)
+            .field("generation", &self.generation)
+            .finish()
+    }
+}
+
+//= RESOURCE POOL ==================================================================================
+
+// A slot keeps its generation after the resource is destroyed, to recognize the stale handles;
+// once the generation is exhausted the slot is retired and never reused.
+#[derive(Debug)]
+struct Slot<T> {
+    generation: u32,
+    entry: Option<(T, u32)>,
+}
+
+/// The resources of a single type, with their reference counts.
+#[derive(Debug)]
+pub struct ResourcePool<T> {
+    slots: Vec<Slot<T>>,
+    free_indices: Vec<u32>,
+    retired_count: usize,
+}
+
+impl<T> Default for ResourcePool<T> {
+    fn default() -> Self {
+        Self {
+            slots: vec![],
+            free_indices: vec![],
+            retired_count: 0,
+        }
+    }
+}
+
+impl<T> ResourcePool<T> {
+    //- Getters ------------------------------------------------------------------------------------
+
+    ///
+    pub fn get(&self, handle: Handle<T>) -> Result<&T, ResourceError> {
+        self.entry(handle).map(|entry| &entry.0)
+    }
+
+    ///
+    pub fn get_mut(&mut self, handle: Handle<T>) -> Result<&mut T, ResourceError> {
+        self.entry_mut(handle).map(|entry| &mut entry.0)
+    }
+
+    /// If the resource of the handle is still alive.
+    pub fn contains(&self, handle: Handle<T>) -> bool {
+        self.entry(handle).is_ok()
+    }
+
+    /// How many handles keep the resource alive.
+    pub fn ref_count(&self, handle: Handle<T>) -> Result<u32, ResourceError> {
+        self.entry(handle).map(|entry| entry.1)
+    }
+
+    /// The number of alive resources.
+    pub fn len(&self) -> usize {
+        self.slots.len() - self.free_indices.len() - self.retired_count
+    }
+
+    ///
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The handles of the alive resources.
+    pub fn handles(&self) -> impl Iterator<Item = Handle<T>> + '_ {
+        self.slots.iter().enumerate().filter_map(|(index, slot)| {
+            slot.entry.as_ref().map(|_| Handle {
+                index: index as u32,
+                generation: slot.generation,
+                marker: PhantomData,
+            })
+        })
+    }
+
+    // The alive resources, in no particular order.
+    fn resources_mut(&mut self) -> impl Iterator<Item = &mut T> + '_ {
+        self.slots
+            .iter_mut()
+            .filter_map(|slot| slot.entry.as_mut().map(|entry| &mut entry.0))
+    }
+
+    //- Lifetime -----------------------------------------------------------------------------------
+
+    // The resource starts with a single reference, the one of the returned handle.
+    fn insert(&mut self, resource: T) -> Handle<T> {
+        let index = match self.free_indices.pop() {
+            Some(index) => index,
+            None => {
+                self.slots.push(Slot {
+                    generation: 0,
+                    entry: None,
+                });
+                (self.slots.len() - 1) as u32
+            }
+        };
+        let slot = &mut self.slots[index as usize];
+        slot.entry = Some((resource, 1));
+        Handle {
+            index,
+            generation: slot.generation,
+            marker: PhantomData,
+        }
+    }
+
+    fn retain(&mut self, handle: Handle<T>) -> Result<(), ResourceError> {
+        let entry = self.entry_mut(handle)?;
+        entry.1 += 1;
+        Ok(())
+    }
+
+    // The resource is given back when its last reference is released.
+    fn release(&mut self, handle: Handle<T>) -> Result<Option<T>, ResourceError> {
+        let entry = self.entry_mut(handle)?;
+        entry.1 -= 1;
+        if entry.1 > 0 {
+            return Ok(None);
+        }
+        self.remove(handle).map(Some)
+    }
+
+    fn remove(&mut self, handle: Handle<T>) -> Result<T, ResourceError> {
+        let slot = self
+            .slots
+            .get_mut(handle.index as usize)
+            .filter(|slot| slot.generation == handle.generation)
+            .ok_or(ResourceError::StaleHandle)?;
+        let entry = slot.entry.take().ok_or(ResourceError::StaleHandle)?;
+        // Wrapping the generation around would make the oldest stale handles valid again
+        match slot.generation.checked_add(1) {
+            Some(generation) => {
+                slot.generation = generation;
+                self.free_indices.push(handle.index);
+            }
+            None => self.retired_count += 1,
+        }
+        Ok(entry.0)
+    }
+
+    fn entry(&self, handle: Handle<T>) -> Result<&(T, u32), ResourceError> {
+        self.slots
+            .get(handle.index as usize)
+            .filter(|slot| slot.generation == handle.generation)
+            .and_then(|slot| slot.entry.as_ref())
+            .ok_or(ResourceError::StaleHandle)
+    }
+
+    fn entry_mut(&mut self, handle: Handle<T>) -> Result<&mut (T, u32), ResourceError> {
+        self.slots
+            .get_mut(handle.index as usize)
+            .filter(|slot| slot.generation == handle.generation)
+            .and_then(|slot| slot.entry.as_mut())
+            .ok_or(ResourceError::StaleHandle)
+    }
+}
+
+//= RESOURCE TRAIT =================================================================================
+
+/// A GPU resource kept by the [ResourceRegistry].
+pub trait Resource: Sized {
+    /// The pool of the registry holding the resources of this type.
+    fn pool(registry: &ResourceRegistry) -> &ResourcePool<Self>;
+
+    ///
+    fn pool_mut(registry: &mut ResourceRegistry) -> &mut ResourcePool<Self>;
+
+    /// Release the handles held by the resource, once it has been destroyed.
+    #[allow(unused_variables)]
+    fn release_dependencies(&self, registry: &mut ResourceRegistry) {}
+}
+
+//= RESOURCE REGISTRY ==============================================================================
+
+/// Owns the meshes, textures, materials and pipelines referred by [Handle]s.
+///
+/// The pipelines are shared, so that the ones built by a
+/// [PipelineCache](crate::PipelineCache) can be registered too.
+///
+/// A resource is destroyed explicitly with [ResourceRegistry::destroy], or when the last of its
+/// references is released; the GPU memory is freed once the frames using it are completed.
+#[derive(Debug, Default)]
+pub struct ResourceRegistry {
+    meshes: ResourcePool<Mesh>,
+    textures: ResourcePool<Texture>,
+    materials: ResourcePool<Material>,
+    pipelines: ResourcePool<Arc<RenderPipeline>>,
+}
+
+impl ResourceRegistry {
+    //- Constructors -------------------------------------------------------------------------------
+
+    ///
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    //- Getters ------------------------------------------------------------------------------------
+
+    ///
+    pub fn get<T: Resource>(&self, handle: Handle<T>) -> Result<&T, ResourceError> {
+        T::pool(self).get(handle)
+    }
+
+    ///
+    pub fn get_mut<T: Resource>(&mut self, handle: Handle<T>) -> Result<&mut T, ResourceError> {
+        T::pool_mut(self).get_mut(handle)
+    }
+
+    /// If the resource of the handle is still alive.
+    pub fn contains<T: Resource>(&self, handle: Handle<T>) -> bool {
+        T::pool(self).contains(handle)
+    }
+
+    /// All the resources of a type.
+    pub fn pool<T: Resource>(&self) -> &ResourcePool<T> {
+        T::pool(self)
+    }
+
+    //- Lifetime -----------------------------------------------------------------------------------
+
+    /// Take the ownership of the resource, the returned handle holds its only reference.
+    pub fn insert<T: Resource>(&mut self, resource: T) -> Handle<T> {
+        T::pool_mut(self).insert(resource)
+    }
+
+    /// Add a reference to the resource, to be given back with [ResourceRegistry::release].
+    pub fn retain<T: Resource>(&mut self, handle: Handle<T>) -> Result<(), ResourceError> {
+        T::pool_mut(self).retain(handle)
+    }
+
+    /// Remove a reference to the resource, destroying it if it was the last one; returns
+    /// whether it has been destroyed.
+    pub fn release<T: Resource>(&mut self, handle: Handle<T>) -> Result<bool, ResourceError> {
+        match T::pool_mut(self).release(handle)? {
+            Some(resource) => {
+                resource.release_dependencies(self);
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+
+    /// Move a reference from the resource held in `current` to the given one, useful for the
+    /// resources held by the renderer.
+    pub fn replace<T: Resource>(
+        &mut self,
+        current: &mut Option<Handle<T>>,
+        handle: Option<Handle<T>>,
+    ) -> Result<(), ResourceError> {
+        if let Some(handle) = handle {
+            self.retain(handle)?;
+        }
+        if let Some(current) = current.take() {
+            let _ = self.release(current);
+        }
+        *current = handle;
+        Ok(())
+    }
+
+    /// Destroy the resource whatever its references, all its handles become stale.
+    pub fn destroy<T: Resource>(&mut self, handle: Handle<T>) -> Result<(), ResourceError> {
+        let resource = T::pool_mut(self).remove(handle)?;
+        resource.release_dependencies(self);
+        Ok(())
+    }
+
+    //- Device Recovery ----------------------------------------------------------------------------
+
+    /// Upload again the textures on a new device, they replace the lost ones only once given to
+    /// [ResourceRegistry::recreate].
+    pub(crate) fn upload_textures(
+        &self,
+        device: &Device,
+        queue: &Queue,
+    ) -> Result<Vec<(Handle<Texture>, TextureImageMetadatas)>, QueueError> {
+        let mut textures = Vec::new();
+        for handle in self.textures.handles() {
+            if let Ok(texture) = self.textures.get(handle) {
+                textures.push((handle, texture.upload(device, queue)?));
+            }
+        }
+        Ok(textures)
+    }
+
+    /// Recreate the meshes and the materials on a new device, together with the textures
+    /// uploaded by [ResourceRegistry::upload_textures]; their handles stay valid.
+    ///
+    /// The pipelines and the materials whose texture has been destroyed cannot be recreated,
+    /// they are destroyed and their handles become stale.
+    pub(crate) fn recreate(
+        &mut self,
+        device: &Device,
+        textures: Vec<(Handle<Texture>, TextureImageMetadatas)>,
+    ) {
+        for mesh in self.meshes.resources_mut() {
+            mesh.recreate(device);
+        }
+        for (handle, image_metadatas) in textures {
+            if let Ok(texture) = self.textures.get_mut(handle) {
+                texture.image_metadatas = image_metadatas;
+            }
+        }
+        // A material keeps its texture alive, unless the texture has been destroyed explicitly
+        let materials = self.materials.handles().collect::<Vec<_>>();
+        for handle in materials {
+            let texture_handle = match self.materials.get(handle) {
+                Ok(material) => material.texture,
+                Err(_) => continue,
+            };
+            let texture = self.textures.get(texture_handle);
+            match (texture, self.materials.get_mut(handle)) {
+                (Ok(texture), Ok(material)) => material.recreate(device, texture),
+                _ => {
+                    let _ = self.destroy(handle);
+                }
+            }
+        }
+        let pipelines = self.pipelines.handles().collect::<Vec<_>>();
+        for pipeline in pipelines {
+            let _ = self.pipelines.remove(pipeline);
+        }
+    }
+}
+
+//= MESH ===========================================================================================
+
+/// Vertices, and optionally their indices, uploaded to the GPU.
+#[derive(Debug)]
+pub struct Mesh {
+    vertex_buffer: wgpu::Buffer,
+    vertex_count: u32,
+    index_buffer: Option<(wgpu::Buffer, wgpu::IndexFormat)>,
+    index_count: u32,
+    // The vertices repeated in the order of the indices, only on the devices without the line
+    // polygon mode, for the barycentric wireframe
+    unindexed_vertex_buffer: Option<wgpu::Buffer>,
+    // Retained to recreate the buffers after the device has been lost
+    vertex_bytes: Vec<u8>,
+    vertex_size: usize,
+    index_bytes: Option<Vec<u8>>,
+}
+
+impl Mesh {
+    //- Constructors -------------------------------------------------------------------------------
+
+    /// The indices must be of the same type of the ones of the pipelines drawing the mesh.
+    ///
+    /// Without the [POLYGON_MODE_LINE](wgpu::Features::POLYGON_MODE_LINE) feature the
+    /// indexed meshes keep a second vertex buffer, used by the wireframe debug view.
+    pub fn new<V: Vertex + Pod, I: Index + Pod>(
+        device: &Device,
+        vertices: &[V],
+        indices: Option<&[I]>,
+    ) -> Self {
+        let index_format = if mem::size_of::<I>() == 2 {
+            wgpu::IndexFormat::Uint16
+        } else {
+            wgpu::IndexFormat::Uint32
+        };
+        Mesh::from_bytes(
+            device,
+            bytemuck::cast_slice(vertices).to_vec(),
+            mem::size_of::<V>(),
+            indices.map(|indices| (bytemuck::cast_slice(indices).to_vec(), index_format)),
+        )
+    }
+
+    fn from_bytes(
+        device: &Device,
+        vertex_bytes: Vec<u8>,
+        vertex_size: usize,
+        indices: Option<(Vec<u8>, wgpu::IndexFormat)>,
+    ) -> Self {
+        let index_count = indices.as_ref().map_or(0, |&(ref index_bytes, index_format)| {
+            index_bytes.len() / index_format_size(index_format)
+        });
+        let unindexed_vertex_buffer = match indices.as_ref() {
+            Some(&(ref index_bytes, index_format))
+                if !device
+                    .features()
+                    .contains(wgpu::Features::POLYGON_MODE_LINE) =>
+            {
+                let unindexed_vertices =
+                    unindex(&vertex_bytes, vertex_size, index_bytes, index_format);
+                Some(create_buffer(
+                    device,
+                    "Mesh Unindexed Vertex Buffer",
+                    &unindexed_vertices,
+                    wgpu::BufferUsages::VERTEX,
+                ))
+            }
+            _ => None,
+        };
+        Self {
+            vertex_buffer: create_buffer(
+                device,
+                "Mesh Vertex Buffer",
+                &vertex_bytes,
+                wgpu::BufferUsages::VERTEX,
+            ),
+            vertex_count: (vertex_bytes.len() / vertex_size.max(1)) as u32,
+            index_buffer: indices.as_ref().map(|&(ref index_bytes, index_format)| {
+                let buffer = create_buffer(
+                    device,
+                    "Mesh Index Buffer",
+                    index_bytes,
+                    wgpu::BufferUsages::INDEX,
+                );
+                (buffer, index_format)
+            }),
+            index_count: index_count as u32,
+            unindexed_vertex_buffer,
+            vertex_bytes,
+            vertex_size,
+            index_bytes: indices.map(|(index_bytes, _)| index_bytes),
+        }
+    }
+
+    // Upload again the retained vertices and indices.
+    fn recreate(&mut self, device: &Device) {
+        let indices = match (self.index_bytes.take(), self.index_buffer.as_ref()) {
+            (Some(index_bytes), Some(&(_, index_format))) => Some((index_bytes, index_format)),
+            _ => None,
+        };
+        let vertex_bytes = mem::take(&mut self.vertex_bytes);
+        *self = Mesh::from_bytes(device, vertex_bytes, self.vertex_size, indices);
+    }
+
+    //- Getters ------------------------------------------------------------------------------------
+
+    ///
+    pub fn vertex_count(&self) -> u32 {
+        self.vertex_count
+    }
+
+    /// Zero if the mesh has no indices.
+    pub fn index_count(&self) -> u32 {
+        self.index_count
+    }
+
+    //- Rendering ----------------------------------------------------------------------------------
+
+    /// Set the buffers of the mesh and draw it, the pipeline and its bind groups must be
+    /// already set.
+    pub(crate) fn draw<'a>(
+        &'a self,
+        render_pass: &mut wgpu::RenderPass<'a>,
+        instances: std::ops::Range<u32>,
+        stats: &mut FrameStats,
+    ) {
+        render_pass.set_vertex_buffer(0, self.vertex_buffer.slice(..));
+        let instance_count = instances.end - instances.start;
+        match self.index_buffer.as_ref() {
+            Some(&(ref index_buffer, index_format)) => {
+                render_pass.set_index_buffer(index_buffer.slice(..), index_format);
+                render_pass.draw_indexed(0..self.index_count, 0, instances);
+                stats.record_draw_indexed(
+                    self.index_count,
+                    instance_count,
+                    wgpu::PrimitiveTopology::TriangleList,
+                );
+            }
+            None => {
+                render_pass.draw(0..self.vertex_count, instances);
+                stats.record_draw(
+                    self.vertex_count,
+                    instance_count,
+                    wgpu::PrimitiveTopology::TriangleList,
+                );
+            }
+        }
+    }
+
+    /// Like [Mesh::draw], but the indexed meshes are drawn from their vertices repeated in the
+    /// order of the indices, so that every three vertices make a triangle.
+    ///
+    /// Only the devices without the line polygon mode have them, otherwise the mesh is drawn
+    /// as by [Mesh::draw].
+    pub(crate) fn draw_unindexed<'a>(
+        &'a self,
+        render_pass: &mut wgpu::RenderPass<'a>,
+        instances: std::ops::Range<u32>,
+        stats: &mut FrameStats,
+    ) {
+        let unindexed_vertex_buffer = match self.unindexed_vertex_buffer.as_ref() {
+            Some(unindexed_vertex_buffer) => unindexed_vertex_buffer,
+            None => return self.draw(render_pass, instances, stats),
+        };
+        render_pass.set_vertex_buffer(0, unindexed_vertex_buffer.slice(..));
+        let instance_count = instances.end - instances.start;
+        render_pass.draw(0..self.index_count, instances);
+        stats.record_draw(
+            self.index_count,
+            instance_count,
+            wgpu::PrimitiveTopology::TriangleList,
+        );
+    }
+}
+
+impl Resource for Mesh {
+    fn pool(registry: &ResourceRegistry) -> &ResourcePool<Self> {
+        &registry.meshes
+    }
+
+    fn pool_mut(registry: &mut ResourceRegistry) -> &mut ResourcePool<Self> {
+        &mut registry.meshes
+    }
+}
+
+//= TEXTURE ========================================================================================
+
+/// An RGBA texture that can be sampled by the materials.
+///
+/// The image is kept on the CPU too, to recreate the texture after the device has been lost.
+#[derive(Debug)]
+pub struct Texture {
+    image_metadatas: TextureImageMetadatas,
+    image: DiffuseTexture,
+}
+
+impl Texture {
+    //- Constructors -------------------------------------------------------------------------------
+
+    /// Create the texture and enqueue the write of its texels, see [Renderer::create_texture].
+    ///
+    /// [Renderer::create_texture]: crate::Renderer::create_texture
+    pub(crate) fn new(
+        device: &Device,
+        queue: &Queue,
+        image: DiffuseTexture,
+    ) -> Result<Self, QueueError> {
+        let size = image.size();
+        let image_metadatas = TextureImageMetadatas::new(device, size.width(), size.height());
+        queue.write_texture(&image_metadatas, &image)?;
+        Ok(Self {
+            image_metadatas,
+            image,
+        })
+    }
+
+    // Upload again the retained image, on a new device.
+    fn upload(&self, device: &Device, queue: &Queue) -> Result<TextureImageMetadatas, QueueError> {
+        let size = self.image.size();
+        let image_metadatas = TextureImageMetadatas::new(device, size.width(), size.height());
+        queue.write_texture(&image_metadatas, &self.image)?;
+        Ok(image_metadatas)
+    }
+
+    //- Getters ------------------------------------------------------------------------------------
+
+    ///
+    pub fn size(&self) -> wgpu::Extent3d {
+        *self.image_metadatas.image_size()
+    }
+
+    ///
+    pub fn expose_wrapped_texture(&self) -> &wgpu::Texture {
+        self.image_metadatas.texture()
+    }
+}
+
+impl Resource for Texture {
+    fn pool(registry: &ResourceRegistry) -> &ResourcePool<Self> {
+        &registry.textures
+    }
+
+    fn pool_mut(registry: &mut ResourceRegistry) -> &mut ResourcePool<Self> {
+        &mut registry.textures
+    }
+}
+
+//= MATERIAL =======================================================================================
+
+/// The texture sampled by a mesh, a material keeps its texture alive.
+#[derive(Debug)]
+pub struct Material {
+    texture: Handle<Texture>,
+    blend_mode: BlendMode,
+    bind_group_metadatas: TextureBindGroupMetadatas,
+}
+
+impl Material {
+    //- Constructors -------------------------------------------------------------------------------
+
+    /// Add a reference to the texture, released when the material is destroyed.
+    ///
+    /// The blend mode chooses the pipeline the material is drawn with.
+    pub fn new(
+        device: &Device,
+        registry: &mut ResourceRegistry,
+        texture: Handle<Texture>,
+        blend_mode: BlendMode,
+    ) -> Result<Self, ResourceError> {
+        let bind_group_metadatas =
+            TextureBindGroupMetadatas::new(device, registry.get(texture)?.expose_wrapped_texture());
+        registry.retain(texture)?;
+        Ok(Self {
+            texture,
+            blend_mode,
+            bind_group_metadatas,
+        })
+    }
+
+    // Bind the texture recreated on a new device.
+    fn recreate(&mut self, device: &Device, texture: &Texture) {
+        self.bind_group_metadatas =
+            TextureBindGroupMetadatas::new(device, texture.expose_wrapped_texture());
+    }
+
+    //- Getters ------------------------------------------------------------------------------------
+
+    ///
+    pub fn texture(&self) -> Handle<Texture> {
+        self.texture
+    }
+
+    ///
+    pub fn blend_mode(&self) -> BlendMode {
+        self.blend_mode
+    }
+
+    ///
+    pub(crate) fn bind_group(&self) -> &wgpu::BindGroup {
+        self.bind_group_metadatas.bind_group()
+    }
+}
+
+impl Resource for Material {
+    fn pool(registry: &ResourceRegistry) -> &ResourcePool<Self> {
+        &registry.materials
+    }
+
+    fn pool_mut(registry: &mut ResourceRegistry) -> &mut ResourcePool<Self> {
+        &mut registry.materials
+    }
+
+    fn release_dependencies(&self, registry: &mut ResourceRegistry) {
+        let _ = registry.release(self.texture);
+    }
+}
+
+//= PIPELINE =======================================================================================
+
+impl Resource for Arc<RenderPipeline> {
+    fn pool(registry: &ResourceRegistry) -> &ResourcePool<Self> {
+        &registry.pipelines
+    }
+
+    fn pool_mut(registry: &mut ResourceRegistry) -> &mut ResourcePool<Self> {
+        &mut registry.pipelines
+    }
+}
+
+//= FUNCTIONS ======================================================================================
+
+// The buffer of the bytes, which are not required to be of any type.
+fn create_buffer(
+    device: &Device,
+    label_text: &str,
+    contents: &[u8],
+    usage: wgpu::BufferUsages,
+) -> wgpu::Buffer {
+    device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+        label: Some(label_text),
+        contents,
+        usage,
+    })
+}
+
+fn index_format_size(index_format: wgpu::IndexFormat) -> usize {
+    match index_format {
+        wgpu::IndexFormat::Uint16 => mem::size_of::<u16>(),
+        wgpu::IndexFormat::Uint32 => mem::size_of::<u32>(),
+    }
+}
+
+// The vertices, of the given size in bytes, repeated in the order of the indices.
+fn unindex(
+    vertex_bytes: &[u8],
+    vertex_size: usize,
+    index_bytes: &[u8],
+    index_format: wgpu::IndexFormat,
+) -> Vec<u8> {
+    let indices: Vec<usize> = match index_format {
+        wgpu::IndexFormat::Uint16 => index_bytes
+            .chunks_exact(mem::size_of::<u16>())
+            .map(|bytes| bytemuck::pod_read_unaligned::<u16>(bytes) as usize)
+            .collect(),
+        wgpu::IndexFormat::Uint32 => index_bytes
+            .chunks_exact(mem::size_of::<u32>())
+            .map(|bytes| bytemuck::pod_read_unaligned::<u32>(bytes) as usize)
+            .collect(),
+    };
+    // An out of range index, invalid anyway, takes a zeroed vertex to keep the triangles aligned
+    let zeroed_vertex = vec![0; vertex_size];
+    let mut unindexed = Vec::with_capacity(indices.len() * vertex_size);
+    for index in indices {
+        let vertex = vertex_bytes
+            .get(index * vertex_size..(index + 1) * vertex_size)
+            .unwrap_or(&zeroed_vertex);
+        unindexed.extend_from_slice(vertex);
+    }
+    unindexed
+}
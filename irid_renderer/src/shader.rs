@@ -7,3 +7,35 @@ pub(crate) const DEFAULT_VERTEX_ENTRY_POINT: &str = "vs_main";
 /// This is the default fragment state entry point name that will be used in which case
 /// one will not be passed.
 pub(crate) const DEFAULT_FRAGMENT_ENTRY_POINT: &str = "fs_main";
+
+/// This is the default compute entry point name that will be used in which case
+/// one will not be passed.
+pub(crate) const DEFAULT_COMPUTE_ENTRY_POINT: &str = "cs_main";
+
+/// Source of the shader used to draw the skybox.
+pub(crate) const SKYBOX_SHADER: &str = include_str!("shaders/skybox.wgsl");
+
+/// Source of the shader used to draw the sprites.
+pub(crate) const SPRITE_SHADER: &str = include_str!("shaders/sprite.wgsl");
+
+/// Source of the shader used to draw the glyphs of the texts.
+pub(crate) const TEXT_SHADER: &str = include_str!("shaders/text.wgsl");
+
+/// Source of the shader used to draw the debug lines.
+pub(crate) const DEBUG_LINE_SHADER: &str = include_str!("shaders/debug_line.wgsl");
+
+/// Source of the shader used to draw the meshes in the debug views.
+pub(crate) const DEBUG_VIEW_SHADER: &str = include_str!("shaders/debug_view.wgsl");
+
+/// Source of the shader used to draw the immediate-mode GUI.
+pub(crate) const GUI_SHADER: &str = include_str!("shaders/gui.wgsl");
+
+/// Source of the shader used to draw the particles.
+pub(crate) const PARTICLE_SHADER: &str = include_str!("shaders/particle.wgsl");
+
+/// Source of the compute shader used to simulate the particles.
+pub(crate) const PARTICLE_SIMULATION_SHADER: &str =
+    include_str!("shaders/particle_simulation.wgsl");
+
+/// Source of the shader used to clear the viewports.
+pub(crate) const VIEWPORT_CLEAR_SHADER: &str = include_str!("shaders/viewport_clear.wgsl");
@@ -0,0 +1,142 @@
+// Debug views of the meshes, drawn in place of the user shaders.
+
+struct CameraUniform {
+    view_proj: mat4x4<f32>;
+};
+
+[[group(0), binding(0)]]
+var<uniform> camera: CameraUniform;
+
+struct VertexOutput {
+    [[builtin(position)]] clip_position: vec4<f32>;
+    [[location(0)]] world_position: vec3<f32>;
+    [[location(1)]] tex_coords: vec2<f32>;
+    [[location(2)]] barycentric: vec3<f32>;
+};
+
+// The model matrix of the instance, in the locations of InstanceRaw
+struct InstanceInput {
+    [[location(5)]] model_matrix_0: vec4<f32>;
+    [[location(6)]] model_matrix_1: vec4<f32>;
+    [[location(7)]] model_matrix_2: vec4<f32>;
+    [[location(8)]] model_matrix_3: vec4<f32>;
+};
+
+fn project(position: vec3<f32>, instance: InstanceInput) -> VertexOutput {
+    let model_matrix = mat4x4<f32>(
+        instance.model_matrix_0,
+        instance.model_matrix_1,
+        instance.model_matrix_2,
+        instance.model_matrix_3,
+    );
+    let world_position = model_matrix * vec4<f32>(position, 1.0);
+    var out: VertexOutput;
+    out.clip_position = camera.view_proj * world_position;
+    out.world_position = world_position.xyz;
+    out.tex_coords = vec2<f32>(0.0, 0.0);
+    out.barycentric = vec3<f32>(1.0, 1.0, 1.0);
+    return out;
+}
+
+// The vertices without texture coordinates
+[[stage(vertex)]]
+fn vs_main([[location(0)]] position: vec3<f32>, instance: InstanceInput) -> VertexOutput {
+    return project(position, instance);
+}
+
+[[stage(vertex)]]
+fn vs_tex_coords(
+    [[location(0)]] position: vec3<f32>,
+    [[location(1)]] tex_coords: vec2<f32>,
+    instance: InstanceInput
+) -> VertexOutput {
+    var out: VertexOutput = project(position, instance);
+    out.tex_coords = tex_coords;
+    return out;
+}
+
+// Every three vertices make a triangle, so the mesh must be drawn without indices
+[[stage(vertex)]]
+fn vs_barycentric(
+    [[builtin(vertex_index)]] vertex_index: u32,
+    [[location(0)]] position: vec3<f32>,
+    instance: InstanceInput
+) -> VertexOutput {
+    var out: VertexOutput = project(position, instance);
+    let corner = vertex_index % 3u;
+    out.barycentric = vec3<f32>(
+        select(0.0, 1.0, corner == 0u),
+        select(0.0, 1.0, corner == 1u),
+        select(0.0, 1.0, corner == 2u)
+    );
+    return out;
+}
+
+let WIREFRAME_COLOR: vec4<f32> = vec4<f32>(0.1, 0.1, 0.1, 1.0);
+
+// The lines rasterized with the line polygon mode
+[[stage(fragment)]]
+fn fs_wireframe(in: VertexOutput) -> [[location(0)]] vec4<f32> {
+    return WIREFRAME_COLOR;
+}
+
+// Only the fragments near an edge, about one pixel wide, are kept
+[[stage(fragment)]]
+fn fs_barycentric(in: VertexOutput) -> [[location(0)]] vec4<f32> {
+    let edge_distance = min(in.barycentric.x, min(in.barycentric.y, in.barycentric.z));
+    if (edge_distance > fwidth(edge_distance)) {
+        discard;
+    }
+    return WIREFRAME_COLOR;
+}
+
+// The normals of the faces, from the derivatives of the positions, mapped to [0, 1]
+[[stage(fragment)]]
+fn fs_normals(in: VertexOutput) -> [[location(0)]] vec4<f32> {
+    let normal = normalize(cross(dpdx(in.world_position), dpdy(in.world_position)));
+    return vec4<f32>(normal * 0.5 + 0.5, 1.0);
+}
+
+let CHECKER_SQUARES: f32 = 8.0;
+
+fn checker(cells: vec3<f32>) -> vec4<f32> {
+    let parity = (floor(cells.x) + floor(cells.y) + floor(cells.z)) % 2.0;
+    let shade = select(0.25, 0.9, abs(parity) > 0.5);
+    return vec4<f32>(shade, shade, shade, 1.0);
+}
+
+[[stage(fragment)]]
+fn fs_tex_coords_checker(in: VertexOutput) -> [[location(0)]] vec4<f32> {
+    let cells = in.tex_coords * CHECKER_SQUARES;
+    // Tinted by the coordinates, to show their orientation too
+    return checker(vec3<f32>(cells, 0.0)) * vec4<f32>(in.tex_coords, 1.0, 1.0);
+}
+
+// Without texture coordinates the checkerboard is laid in world space
+[[stage(fragment)]]
+fn fs_world_checker(in: VertexOutput) -> [[location(0)]] vec4<f32> {
+    return checker(in.world_position);
+}
+
+// The nearest fragments are white, the far plane is black
+fn depth_color(nearness: f32) -> vec4<f32> {
+    // Most of the depth range is crowded near the far plane
+    let shade = pow(clamp(nearness, 0.0, 1.0), 0.125);
+    return vec4<f32>(shade, shade, shade, 1.0);
+}
+
+[[stage(fragment)]]
+fn fs_depth(in: VertexOutput) -> [[location(0)]] vec4<f32> {
+    return depth_color(1.0 - in.clip_position.z);
+}
+
+[[stage(fragment)]]
+fn fs_depth_reversed(in: VertexOutput) -> [[location(0)]] vec4<f32> {
+    return depth_color(in.clip_position.z);
+}
+
+// Added for every fragment drawn, from black to red, yellow and finally white
+[[stage(fragment)]]
+fn fs_overdraw(in: VertexOutput) -> [[location(0)]] vec4<f32> {
+    return vec4<f32>(0.1, 0.04, 0.02, 1.0);
+}
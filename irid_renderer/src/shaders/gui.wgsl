@@ -0,0 +1,56 @@
+// Immediate-mode GUI meshes, positions are in points from the top-left corner of the window.
+
+struct ScreenUniform {
+    // Only the first two components are used, the others are padding
+    size_in_points: vec4<f32>;
+};
+
+[[group(0), binding(0)]]
+var t_gui: texture_2d<f32>;
+[[group(0), binding(1)]]
+var s_gui: sampler;
+
+[[group(1), binding(0)]]
+var<uniform> screen: ScreenUniform;
+
+struct VertexInput {
+    [[location(0)]] position: vec2<f32>;
+    [[location(1)]] tex_coords: vec2<f32>;
+    [[location(2)]] color: vec4<f32>;
+};
+
+struct VertexOutput {
+    [[builtin(position)]] clip_position: vec4<f32>;
+    [[location(0)]] tex_coords: vec2<f32>;
+    [[location(1)]] color: vec4<f32>;
+};
+
+// The vertex colors are sRGB encoded, while the blending happens in linear space.
+fn linear_from_srgb(srgb: vec3<f32>) -> vec3<f32> {
+    let cutoff = srgb < vec3<f32>(0.04045);
+    let lower = srgb / vec3<f32>(12.92);
+    let higher = pow((srgb + vec3<f32>(0.055)) / vec3<f32>(1.055), vec3<f32>(2.4));
+    return select(higher, lower, cutoff);
+}
+
+[[stage(vertex)]]
+fn vs_main(vertex: VertexInput) -> VertexOutput {
+    let size = screen.size_in_points.xy;
+
+    var out: VertexOutput;
+    out.clip_position = vec4<f32>(
+        2.0 * vertex.position.x / size.x - 1.0,
+        1.0 - 2.0 * vertex.position.y / size.y,
+        0.0,
+        1.0,
+    );
+    out.tex_coords = vertex.tex_coords;
+    out.color = vec4<f32>(linear_from_srgb(vertex.color.rgb), vertex.color.a);
+    return out;
+}
+
+[[stage(fragment)]]
+fn fs_main(in: VertexOutput) -> [[location(0)]] vec4<f32> {
+    // Both the colors and the texture are premultiplied by alpha
+    return in.color * textureSample(t_gui, s_gui, in.tex_coords);
+}
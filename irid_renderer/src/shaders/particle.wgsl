@@ -0,0 +1,98 @@
+// Instanced billboards facing the camera: every instance is a particle, the corners of its
+// quad come from the vertex index.
+
+struct CameraUniform {
+    view_proj: mat4x4<f32>;
+    // The world directions of the screen right and up
+    right: vec4<f32>;
+    up: vec4<f32>;
+};
+
+struct AppearanceUniform {
+    colors: array<vec4<f32>, 4>;
+    sizes: vec4<f32>;
+};
+
+[[group(0), binding(0)]]
+var<uniform> appearance: AppearanceUniform;
+
+[[group(1), binding(0)]]
+var<uniform> camera: CameraUniform;
+
+struct InstanceInput {
+    [[location(5)]] position_age: vec4<f32>;
+    [[location(6)]] velocity_lifetime: vec4<f32>;
+};
+
+struct VertexOutput {
+    [[builtin(position)]] clip_position: vec4<f32>;
+    [[location(0)]] corner: vec2<f32>;
+    [[location(1)]] color: vec4<f32>;
+};
+
+// Evenly spaced keys at 0, 1/3, 2/3 and 1 of the particle life
+fn sample_curve(keys: vec4<f32>, t: f32) -> f32 {
+    let x = clamp(t, 0.0, 1.0) * 3.0;
+    if (x < 1.0) {
+        return mix(keys.x, keys.y, x);
+    }
+    if (x < 2.0) {
+        return mix(keys.y, keys.z, x - 1.0);
+    }
+    return mix(keys.z, keys.w, x - 2.0);
+}
+
+fn sample_color(t: f32) -> vec4<f32> {
+    let x = clamp(t, 0.0, 1.0) * 3.0;
+    if (x < 1.0) {
+        return mix(appearance.colors[0], appearance.colors[1], vec4<f32>(x));
+    }
+    if (x < 2.0) {
+        return mix(appearance.colors[1], appearance.colors[2], vec4<f32>(x - 1.0));
+    }
+    return mix(appearance.colors[2], appearance.colors[3], vec4<f32>(x - 2.0));
+}
+
+[[stage(vertex)]]
+fn vs_main(
+    [[builtin(vertex_index)]] vertex_index: u32,
+    instance: InstanceInput,
+) -> VertexOutput {
+    var corners = array<vec2<f32>, 6>(
+        vec2<f32>(-1.0, -1.0),
+        vec2<f32>(1.0, -1.0),
+        vec2<f32>(1.0, 1.0),
+        vec2<f32>(-1.0, -1.0),
+        vec2<f32>(1.0, 1.0),
+        vec2<f32>(-1.0, 1.0),
+    );
+    let corner = corners[vertex_index];
+
+    var out: VertexOutput;
+    out.corner = corner;
+
+    let age = instance.position_age.w;
+    let lifetime = instance.velocity_lifetime.w;
+    if (age >= lifetime) {
+        // Outside of the clip volume, so the dead particles are discarded
+        out.clip_position = vec4<f32>(2.0, 2.0, 2.0, 1.0);
+        out.color = vec4<f32>(0.0);
+        return out;
+    }
+
+    let t = age / lifetime;
+    let half_size = sample_curve(appearance.sizes, t) * 0.5;
+    let world = instance.position_age.xyz
+        + camera.right.xyz * corner.x * half_size
+        + camera.up.xyz * corner.y * half_size;
+    out.clip_position = camera.view_proj * vec4<f32>(world, 1.0);
+    out.color = sample_color(t);
+    return out;
+}
+
+[[stage(fragment)]]
+fn fs_main(in: VertexOutput) -> [[location(0)]] vec4<f32> {
+    // Round particles with soft edges
+    let fade = 1.0 - clamp((length(in.corner) - 0.6) / 0.4, 0.0, 1.0);
+    return vec4<f32>(in.color.rgb, in.color.a * fade);
+}
@@ -0,0 +1,55 @@
+// Integrates the alive particles of an emitter, the dead ones are left untouched.
+
+struct Particle {
+    position: vec3<f32>;
+    age: f32;
+    velocity: vec3<f32>;
+    lifetime: f32;
+};
+
+struct Particles {
+    particles: array<Particle>;
+};
+
+struct SimulationUniform {
+    gravity_dt: vec4<f32>;
+    velocity_curve: vec4<f32>;
+    count: u32;
+};
+
+[[group(0), binding(0)]]
+var<storage, read_write> data: Particles;
+[[group(0), binding(1)]]
+var<uniform> simulation: SimulationUniform;
+
+// Evenly spaced keys at 0, 1/3, 2/3 and 1 of the particle life
+fn sample_curve(keys: vec4<f32>, t: f32) -> f32 {
+    let x = clamp(t, 0.0, 1.0) * 3.0;
+    if (x < 1.0) {
+        return mix(keys.x, keys.y, x);
+    }
+    if (x < 2.0) {
+        return mix(keys.y, keys.z, x - 1.0);
+    }
+    return mix(keys.z, keys.w, x - 2.0);
+}
+
+[[stage(compute), workgroup_size(64)]]
+fn cs_main([[builtin(global_invocation_id)]] id: vec3<u32>) {
+    let index = id.x;
+    if (index >= simulation.count) {
+        return;
+    }
+
+    var particle = data.particles[index];
+    if (particle.age >= particle.lifetime) {
+        return;
+    }
+
+    let dt = simulation.gravity_dt.w;
+    let scale = sample_curve(simulation.velocity_curve, particle.age / particle.lifetime);
+    particle.velocity = particle.velocity + simulation.gravity_dt.xyz * dt;
+    particle.position = particle.position + particle.velocity * scale * dt;
+    particle.age = particle.age + dt;
+    data.particles[index] = particle;
+}
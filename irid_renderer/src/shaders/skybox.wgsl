@@ -0,0 +1,41 @@
+// Skybox drawn as a fullscreen triangle placed on the far plane, after the opaque geometry.
+// The depth test is LessEqual without depth writes, so only the untouched texels are filled.
+// With reverse-Z the far plane is at depth 0.0 and the test becomes GreaterEqual.
+
+struct SkyboxUniform {
+    inv_view_proj: mat4x4<f32>;
+    // The w component is the depth of the far plane
+    eye: vec4<f32>;
+};
+
+[[group(0), binding(0)]]
+var<uniform> skybox: SkyboxUniform;
+
+[[group(1), binding(0)]]
+var t_cube: texture_cube<f32>;
+[[group(1), binding(1)]]
+var s_cube: sampler;
+
+struct VertexOutput {
+    [[builtin(position)]] clip_position: vec4<f32>;
+    [[location(0)]] ndc: vec2<f32>;
+};
+
+[[stage(vertex)]]
+fn vs_main([[builtin(vertex_index)]] vertex_index: u32) -> VertexOutput {
+    // (-1, -1), (3, -1), (-1, 3): a triangle covering the whole screen
+    let x = f32(i32(vertex_index & 1u) * 4 - 1);
+    let y = f32(i32(vertex_index >> 1u) * 4 - 1);
+
+    var out: VertexOutput;
+    out.clip_position = vec4<f32>(x, y, skybox.eye.w, 1.0);
+    out.ndc = vec2<f32>(x, y);
+    return out;
+}
+
+[[stage(fragment)]]
+fn fs_main(in: VertexOutput) -> [[location(0)]] vec4<f32> {
+    let world = skybox.inv_view_proj * vec4<f32>(in.ndc, 1.0, 1.0);
+    let direction = world.xyz / world.w - skybox.eye.xyz;
+    return textureSample(t_cube, s_cube, direction);
+}
@@ -0,0 +1,59 @@
+// Instanced textured quads: every instance is a sprite, the quad itself is shared.
+
+struct CameraUniform {
+    view_proj: mat4x4<f32>;
+};
+
+[[group(0), binding(0)]]
+var t_diffuse: texture_2d<f32>;
+[[group(0), binding(1)]]
+var s_diffuse: sampler;
+
+[[group(1), binding(0)]]
+var<uniform> camera: CameraUniform;
+
+struct VertexInput {
+    [[location(0)]] position: vec3<f32>;
+    [[location(1)]] tex_coords: vec2<f32>;
+};
+
+struct InstanceInput {
+    [[location(5)]] translation_scale: vec4<f32>;
+    [[location(6)]] rotation_layer: vec2<f32>;
+    [[location(7)]] tint: vec4<f32>;
+    [[location(8)]] uv_rect: vec4<f32>;
+};
+
+struct VertexOutput {
+    [[builtin(position)]] clip_position: vec4<f32>;
+    [[location(0)]] tex_coords: vec2<f32>;
+    [[location(1)]] tint: vec4<f32>;
+};
+
+[[stage(vertex)]]
+fn vs_main(vertex: VertexInput, instance: InstanceInput) -> VertexOutput {
+    let c = cos(instance.rotation_layer.x);
+    let s = sin(instance.rotation_layer.x);
+
+    let local = vertex.position.xy * instance.translation_scale.zw;
+    let rotated = vec2<f32>(local.x * c - local.y * s, local.x * s + local.y * c);
+    let world = rotated + instance.translation_scale.xy;
+
+    var out: VertexOutput;
+    out.clip_position = camera.view_proj * vec4<f32>(world, 0.0, 1.0);
+    // The layer replaces the camera depth: 0.0 is the nearest, 1.0 the farthest
+    out.clip_position.z = clamp(instance.rotation_layer.y, 0.0, 1.0) * out.clip_position.w;
+    out.tex_coords = instance.uv_rect.xy + vertex.tex_coords * instance.uv_rect.zw;
+    out.tint = instance.tint;
+    return out;
+}
+
+[[stage(fragment)]]
+fn fs_main(in: VertexOutput) -> [[location(0)]] vec4<f32> {
+    let color = textureSample(t_diffuse, s_diffuse, in.tex_coords) * in.tint;
+    // Fully transparent texels must not write the depth, or they would hide what is behind
+    if (color.a < 0.01) {
+        discard;
+    }
+    return color;
+}
@@ -0,0 +1,50 @@
+// Instanced glyph quads sampling the coverage stored in the glyph atlas.
+
+struct CameraUniform {
+    view_proj: mat4x4<f32>;
+};
+
+[[group(0), binding(0)]]
+var t_atlas: texture_2d<f32>;
+[[group(0), binding(1)]]
+var s_atlas: sampler;
+
+[[group(1), binding(0)]]
+var<uniform> camera: CameraUniform;
+
+struct VertexInput {
+    [[location(0)]] position: vec3<f32>;
+    [[location(1)]] tex_coords: vec2<f32>;
+};
+
+struct InstanceInput {
+    [[location(5)]] origin_scale: vec4<f32>;
+    [[location(6)]] rect: vec4<f32>;
+    [[location(7)]] uv_rect: vec4<f32>;
+    [[location(8)]] color: vec4<f32>;
+};
+
+struct VertexOutput {
+    [[builtin(position)]] clip_position: vec4<f32>;
+    [[location(0)]] tex_coords: vec2<f32>;
+    [[location(1)]] color: vec4<f32>;
+};
+
+[[stage(vertex)]]
+fn vs_main(vertex: VertexInput, instance: InstanceInput) -> VertexOutput {
+    // Glyph rects are in pixels with the y axis pointing down, the world one points up
+    let local = instance.rect.xy + vertex.position.xy * instance.rect.zw;
+    let world = instance.origin_scale.xyz + vec3<f32>(local.x, -local.y, 0.0) * instance.origin_scale.w;
+
+    var out: VertexOutput;
+    out.clip_position = camera.view_proj * vec4<f32>(world, 1.0);
+    out.tex_coords = instance.uv_rect.xy + vertex.tex_coords * instance.uv_rect.zw;
+    out.color = instance.color;
+    return out;
+}
+
+[[stage(fragment)]]
+fn fs_main(in: VertexOutput) -> [[location(0)]] vec4<f32> {
+    let coverage = textureSample(t_atlas, s_atlas, in.tex_coords).r;
+    return vec4<f32>(in.color.rgb, in.color.a * coverage);
+}
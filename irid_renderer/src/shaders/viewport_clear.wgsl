@@ -0,0 +1,25 @@
+// Clears a viewport by drawing a fullscreen triangle limited by its scissor rect, since the
+// load operations of a render pass always clear the whole attachment.
+// The color and depth writes are chosen by the pipeline, the depth test always passes.
+
+struct ClearUniform {
+    color: vec4<f32>;
+    // The x component is the depth of the far plane
+    depth: vec4<f32>;
+};
+
+[[group(0), binding(0)]]
+var<uniform> clear: ClearUniform;
+
+[[stage(vertex)]]
+fn vs_main([[builtin(vertex_index)]] vertex_index: u32) -> [[builtin(position)]] vec4<f32> {
+    // (-1, -1), (3, -1), (-1, 3): a triangle covering the whole viewport
+    let x = f32(i32(vertex_index & 1u) * 4 - 1);
+    let y = f32(i32(vertex_index >> 1u) * 4 - 1);
+    return vec4<f32>(x, y, clear.depth.x, 1.0);
+}
+
+[[stage(fragment)]]
+fn fs_main() -> [[location(0)]] vec4<f32> {
+    return clear.color;
+}
@@ -0,0 +1,272 @@
+//= USES ===========================================================================================
+
+use irid_assets::CubemapTexture;
+
+use crate::{
+    camera::Camera,
+    depth::DepthConfig,
+    device::Device,
+    pipeline::{PipelineLayoutBuilder, RenderPipeline, RenderPipelineBuilder},
+    queue::Queue,
+    shader::{DEFAULT_FRAGMENT_ENTRY_POINT, DEFAULT_VERTEX_ENTRY_POINT, SKYBOX_SHADER},
+    stats::FrameStats,
+    texture_metadatas::{TextureCubeBindGroupMetadatas, TextureCubeMetadatas},
+};
+
+//= SKYBOX SOURCE ==================================================================================
+
+/// Where the skybox cubemap is loaded from when the renderer is built.
+#[derive(Clone, Debug)]
+pub(crate) enum SkyboxSource<P> {
+    /// Six images ordered as [CubemapFace::ALL](irid_assets::CubemapFace::ALL).
+    Faces([P; 6]),
+    /// An equirectangular HDR image with an optional face size.
+    Equirectangular(P, Option<u32>),
+}
+
+impl<P: AsRef<std::path::Path>> SkyboxSource<P> {
+    ///
+    pub(crate) fn load(&self) -> Result<CubemapTexture, irid_assets::TextureError> {
+        match *self {
+            SkyboxSource::Faces(ref paths) => CubemapTexture::load_faces(paths),
+            SkyboxSource::Equirectangular(ref path, face_size) => {
+                CubemapTexture::load_equirectangular(path, face_size)
+            }
+        }
+    }
+}
+
+//= SKYBOX =========================================================================================
+
+/// A cubemap drawn behind everything else, after the opaque geometry and reusing its
+/// depth buffer.
+///
+/// Its cube texture bind group is also exposed to the main pipeline, so that the materials
+/// can sample the environment for reflections.
+#[derive(Debug)]
+pub(crate) struct Skybox {
+    _cube_metadatas: TextureCubeMetadatas,
+    cube_bind_group_metadatas: TextureCubeBindGroupMetadatas,
+    bind_group_layout: wgpu::BindGroupLayout,
+    view: SkyboxView,
+    pipeline: RenderPipeline,
+}
+
+impl Skybox {
+    //- Constructors -------------------------------------------------------------------------------
+
+    /// Create the skybox pipeline and enqueue the cubemap upload.
+    pub(crate) fn new<C: Camera>(
+        device: &Device,
+        queue: &Queue,
+        camera: &C,
+        cubemap: &CubemapTexture,
+        color_format: wgpu::TextureFormat,
+        depth: &DepthConfig,
+    ) -> Self {
+        //- Cube Texture ---------------------------------------------------------------------------
+
+        let cube_metadatas = TextureCubeMetadatas::new(
+            device,
+            cubemap.face_size(),
+            cubemap.bytes_per_texel(),
+            cubemap.format(),
+        );
+        queue.write_cubemap(&cube_metadatas, cubemap);
+
+        let cube_bind_group_metadatas =
+            TextureCubeBindGroupMetadatas::new(device, cube_metadatas.texture());
+
+        //- Uniform --------------------------------------------------------------------------------
+
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            entries: &[wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::VERTEX | wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            }],
+            label: Some("Skybox Bind Group Layout"),
+        });
+
+        let mut uniform = SkyboxUniform::new(depth.clear_depth());
+        uniform.update_inv_view_proj(camera);
+        let view = SkyboxView::new(device, &bind_group_layout, uniform);
+
+        //- Pipeline -------------------------------------------------------------------------------
+
+        let shader_module = device.create_shader_module(&wgpu::ShaderModuleDescriptor {
+            label: Some("Skybox Shader"),
+            source: wgpu::ShaderSource::Wgsl(std::borrow::Cow::Borrowed(SKYBOX_SHADER)),
+        });
+
+        let pipeline_layout = PipelineLayoutBuilder::new()
+            .with_label("Skybox Pipeline Layout")
+            .with_bind_group_layouts(&[
+                &bind_group_layout,
+                cube_bind_group_metadatas.bind_group_layout(),
+            ])
+            .build(device);
+
+        let color_targets = [wgpu::ColorTargetState {
+            format: color_format,
+            blend: Some(wgpu::BlendState::REPLACE),
+            write_mask: wgpu::ColorWrites::ALL,
+        }];
+
+        let pipeline = RenderPipelineBuilder::new(wgpu::VertexState {
+            module: &shader_module,
+            entry_point: DEFAULT_VERTEX_ENTRY_POINT,
+            buffers: &[],
+        })
+        .with_label("Skybox Render Pipeline")
+        .with_layout(&pipeline_layout)
+        .with_fragment(wgpu::FragmentState {
+            module: &shader_module,
+            entry_point: DEFAULT_FRAGMENT_ENTRY_POINT,
+            targets: &color_targets,
+        })
+        // The triangle lies on the far plane: it passes the test only where the depth buffer
+        // still contains the clear value, and must not hide what will be drawn later.
+        // The depth of the far plane is given by the uniform, since it depends on reverse-Z
+        .with_depth_stencil(depth.depth_stencil_state(false, wgpu::CompareFunction::LessEqual))
+        .build(device);
+
+        Self {
+            _cube_metadatas: cube_metadatas,
+            cube_bind_group_metadatas,
+            bind_group_layout,
+            view,
+            pipeline,
+        }
+    }
+
+    /// Create the uniform of another camera, to draw the skybox in its viewport.
+    pub(crate) fn create_view<C: Camera>(&self, device: &Device, camera: &C) -> SkyboxView {
+        let mut uniform = self.view.uniform;
+        uniform.update_inv_view_proj(camera);
+        SkyboxView::new(device, &self.bind_group_layout, uniform)
+    }
+
+    //- Rendering ----------------------------------------------------------------------------------
+
+    /// Record the skybox draw as seen by the camera of the view, it must be called after the
+    /// opaque geometry has been drawn.
+    pub(crate) fn draw<'a>(
+        &'a self,
+        render_pass: &mut wgpu::RenderPass<'a>,
+        view: &'a SkyboxView,
+        stats: &mut FrameStats,
+    ) {
+        render_pass.set_pipeline(self.pipeline.expose_wrapped_render_pipeline());
+        render_pass.set_bind_group(0, &view.bind_group, &[]);
+        render_pass.set_bind_group(1, self.cube_bind_group_metadatas.bind_group(), &[]);
+        render_pass.draw(0..3, 0..1);
+        stats.record_pipeline();
+        stats.record_bind_group();
+        stats.record_bind_group();
+        stats.record_draw(3, 1, wgpu::PrimitiveTopology::TriangleList);
+    }
+
+    //- Getters ------------------------------------------------------------------------------------
+
+    /// The view of the renderer camera.
+    pub(crate) fn view(&self) -> &SkyboxView {
+        &self.view
+    }
+
+    /// The cube texture bind group used for reflections.
+    pub(crate) fn cube_bind_group_metadatas(&self) -> &TextureCubeBindGroupMetadatas {
+        &self.cube_bind_group_metadatas
+    }
+}
+
+//= SKYBOX VIEW ====================================================================================
+
+/// The uniform of a camera looking at the skybox.
+#[derive(Debug)]
+pub(crate) struct SkyboxView {
+    uniform: SkyboxUniform,
+    buffer: wgpu::Buffer,
+    bind_group: wgpu::BindGroup,
+}
+
+impl SkyboxView {
+    //- Constructors -------------------------------------------------------------------------------
+
+    ///
+    fn new(
+        device: &Device,
+        bind_group_layout: &wgpu::BindGroupLayout,
+        uniform: SkyboxUniform,
+    ) -> Self {
+        let buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Skybox Buffer"),
+            contents: bytemuck::cast_slice(&[uniform]),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        });
+
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: bind_group_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: buffer.as_entire_binding(),
+            }],
+            label: Some("Skybox Bind Group"),
+        });
+
+        Self {
+            uniform,
+            buffer,
+            bind_group,
+        }
+    }
+
+    //- Getters ------------------------------------------------------------------------------------
+
+    ///
+    pub(crate) fn uniform(&self) -> &SkyboxUniform {
+        &self.uniform
+    }
+
+    ///
+    pub(crate) fn buffer(&self) -> &wgpu::Buffer {
+        &self.buffer
+    }
+}
+
+//= SKYBOX UNIFORM BUFFER ==========================================================================
+
+///
+#[repr(C)]
+#[derive(Debug, Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
+pub(crate) struct SkyboxUniform {
+    // Used to get back the world direction of each fragment from its clip space position
+    inv_view_proj: [[f32; 4]; 4],
+    // The w component is the depth of the far plane
+    eye: [f32; 4],
+}
+
+impl SkyboxUniform {
+    pub(crate) fn new(far_depth: f32) -> Self {
+        use cgmath::SquareMatrix;
+        Self {
+            inv_view_proj: cgmath::Matrix4::identity().into(),
+            eye: [0.0, 0.0, 0.0, far_depth],
+        }
+    }
+
+    pub(crate) fn update_inv_view_proj<C: Camera>(&mut self, camera: &C) {
+        use cgmath::SquareMatrix;
+        // A degenerate camera has no inverse, in that case keep the previous one
+        if let Some(inv_view_proj) = camera.build_view_projection_matrix().invert() {
+            self.inv_view_proj = inv_view_proj.into();
+        }
+        let eye = camera.eye();
+        self.eye = [eye.x, eye.y, eye.z, self.eye[3]];
+    }
+}
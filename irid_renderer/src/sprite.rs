@@ -0,0 +1,475 @@
+//= USES ===========================================================================================
+
+use std::{cmp::Ordering, mem, ops::Range};
+
+use irid_assets::{DiffuseTexture, TextCoordsVertex};
+use irid_assets_interface::{ImageSize, Vertex};
+
+use crate::{
+    camera::{Camera, OrthographicCamera},
+    camera_bind::CameraBindGroup,
+    depth::DepthConfig,
+    device::Device,
+    pipeline::{PipelineLayoutBuilder, RenderPipeline, RenderPipelineBuilder},
+    queue::{Queue, QueueError},
+    shader::{DEFAULT_FRAGMENT_ENTRY_POINT, DEFAULT_VERTEX_ENTRY_POINT, SPRITE_SHADER},
+    stats::FrameStats,
+    texture_metadatas::{TextureBindGroupMetadatas, TextureImageMetadatas},
+};
+
+//= CONSTS =========================================================================================
+
+// Number of sprites the instances buffer can contain when it is first created.
+const INITIAL_INSTANCES_CAPACITY: usize = 1024;
+
+// A unit quad centered on the origin, the sprite scale gives its final size.
+const QUAD_VERTICES: [TextCoordsVertex; 4] = [
+    TextCoordsVertex {
+        position: [-0.5, -0.5, 0.0],
+        tex_coords: [0.0, 1.0],
+    },
+    TextCoordsVertex {
+        position: [0.5, -0.5, 0.0],
+        tex_coords: [1.0, 1.0],
+    },
+    TextCoordsVertex {
+        position: [0.5, 0.5, 0.0],
+        tex_coords: [1.0, 0.0],
+    },
+    TextCoordsVertex {
+        position: [-0.5, 0.5, 0.0],
+        tex_coords: [0.0, 0.0],
+    },
+];
+
+const QUAD_INDICES: [u16; 6] = [0, 1, 2, 0, 2, 3];
+
+//= SPRITE TEXTURE ID ==============================================================================
+
+/// Identifies a texture loaded with
+/// [Renderer::load_sprite_texture](crate::Renderer::load_sprite_texture).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct SpriteTextureId(usize);
+
+//= SPRITE =========================================================================================
+
+/// A textured quad to draw with [Renderer::draw_sprite](crate::Renderer::draw_sprite).
+#[derive(Clone, Copy, Debug)]
+pub struct Sprite {
+    position: [f32; 2],
+    rotation: f32,
+    scale: [f32; 2],
+    tint: [f32; 4],
+    uv_rect: [f32; 4],
+    layer: f32,
+}
+
+impl Default for Sprite {
+    fn default() -> Self {
+        Self {
+            position: [0.0, 0.0],
+            rotation: 0.0,
+            scale: [1.0, 1.0],
+            tint: [1.0, 1.0, 1.0, 1.0],
+            uv_rect: [0.0, 0.0, 1.0, 1.0],
+            layer: 0.5,
+        }
+    }
+}
+
+impl Sprite {
+    //- Constructors -------------------------------------------------------------------------------
+
+    ///
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    //- Setters ------------------------------------------------------------------------------------
+
+    /// World position of the sprite center.
+    #[inline]
+    pub fn with_position(mut self, x: f32, y: f32) -> Self {
+        self.position = [x, y];
+        self
+    }
+
+    /// Counterclockwise rotation, in radians, around the sprite center.
+    #[inline]
+    pub fn with_rotation(mut self, radians: f32) -> Self {
+        self.rotation = radians;
+        self
+    }
+
+    /// Width and height of the sprite in world units.
+    #[inline]
+    pub fn with_scale(mut self, width: f32, height: f32) -> Self {
+        self.scale = [width, height];
+        self
+    }
+
+    /// Color multiplied with the texture one, alpha included.
+    #[inline]
+    pub fn with_tint(mut self, r: f32, g: f32, b: f32, a: f32) -> Self {
+        self.tint = [r, g, b, a];
+        self
+    }
+
+    /// The portion of the texture to draw, in normalized coordinates with the origin
+    /// on the top-left corner. Useful to draw a single frame of a sprite sheet.
+    #[inline]
+    pub fn with_uv_rect(mut self, x: f32, y: f32, width: f32, height: f32) -> Self {
+        self.uv_rect = [x, y, width, height];
+        self
+    }
+
+    /// Layer depth between 0.0 (nearest) and 1.0 (farthest).
+    #[inline]
+    pub fn with_layer(mut self, layer: f32) -> Self {
+        self.layer = layer;
+        self
+    }
+
+    //- Conversions --------------------------------------------------------------------------------
+
+    fn to_raw(self) -> SpriteInstanceRaw {
+        SpriteInstanceRaw {
+            translation_scale: [
+                self.position[0],
+                self.position[1],
+                self.scale[0],
+                self.scale[1],
+            ],
+            rotation_layer: [self.rotation, self.layer],
+            tint: self.tint,
+            uv_rect: self.uv_rect,
+        }
+    }
+}
+
+//= SPRITE BATCH ===================================================================================
+
+// A texture usable by the sprites, render targets keep their own texture.
+#[derive(Debug)]
+struct SpriteTexture {
+    _image_metadatas: Option<TextureImageMetadatas>,
+    bind_group_metadatas: TextureBindGroupMetadatas,
+    // Retained to recreate the loaded textures after the device has been lost
+    image: Option<DiffuseTexture>,
+}
+
+/// Collects the sprites drawn during a frame and renders them with one instanced draw call
+/// for every texture used.
+///
+/// Sprites sharing a texture are drawn from the farthest layer to the nearest one, while the
+/// depth buffer orders the sprites of different textures.
+#[derive(Debug)]
+pub(crate) struct SpriteBatch {
+    camera: OrthographicCamera,
+    camera_bind_group: CameraBindGroup,
+    // The textures of the render targets are lost together with the device
+    textures: Vec<Option<SpriteTexture>>,
+    vertex_buffer: wgpu::Buffer,
+    index_buffer: wgpu::Buffer,
+    instances_buffer: wgpu::Buffer,
+    instances_capacity: usize,
+    queued: Vec<(SpriteTextureId, SpriteInstanceRaw)>,
+    batches: Vec<(SpriteTextureId, Range<u32>)>,
+    pipeline: RenderPipeline,
+}
+
+impl SpriteBatch {
+    //- Constructors -------------------------------------------------------------------------------
+
+    ///
+    pub(crate) fn new(
+        device: &Device,
+        window_size: winit::dpi::PhysicalSize<u32>,
+        color_format: wgpu::TextureFormat,
+        depth: &DepthConfig,
+    ) -> Self {
+        let camera = OrthographicCamera::new(window_size.width as f32, window_size.height as f32)
+            .with_reverse_z(depth.is_reverse_z());
+        let camera_bind_group = CameraBindGroup::new(&camera, device);
+
+        let vertex_buffer =
+            device.create_vertex_buffer_init("Sprite Vertex Buffer", &QUAD_VERTICES);
+        let index_buffer = device.create_indices_buffer_init("Sprite Index Buffer", &QUAD_INDICES);
+        let instances_buffer =
+            SpriteBatch::create_instances_buffer(device, INITIAL_INSTANCES_CAPACITY);
+
+        //- Pipeline -------------------------------------------------------------------------------
+
+        let shader_module = device.create_shader_module(&wgpu::ShaderModuleDescriptor {
+            label: Some("Sprite Shader"),
+            source: wgpu::ShaderSource::Wgsl(std::borrow::Cow::Borrowed(SPRITE_SHADER)),
+        });
+
+        let texture_bgl = TextureBindGroupMetadatas::create_bind_group_layout(device);
+        let pipeline_layout = PipelineLayoutBuilder::new()
+            .with_label("Sprite Pipeline Layout")
+            .with_bind_group_layouts(&[&texture_bgl, camera_bind_group.bind_group_layout()])
+            .build(device);
+
+        let vertex_buffers = [TextCoordsVertex::desc(), SpriteInstanceRaw::desc()];
+
+        let color_targets = [wgpu::ColorTargetState {
+            format: color_format,
+            blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+            write_mask: wgpu::ColorWrites::ALL,
+        }];
+
+        let pipeline = RenderPipelineBuilder::new(wgpu::VertexState {
+            module: &shader_module,
+            entry_point: DEFAULT_VERTEX_ENTRY_POINT,
+            buffers: &vertex_buffers,
+        })
+        .with_label("Sprite Render Pipeline")
+        .with_layout(&pipeline_layout)
+        .with_fragment(wgpu::FragmentState {
+            module: &shader_module,
+            entry_point: DEFAULT_FRAGMENT_ENTRY_POINT,
+            targets: &color_targets,
+        })
+        .with_depth_stencil(depth.depth_stencil_state(true, wgpu::CompareFunction::LessEqual))
+        .build(device);
+
+        Self {
+            camera,
+            camera_bind_group,
+            textures: vec![],
+            vertex_buffer,
+            index_buffer,
+            instances_buffer,
+            instances_capacity: INITIAL_INSTANCES_CAPACITY,
+            queued: vec![],
+            batches: vec![],
+            pipeline,
+        }
+    }
+
+    fn create_instances_buffer(device: &Device, capacity: usize) -> wgpu::Buffer {
+        device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Sprite Instances Buffer"),
+            size: (capacity * mem::size_of::<SpriteInstanceRaw>()) as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        })
+    }
+
+    //- Textures -----------------------------------------------------------------------------------
+
+    /// Create a GPU texture, of any size, and enqueue its upload.
+    pub(crate) fn load_texture(
+        &mut self,
+        device: &Device,
+        queue: &Queue,
+        texture: &DiffuseTexture,
+    ) -> Result<SpriteTextureId, QueueError> {
+        self.push_texture(device, queue, texture.clone())
+    }
+
+    fn push_texture(
+        &mut self,
+        device: &Device,
+        queue: &Queue,
+        image: DiffuseTexture,
+    ) -> Result<SpriteTextureId, QueueError> {
+        let size = image.size();
+        let image_metadatas = TextureImageMetadatas::new(device, size.width(), size.height());
+        queue.write_texture(&image_metadatas, &image)?;
+
+        let bind_group_metadatas =
+            TextureBindGroupMetadatas::new(device, image_metadatas.texture());
+
+        self.textures.push(Some(SpriteTexture {
+            _image_metadatas: Some(image_metadatas),
+            bind_group_metadatas,
+            image: Some(image),
+        }));
+        Ok(SpriteTextureId(self.textures.len() - 1))
+    }
+
+    /// Make a texture already on the GPU, like the color of a render target, usable by the
+    /// sprites.
+    pub(crate) fn add_texture(
+        &mut self,
+        device: &Device,
+        texture: &wgpu::Texture,
+    ) -> SpriteTextureId {
+        self.textures.push(Some(SpriteTexture {
+            _image_metadatas: None,
+            bind_group_metadatas: TextureBindGroupMetadatas::new(device, texture),
+            image: None,
+        }));
+        SpriteTextureId(self.textures.len() - 1)
+    }
+
+    /// Copy the camera and the textures of a batch whose device has been lost, uploading again
+    /// the loaded textures with the same ids; the ids of the render targets become unknown.
+    pub(crate) fn restore(
+        &mut self,
+        device: &Device,
+        queue: &Queue,
+        lost: &SpriteBatch,
+    ) -> Result<(), QueueError> {
+        self.camera = lost.camera.clone();
+        for texture in lost.textures.iter() {
+            match texture.as_ref().and_then(|texture| texture.image.as_ref()) {
+                Some(image) => {
+                    let _ = self.push_texture(device, queue, image.clone())?;
+                }
+                None => self.textures.push(None),
+            }
+        }
+        Ok(())
+    }
+
+    //- Sprites ------------------------------------------------------------------------------------
+
+    /// Enqueue a sprite for the next frame, sprites with an unknown texture are ignored.
+    pub(crate) fn push(&mut self, texture: SpriteTextureId, sprite: &Sprite) {
+        if self.textures.get(texture.0).and_then(Option::as_ref).is_some() {
+            self.queued.push((texture, sprite.to_raw()));
+        }
+    }
+
+    /// Sort the enqueued sprites in batches and upload them, must be called before
+    /// [SpriteBatch::draw].
+    pub(crate) fn prepare(&mut self, device: &Device, queue: &Queue) {
+        self.batches.clear();
+
+        queue.write_camera_buffer(&self.camera, &self.camera_bind_group);
+
+        if self.queued.is_empty() {
+            return;
+        }
+
+        self.queued.sort_by(|a, b| {
+            a.0 .0.cmp(&b.0 .0).then_with(|| {
+                // Farthest layers first
+                b.1.rotation_layer[1]
+                    .partial_cmp(&a.1.rotation_layer[1])
+                    .unwrap_or(Ordering::Equal)
+            })
+        });
+
+        if self.queued.len() > self.instances_capacity {
+            self.instances_capacity = self.queued.len().next_power_of_two();
+            self.instances_buffer =
+                SpriteBatch::create_instances_buffer(device, self.instances_capacity);
+        }
+
+        let instances = self.queued.iter().map(|q| q.1).collect::<Vec<_>>();
+        queue.write_buffer(&self.instances_buffer, 0, bytemuck::cast_slice(&instances));
+
+        let mut start = 0;
+        for (i, queued) in self.queued.iter().enumerate() {
+            let end = i + 1;
+            let is_last_of_batch = match self.queued.get(end) {
+                Some(next) => next.0 != queued.0,
+                None => true,
+            };
+            if is_last_of_batch {
+                self.batches.push((queued.0, start as u32..end as u32));
+                start = end;
+            }
+        }
+
+        self.queued.clear();
+    }
+
+    /// Record the draw calls of the batches prepared for this frame, seen by the camera of the
+    /// window or by the one given, for a viewport.
+    pub(crate) fn draw<'a>(
+        &'a self,
+        render_pass: &mut wgpu::RenderPass<'a>,
+        camera_bind_group: Option<&'a CameraBindGroup>,
+        stats: &mut FrameStats,
+    ) {
+        if self.batches.is_empty() {
+            return;
+        }
+
+        let camera_bind_group = camera_bind_group.unwrap_or(&self.camera_bind_group);
+        render_pass.set_pipeline(self.pipeline.expose_wrapped_render_pipeline());
+        render_pass.set_bind_group(1, camera_bind_group.bind_group(), &[]);
+        render_pass.set_vertex_buffer(0, self.vertex_buffer.slice(..));
+        render_pass.set_vertex_buffer(1, self.instances_buffer.slice(..));
+        render_pass.set_index_buffer(self.index_buffer.slice(..), wgpu::IndexFormat::Uint16);
+        stats.record_pipeline();
+        stats.record_bind_group();
+
+        for batch in self.batches.iter() {
+            let texture = match self.textures[batch.0 .0].as_ref() {
+                Some(texture) => texture,
+                None => continue,
+            };
+            render_pass.set_bind_group(0, texture.bind_group_metadatas.bind_group(), &[]);
+            render_pass.draw_indexed(0..QUAD_INDICES.len() as u32, 0, batch.1.clone());
+            stats.record_bind_group();
+            stats.record_draw_indexed(
+                QUAD_INDICES.len() as u32,
+                batch.1.end - batch.1.start,
+                wgpu::PrimitiveTopology::TriangleList,
+            );
+        }
+    }
+
+    //- Getters ------------------------------------------------------------------------------------
+
+    ///
+    pub(crate) fn camera(&self) -> &OrthographicCamera {
+        &self.camera
+    }
+
+    ///
+    pub(crate) fn camera_mut(&mut self) -> &mut OrthographicCamera {
+        &mut self.camera
+    }
+}
+
+//= SPRITE INSTANCE FOR SHADERS ====================================================================
+
+/// The per-sprite data that will go into the instances buffer.
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+pub(crate) struct SpriteInstanceRaw {
+    translation_scale: [f32; 4],
+    rotation_layer: [f32; 2],
+    tint: [f32; 4],
+    uv_rect: [f32; 4],
+}
+
+impl SpriteInstanceRaw {
+    ///
+    pub(crate) fn desc<'a>() -> wgpu::VertexBufferLayout<'a> {
+        wgpu::VertexBufferLayout {
+            array_stride: mem::size_of::<SpriteInstanceRaw>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Instance,
+            // Same locations used by the 3D instances, after the vertex ones
+            attributes: &[
+                wgpu::VertexAttribute {
+                    offset: 0,
+                    shader_location: 5,
+                    format: wgpu::VertexFormat::Float32x4,
+                },
+                wgpu::VertexAttribute {
+                    offset: mem::size_of::<[f32; 4]>() as wgpu::BufferAddress,
+                    shader_location: 6,
+                    format: wgpu::VertexFormat::Float32x2,
+                },
+                wgpu::VertexAttribute {
+                    offset: mem::size_of::<[f32; 6]>() as wgpu::BufferAddress,
+                    shader_location: 7,
+                    format: wgpu::VertexFormat::Float32x4,
+                },
+                wgpu::VertexAttribute {
+                    offset: mem::size_of::<[f32; 10]>() as wgpu::BufferAddress,
+                    shader_location: 8,
+                    format: wgpu::VertexFormat::Float32x4,
+                },
+            ],
+        }
+    }
+}
@@ -0,0 +1,203 @@
+//= USES ===========================================================================================
+
+use std::{
+    collections::VecDeque,
+    time::{Duration, Instant},
+};
+
+//= CONSTS =========================================================================================
+
+// Number of frames used to compute the average frames per second.
+const FPS_WINDOW: usize = 60;
+
+//= FRAME STATS ====================================================================================
+
+/// Statistics about the last rendered frame.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct FrameStats {
+    frame_time: Duration,
+    cpu_time: Duration,
+    fps: f32,
+    draw_calls: u32,
+    pipelines: u32,
+    bind_groups: u32,
+    vertices: u64,
+    indices: u64,
+    triangles: u64,
+    cumulative_buffer_memory: u64,
+    cumulative_texture_memory: u64,
+}
+
+impl FrameStats {
+    //- Getters ------------------------------------------------------------------------------------
+
+    /// Time elapsed between the start of the previous frame and the start of this one.
+    pub fn frame_time(&self) -> Duration {
+        self.frame_time
+    }
+
+    /// Time spent by the CPU preparing, recording and submitting the frame.
+    pub fn cpu_time(&self) -> Duration {
+        self.cpu_time
+    }
+
+    /// Frames per second, averaged over the last 60 frames.
+    pub fn fps(&self) -> f32 {
+        self.fps
+    }
+
+    ///
+    pub fn draw_calls(&self) -> u32 {
+        self.draw_calls
+    }
+
+    /// Number of times a pipeline has been set.
+    pub fn pipelines(&self) -> u32 {
+        self.pipelines
+    }
+
+    /// Number of times a bind group has been set.
+    pub fn bind_groups(&self) -> u32 {
+        self.bind_groups
+    }
+
+    /// Number of vertices submitted by the non-indexed draw calls, for every instance.
+    pub fn vertices(&self) -> u64 {
+        self.vertices
+    }
+
+    /// Number of indices submitted by the indexed draw calls, for every instance.
+    pub fn indices(&self) -> u64 {
+        self.indices
+    }
+
+    ///
+    pub fn triangles(&self) -> u64 {
+        self.triangles
+    }
+
+    /// Bytes of all the buffers created so far, the released ones included: it is the
+    /// cumulative allocation, not the memory currently in use.
+    pub fn cumulative_buffer_memory(&self) -> u64 {
+        self.cumulative_buffer_memory
+    }
+
+    /// Estimated bytes of all the textures created so far, the released ones included: it is
+    /// the cumulative allocation, not the memory currently in use.
+    pub fn cumulative_texture_memory(&self) -> u64 {
+        self.cumulative_texture_memory
+    }
+
+    //- Recording ----------------------------------------------------------------------------------
+
+    ///
+    pub(crate) fn record_pipeline(&mut self) {
+        self.pipelines += 1;
+    }
+
+    ///
+    pub(crate) fn record_bind_group(&mut self) {
+        self.bind_groups += 1;
+    }
+
+    ///
+    pub(crate) fn record_draw(
+        &mut self,
+        vertex_count: u32,
+        instance_count: u32,
+        topology: wgpu::PrimitiveTopology,
+    ) {
+        self.draw_calls += 1;
+        self.vertices += vertex_count as u64 * instance_count as u64;
+        self.triangles += triangle_count(vertex_count, topology) * instance_count as u64;
+    }
+
+    ///
+    pub(crate) fn record_draw_indexed(
+        &mut self,
+        index_count: u32,
+        instance_count: u32,
+        topology: wgpu::PrimitiveTopology,
+    ) {
+        self.draw_calls += 1;
+        self.indices += index_count as u64 * instance_count as u64;
+        self.triangles += triangle_count(index_count, topology) * instance_count as u64;
+    }
+}
+
+fn triangle_count(count: u32, topology: wgpu::PrimitiveTopology) -> u64 {
+    match topology {
+        wgpu::PrimitiveTopology::TriangleList => (count / 3) as u64,
+        wgpu::PrimitiveTopology::TriangleStrip => count.saturating_sub(2) as u64,
+        _ => 0,
+    }
+}
+
+//= FRAME STATS COLLECTOR ==========================================================================
+
+/// Keeps the timings of the last frames and the counters of the frame being rendered.
+#[derive(Debug, Default)]
+pub(crate) struct FrameStatsCollector {
+    current: FrameStats,
+    last: FrameStats,
+    frame_start: Option<Instant>,
+    frame_times: VecDeque<Duration>,
+}
+
+impl FrameStatsCollector {
+    //- Getters ------------------------------------------------------------------------------------
+
+    /// The statistics of the last completed frame.
+    pub(crate) fn last(&self) -> &FrameStats {
+        &self.last
+    }
+
+    /// The statistics of the frame being rendered, where the draws are recorded.
+    pub(crate) fn current_mut(&mut self) -> &mut FrameStats {
+        &mut self.current
+    }
+
+    //- Collecting ---------------------------------------------------------------------------------
+
+    ///
+    pub(crate) fn begin_frame(&mut self) {
+        let now = Instant::now();
+        let frame_time = match self.frame_start {
+            Some(frame_start) => now - frame_start,
+            None => Duration::ZERO,
+        };
+        self.frame_start = Some(now);
+
+        if frame_time > Duration::ZERO {
+            if self.frame_times.len() == FPS_WINDOW {
+                let _ = self.frame_times.pop_front();
+            }
+            self.frame_times.push_back(frame_time);
+        }
+
+        self.current = FrameStats {
+            frame_time,
+            ..FrameStats::default()
+        };
+    }
+
+    /// Complete the frame statistics with its timings and the memory allocated so far.
+    pub(crate) fn end_frame(
+        &mut self,
+        cumulative_buffer_memory: u64,
+        cumulative_texture_memory: u64,
+    ) {
+        let total_time = self.frame_times.iter().sum::<Duration>();
+        self.current.fps = if total_time > Duration::ZERO {
+            self.frame_times.len() as f32 / total_time.as_secs_f32()
+        } else {
+            0.0
+        };
+        if let Some(frame_start) = self.frame_start {
+            self.current.cpu_time = frame_start.elapsed();
+        }
+        self.current.cumulative_buffer_memory = cumulative_buffer_memory;
+        self.current.cumulative_texture_memory = cumulative_texture_memory;
+        self.last = std::mem::take(&mut self.current);
+    }
+}
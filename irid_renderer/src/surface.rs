@@ -1,9 +1,11 @@
 //= USES ===========================================================================================
 
-use pollster::FutureExt;
 use thiserror::Error;
 
-use crate::device::Device;
+use crate::{
+    adapter::{AdapterDetails, AdapterError, AdapterRequest},
+    device::Device,
+};
 
 //= ERRORS =========================================================================================
 
@@ -11,7 +13,14 @@ use crate::device::Device;
 #[derive(Debug, Error)]
 pub(crate) enum SurfaceError {
     #[error("An adapter compatible with the given surface could not be obtained")]
-    AdapterNotObtained,
+    Adapter {
+        #[from]
+        source: AdapterError,
+    },
+    #[error("The adapter cannot present to the given surface")]
+    IncompatibleSurface,
+    #[error("The adapter cannot render to the {format:?} format of the surface")]
+    Format { format: wgpu::TextureFormat },
 }
 
 //= SURFACE WRAPPER ================================================================================
@@ -29,78 +38,106 @@ impl Surface {
     //- Constructors -------------------------------------------------------------------------------
 
     /// Create a new Surface using the window handle and retrieves an Adapter which matches
-    /// the created surface.
+    /// the created surface, with the Instance needed to create the surfaces of other windows.
     pub(crate) fn new(
         backends: wgpu::Backends,
         window: &winit::window::Window,
-        power_preference: wgpu::PowerPreference,
-        force_fallback_adapter: bool,
+        adapter_request: &AdapterRequest,
         preferred_format: Option<wgpu::TextureFormat>,
+        srgb: bool,
         present_mode: wgpu::PresentMode,
-    ) -> Result<(Self, wgpu::Adapter), SurfaceError> {
+        frame_capture: bool,
+    ) -> Result<(Self, wgpu::Instance, wgpu::Adapter), SurfaceError> {
         // Context for all other wgpu objects
         let wgpu_instance = wgpu::Instance::new(backends);
 
         // Handle to a presentable surface onto which rendered images
         let wgpu_surface = unsafe { wgpu_instance.create_surface(window) };
 
-        // For debug purpose prints on console all the available adapters
-        enumerate_all_adapters(backends, &wgpu_instance);
+        let adapter = adapter_request.request(&wgpu_instance, backends, &wgpu_surface)?;
 
-        let adapter = {
-            let adapter_options = wgpu::RequestAdapterOptions {
-                power_preference,
-                force_fallback_adapter,
-                compatible_surface: Some(&wgpu_surface),
-            };
+        log::info!("Picked Adapter: {}", AdapterDetails::new(&adapter));
 
-            let adapter_option =
-                async { wgpu_instance.request_adapter(&adapter_options).await }.block_on();
+        // Checked before the surface is configured, which would panic with a wrong format
+        let format = negotiate_format(&wgpu_surface, &adapter, preferred_format, srgb)?;
 
-            if let Some(a) = adapter_option {
-                Ok(a)
+        log::info!("Preferred Texture Color Format: {:?}", format);
+
+        // The frames are copied to be captured only when asked, and if the format allows it
+        let mut usage = wgpu::TextureUsages::RENDER_ATTACHMENT;
+        if frame_capture {
+            let format_features = adapter.get_texture_format_features(format);
+            if format_features
+                .allowed_usages
+                .contains(wgpu::TextureUsages::COPY_SRC)
+            {
+                usage |= wgpu::TextureUsages::COPY_SRC;
             } else {
-                Err(SurfaceError::AdapterNotObtained)
+                log::warn!("The {:?} frames cannot be copied, capture is disabled", format);
             }
-        }?;
-
-        log::info!("Picked Adapter: {}", pprint_adapter_info(&adapter));
+        }
 
-        let format = preferred_format.unwrap_or({
-            wgpu_surface
-                .get_preferred_format(&adapter)
-                // Most images are stored using sRGB so we need to reflect that here.
-                .unwrap_or(wgpu::TextureFormat::Rgba8UnormSrgb)
-        });
+        let surface = Self {
+            wgpu_surface,
+            format,
+            configuration: create_configuration(window, format, present_mode, usage),
+        };
 
-        log::info!("Preferred Texture Color Format: {:?}", format);
+        Ok((surface, wgpu_instance, adapter))
+    }
 
-        let window_size = window.inner_size();
+    /// Create the Surface of another window, presented by the same Adapter with the given
+    /// format, so that it can be drawn by the same pipelines.
+    pub(crate) fn with_format(
+        instance: &wgpu::Instance,
+        adapter: &wgpu::Adapter,
+        window: &winit::window::Window,
+        format: wgpu::TextureFormat,
+        present_mode: wgpu::PresentMode,
+    ) -> Result<Self, SurfaceError> {
+        let wgpu_surface = unsafe { instance.create_surface(window) };
 
-        let configuration = wgpu::SurfaceConfiguration {
-            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
-            format,
-            width: window_size.width,
-            height: window_size.height,
-            present_mode,
-        };
+        // There is no preferred format when the adapter cannot present to the surface
+        if wgpu_surface.get_preferred_format(adapter).is_none() {
+            return Err(SurfaceError::IncompatibleSurface);
+        }
 
-        let surface = Self {
+        Ok(Self {
             wgpu_surface,
             format,
-            configuration,
-        };
-
-        Ok((surface, adapter))
+            configuration: create_configuration(
+                window,
+                format,
+                present_mode,
+                wgpu::TextureUsages::RENDER_ATTACHMENT,
+            ),
+        })
     }
 
     //- Getters ------------------------------------------------------------------------------------
 
+    ///
+    pub(crate) fn expose_wrapped_surface(&self) -> &wgpu::Surface {
+        &self.wgpu_surface
+    }
+
     /// Returns the optimal texture format to use with this Surface.
     pub(crate) fn format(&self) -> wgpu::TextureFormat {
         self.format
     }
 
+    ///
+    pub(crate) fn present_mode(&self) -> wgpu::PresentMode {
+        self.configuration.present_mode
+    }
+
+    /// Returns true if the presented frames can be copied to be captured.
+    pub(crate) fn is_capturable(&self) -> bool {
+        self.configuration
+            .usage
+            .contains(wgpu::TextureUsages::COPY_SRC)
+    }
+
     // Swapchain -----------------------------------------------------------------------------------
 
     /// Initializes Surface for presentation.
@@ -126,30 +163,120 @@ impl Surface {
 
 //= FUNCTIONS ======================================================================================
 
-// Shows all the adapters information.
-fn enumerate_all_adapters(backends: wgpu::Backends, instance: &wgpu::Instance) {
-    instance.poll_all(true);
-    let adapters = instance.enumerate_adapters(backends);
-
-    let mut found = false;
-    for (i, adapter) in adapters.enumerate() {
-        let info = pprint_adapter_info(&adapter);
-        if i == 0 {
-            log::info!("Adapter(s) found - {}", info);
-        } else {
-            log::info!("                 - {}", info);
+// The configuration of a surface as big as the window.
+fn create_configuration(
+    window: &winit::window::Window,
+    format: wgpu::TextureFormat,
+    present_mode: wgpu::PresentMode,
+    usage: wgpu::TextureUsages,
+) -> wgpu::SurfaceConfiguration {
+    let window_size = window.inner_size();
+    wgpu::SurfaceConfiguration {
+        usage,
+        format,
+        width: window_size.width,
+        height: window_size.height,
+        present_mode,
+    }
+}
+
+// Use the preferred format if the surface can present it and the adapter can render to it,
+// otherwise the one preferred by the surface in the requested color space.
+//
+// An error if the adapter cannot present to the surface at all, or cannot render to the format.
+fn negotiate_format(
+    surface: &wgpu::Surface,
+    adapter: &wgpu::Adapter,
+    preferred_format: Option<wgpu::TextureFormat>,
+    srgb: bool,
+) -> Result<wgpu::TextureFormat, SurfaceError> {
+    let surface_format = surface
+        .get_preferred_format(adapter)
+        .ok_or(SurfaceError::IncompatibleSurface)?;
+
+    if let Some(preferred_format) = preferred_format {
+        // Only the color space of the surface format can be changed, the other ones could
+        // make the configuration of the surface fail
+        let is_presentable =
+            with_color_space(surface_format, true) == with_color_space(preferred_format, true);
+        if is_presentable && is_renderable(adapter, preferred_format) {
+            return Ok(preferred_format);
         }
-        found = true;
+        log::warn!(
+            "The surface cannot present the {:?} format, the {:?} one will be used",
+            preferred_format,
+            surface_format
+        );
+        return check_renderable(
+            adapter,
+            with_color_space(surface_format, preferred_format.describe().srgb),
+        );
+    }
+
+    check_renderable(adapter, with_color_space(surface_format, srgb))
+}
+
+fn is_renderable(adapter: &wgpu::Adapter, format: wgpu::TextureFormat) -> bool {
+    adapter
+        .get_texture_format_features(format)
+        .allowed_usages
+        .contains(wgpu::TextureUsages::RENDER_ATTACHMENT)
+}
+
+fn check_renderable(
+    adapter: &wgpu::Adapter,
+    format: wgpu::TextureFormat,
+) -> Result<wgpu::TextureFormat, SurfaceError> {
+    if is_renderable(adapter, format) {
+        Ok(format)
+    } else {
+        Err(SurfaceError::Format { format })
     }
+}
 
-    if !found {
-        log::info!("No Adapter Found");
+// The sRGB or linear variant of the format, if it has one.
+fn with_color_space(format: wgpu::TextureFormat, srgb: bool) -> wgpu::TextureFormat {
+    if format.describe().srgb == srgb {
+        return format;
+    }
+    match format {
+        wgpu::TextureFormat::Rgba8Unorm => wgpu::TextureFormat::Rgba8UnormSrgb,
+        wgpu::TextureFormat::Rgba8UnormSrgb => wgpu::TextureFormat::Rgba8Unorm,
+        wgpu::TextureFormat::Bgra8Unorm => wgpu::TextureFormat::Bgra8UnormSrgb,
+        wgpu::TextureFormat::Bgra8UnormSrgb => wgpu::TextureFormat::Bgra8Unorm,
+        _ => format,
     }
 }
 
-// Wgpu adapter info pretty printing.
-fn pprint_adapter_info(adapter: &wgpu::Adapter) -> String {
-    format!("{:?}", adapter.get_info())
-        .replace("AdapterInfo { name: ", "")
-        .replace(" }", "")
+//= TESTS ==========================================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::with_color_space;
+
+    #[test]
+    fn with_color_space_switches_the_8_bits_formats() {
+        use wgpu::TextureFormat::*;
+
+        assert_eq!(with_color_space(Rgba8Unorm, true), Rgba8UnormSrgb);
+        assert_eq!(with_color_space(Rgba8UnormSrgb, false), Rgba8Unorm);
+        assert_eq!(with_color_space(Bgra8Unorm, true), Bgra8UnormSrgb);
+        assert_eq!(with_color_space(Bgra8UnormSrgb, false), Bgra8Unorm);
+    }
+
+    #[test]
+    fn with_color_space_keeps_the_format_already_in_the_color_space() {
+        use wgpu::TextureFormat::*;
+
+        assert_eq!(with_color_space(Rgba8UnormSrgb, true), Rgba8UnormSrgb);
+        assert_eq!(with_color_space(Bgra8Unorm, false), Bgra8Unorm);
+    }
+
+    #[test]
+    fn with_color_space_keeps_the_formats_without_variant() {
+        use wgpu::TextureFormat::*;
+
+        assert_eq!(with_color_space(Rgba16Float, true), Rgba16Float);
+        assert_eq!(with_color_space(Rgb10a2Unorm, true), Rgb10a2Unorm);
+    }
 }
@@ -0,0 +1,591 @@
+//= USES ===========================================================================================
+
+use std::{collections::HashMap, mem, ops::Range, sync::Arc};
+
+use irid_assets::{Font, TextCoordsVertex, TextStyle};
+use irid_assets_interface::Vertex;
+
+use crate::{
+    camera::{Camera, OrthographicCamera},
+    camera_bind::CameraBindGroup,
+    depth::DepthConfig,
+    device::Device,
+    pipeline::{PipelineCache, RenderPipeline, RenderPipelineBuilder},
+    queue::Queue,
+    shader::{DEFAULT_FRAGMENT_ENTRY_POINT, DEFAULT_VERTEX_ENTRY_POINT, TEXT_SHADER},
+    stats::FrameStats,
+    texture_metadatas::TextureBindGroupMetadatas,
+};
+
+//= CONSTS =========================================================================================
+
+// Width and height of the glyph atlas texture.
+const GLYPH_ATLAS_SIZE: u32 = 1024;
+
+// Empty texels left around every glyph to avoid bleeding with the linear sampling.
+const GLYPH_PADDING: u32 = 1;
+
+// Number of glyphs the instances buffer can contain when it is first created.
+const INITIAL_INSTANCES_CAPACITY: usize = 1024;
+
+// A unit quad with the origin on the top-left corner and the y axis pointing down, as the
+// glyph rects.
+const QUAD_VERTICES: [TextCoordsVertex; 4] = [
+    TextCoordsVertex {
+        position: [0.0, 0.0, 0.0],
+        tex_coords: [0.0, 0.0],
+    },
+    TextCoordsVertex {
+        position: [0.0, 1.0, 0.0],
+        tex_coords: [0.0, 1.0],
+    },
+    TextCoordsVertex {
+        position: [1.0, 1.0, 0.0],
+        tex_coords: [1.0, 1.0],
+    },
+    TextCoordsVertex {
+        position: [1.0, 0.0, 0.0],
+        tex_coords: [1.0, 0.0],
+    },
+];
+
+const QUAD_INDICES: [u16; 6] = [0, 1, 2, 0, 2, 3];
+
+//= FONT ID ========================================================================================
+
+/// Identifies a font loaded with [Renderer::load_font](crate::Renderer::load_font).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct FontId(usize);
+
+//= GLYPH ATLAS ====================================================================================
+
+// Identifies a glyph rasterized at a specific size.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+struct GlyphKey {
+    font: FontId,
+    id: u16,
+    size_bits: u32,
+}
+
+// Where a rasterized glyph lies inside the atlas.
+#[derive(Clone, Copy, Debug)]
+struct AtlasGlyph {
+    uv_rect: [f32; 4],
+    size: [f32; 2],
+    offset: [f32; 2],
+}
+
+/// A single channel texture where the glyphs are rasterized on demand, packed in rows
+/// of growing height.
+#[derive(Debug)]
+struct GlyphAtlas {
+    texture: wgpu::Texture,
+    bind_group_metadatas: TextureBindGroupMetadatas,
+    // Glyphs without an outline are cached as None
+    glyphs: HashMap<GlyphKey, Option<AtlasGlyph>>,
+    cursor: [u32; 2],
+    row_height: u32,
+}
+
+impl GlyphAtlas {
+    //- Constructors -------------------------------------------------------------------------------
+
+    fn new(device: &Device) -> Self {
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Glyph Atlas Texture"),
+            format: wgpu::TextureFormat::R8Unorm,
+            size: wgpu::Extent3d {
+                width: GLYPH_ATLAS_SIZE,
+                height: GLYPH_ATLAS_SIZE,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+        });
+        let bind_group_metadatas = TextureBindGroupMetadatas::new(device, &texture);
+
+        Self {
+            texture,
+            bind_group_metadatas,
+            glyphs: HashMap::new(),
+            cursor: [GLYPH_PADDING, GLYPH_PADDING],
+            row_height: 0,
+        }
+    }
+
+    //- Packing ------------------------------------------------------------------------------------
+
+    // Forget every glyph, the texture content will be overwritten by the next ones.
+    fn clear(&mut self) {
+        self.glyphs.clear();
+        self.cursor = [GLYPH_PADDING, GLYPH_PADDING];
+        self.row_height = 0;
+    }
+
+    // Returns the cached glyph, rasterizing and uploading it if needed.
+    // The outer None means that the atlas is full.
+    fn get_or_insert(
+        &mut self,
+        queue: &Queue,
+        font: &Font,
+        key: GlyphKey,
+    ) -> Option<Option<AtlasGlyph>> {
+        if let Some(glyph) = self.glyphs.get(&key) {
+            return Some(*glyph);
+        }
+
+        let bitmap = match font.rasterize(key.id, f32::from_bits(key.size_bits)) {
+            Some(bitmap) if bitmap.width > 0 && bitmap.height > 0 => bitmap,
+            _ => {
+                let _ = self.glyphs.insert(key, None);
+                return Some(None);
+            }
+        };
+
+        if self.cursor[0] + bitmap.width + GLYPH_PADDING > GLYPH_ATLAS_SIZE {
+            self.cursor = [
+                GLYPH_PADDING,
+                self.cursor[1] + self.row_height + GLYPH_PADDING,
+            ];
+            self.row_height = 0;
+        }
+        if self.cursor[0] + bitmap.width + GLYPH_PADDING > GLYPH_ATLAS_SIZE
+            || self.cursor[1] + bitmap.height + GLYPH_PADDING > GLYPH_ATLAS_SIZE
+        {
+            return None;
+        }
+
+        queue.write_glyph(&self.texture, self.cursor, &bitmap);
+
+        let atlas_size = GLYPH_ATLAS_SIZE as f32;
+        let glyph = AtlasGlyph {
+            uv_rect: [
+                self.cursor[0] as f32 / atlas_size,
+                self.cursor[1] as f32 / atlas_size,
+                bitmap.width as f32 / atlas_size,
+                bitmap.height as f32 / atlas_size,
+            ],
+            size: [bitmap.width as f32, bitmap.height as f32],
+            offset: bitmap.offset,
+        };
+        let _ = self.glyphs.insert(key, Some(glyph));
+
+        self.cursor[0] += bitmap.width + GLYPH_PADDING;
+        self.row_height = self.row_height.max(bitmap.height);
+        Some(Some(glyph))
+    }
+}
+
+//= TEXT RENDERER ==================================================================================
+
+// A text enqueued for the next frame.
+#[derive(Debug)]
+struct QueuedText {
+    font: FontId,
+    text: String,
+    style: TextStyle,
+    origin: [f32; 3],
+    units_per_pixel: f32,
+    world_space: bool,
+}
+
+/// Lays out the texts drawn during a frame and renders their glyphs as instanced quads.
+///
+/// Screen-space texts use pixel coordinates, from the top-left corner of the window, and are
+/// drawn over everything else. World-space texts are placed on the XY plane, facing +z, and
+/// are hidden by the nearer geometry.
+#[derive(Debug)]
+pub(crate) struct TextRenderer {
+    fonts: Vec<Font>,
+    atlas: GlyphAtlas,
+    screen_camera: OrthographicCamera,
+    screen_camera_bind_group: CameraBindGroup,
+    vertex_buffer: wgpu::Buffer,
+    index_buffer: wgpu::Buffer,
+    instances_buffer: wgpu::Buffer,
+    instances_capacity: usize,
+    queued: Vec<QueuedText>,
+    screen_instances: Range<u32>,
+    world_instances: Range<u32>,
+    screen_pipeline: Arc<RenderPipeline>,
+    world_pipeline: Arc<RenderPipeline>,
+}
+
+impl TextRenderer {
+    //- Constructors -------------------------------------------------------------------------------
+
+    ///
+    pub(crate) fn new(
+        device: &Device,
+        pipeline_cache: &mut PipelineCache,
+        window_size: winit::dpi::PhysicalSize<u32>,
+        color_format: wgpu::TextureFormat,
+        depth: &DepthConfig,
+    ) -> Self {
+        let atlas = GlyphAtlas::new(device);
+
+        let screen_camera =
+            TextRenderer::create_screen_camera(window_size.width as f32, window_size.height as f32);
+        let screen_camera_bind_group = CameraBindGroup::new(&screen_camera, device);
+
+        let vertex_buffer = device.create_vertex_buffer_init("Text Vertex Buffer", &QUAD_VERTICES);
+        let index_buffer = device.create_indices_buffer_init("Text Index Buffer", &QUAD_INDICES);
+        let instances_buffer =
+            TextRenderer::create_instances_buffer(device, INITIAL_INSTANCES_CAPACITY);
+
+        //- Pipelines ------------------------------------------------------------------------------
+
+        let shader_module = pipeline_cache.shader_module(device, "Text Shader", TEXT_SHADER);
+
+        let pipeline_layout = pipeline_cache.pipeline_layout(
+            device,
+            "Text Pipeline Layout",
+            &[
+                atlas.bind_group_metadatas.bind_group_layout(),
+                screen_camera_bind_group.bind_group_layout(),
+            ],
+            &[],
+        );
+
+        let vertex_buffers = [TextCoordsVertex::desc(), GlyphInstanceRaw::desc()];
+
+        let color_targets = [wgpu::ColorTargetState {
+            format: color_format,
+            blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+            write_mask: wgpu::ColorWrites::ALL,
+        }];
+
+        // The glyph quads overlap each other, so neither of the pipelines writes the depth
+        let mut create_pipeline = |label, depth_compare| {
+            RenderPipelineBuilder::new(wgpu::VertexState {
+                module: &shader_module,
+                entry_point: DEFAULT_VERTEX_ENTRY_POINT,
+                buffers: &vertex_buffers,
+            })
+            .with_label(label)
+            .with_layout(&pipeline_layout)
+            .with_fragment(wgpu::FragmentState {
+                module: &shader_module,
+                entry_point: DEFAULT_FRAGMENT_ENTRY_POINT,
+                targets: &color_targets,
+            })
+            .with_depth_stencil(depth.depth_stencil_state(false, depth_compare))
+            .build_cached(device, pipeline_cache)
+        };
+
+        let screen_pipeline =
+            create_pipeline("Screen Text Render Pipeline", wgpu::CompareFunction::Always);
+        let world_pipeline = create_pipeline(
+            "World Text Render Pipeline",
+            wgpu::CompareFunction::LessEqual,
+        );
+
+        Self {
+            fonts: vec![],
+            atlas,
+            screen_camera,
+            screen_camera_bind_group,
+            vertex_buffer,
+            index_buffer,
+            instances_buffer,
+            instances_capacity: INITIAL_INSTANCES_CAPACITY,
+            queued: vec![],
+            screen_instances: 0..0,
+            world_instances: 0..0,
+            screen_pipeline,
+            world_pipeline,
+        }
+    }
+
+    /// One world unit is one pixel, with the origin on the top-left corner of an area of the
+    /// given size, the window or a viewport.
+    pub(crate) fn create_screen_camera(width: f32, height: f32) -> OrthographicCamera {
+        let mut camera = OrthographicCamera::new(width, height);
+        camera.translate(cgmath::Vector3::new(width * 0.5, -height * 0.5, 0.0));
+        camera
+    }
+
+    fn create_instances_buffer(device: &Device, capacity: usize) -> wgpu::Buffer {
+        device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Text Instances Buffer"),
+            size: (capacity * mem::size_of::<GlyphInstanceRaw>()) as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        })
+    }
+
+    //- Setters ------------------------------------------------------------------------------------
+
+    /// Keep the screen-space texts in pixel coordinates after the window has been resized.
+    pub(crate) fn resize(&mut self, window_size: winit::dpi::PhysicalSize<u32>) {
+        self.screen_camera =
+            TextRenderer::create_screen_camera(window_size.width as f32, window_size.height as f32);
+    }
+
+    //- Fonts --------------------------------------------------------------------------------------
+
+    ///
+    pub(crate) fn load_font(&mut self, font: Font) -> FontId {
+        self.fonts.push(font);
+        FontId(self.fonts.len() - 1)
+    }
+
+    /// Take the fonts of a renderer whose device has been lost, with the same ids.
+    pub(crate) fn restore_fonts(&mut self, lost: &mut TextRenderer) {
+        self.fonts = mem::take(&mut lost.fonts);
+    }
+
+    //- Texts --------------------------------------------------------------------------------------
+
+    /// Enqueue a text at the given pixel position, texts with an unknown font are ignored.
+    pub(crate) fn push_screen(
+        &mut self,
+        font: FontId,
+        text: &str,
+        position: cgmath::Point2<f32>,
+        style: &TextStyle,
+    ) {
+        self.push(
+            font,
+            text,
+            [position.x, -position.y, 0.0],
+            1.0,
+            false,
+            style,
+        );
+    }
+
+    /// Enqueue a text with its top-left corner at the given world position, texts with an
+    /// unknown font are ignored.
+    pub(crate) fn push_world(
+        &mut self,
+        font: FontId,
+        text: &str,
+        position: cgmath::Point3<f32>,
+        units_per_pixel: f32,
+        style: &TextStyle,
+    ) {
+        let origin = [position.x, position.y, position.z];
+        self.push(font, text, origin, units_per_pixel, true, style);
+    }
+
+    fn push(
+        &mut self,
+        font: FontId,
+        text: &str,
+        origin: [f32; 3],
+        units_per_pixel: f32,
+        world_space: bool,
+        style: &TextStyle,
+    ) {
+        if font.0 < self.fonts.len() && !text.is_empty() {
+            self.queued.push(QueuedText {
+                font,
+                text: text.to_string(),
+                style: *style,
+                origin,
+                units_per_pixel,
+                world_space,
+            });
+        }
+    }
+
+    /// Lay out the enqueued texts, rasterize the missing glyphs and upload the instances,
+    /// must be called before [TextRenderer::draw].
+    pub(crate) fn prepare(&mut self, device: &Device, queue: &Queue) {
+        self.screen_instances = 0..0;
+        self.world_instances = 0..0;
+
+        queue.write_camera_buffer(&self.screen_camera, &self.screen_camera_bind_group);
+
+        if self.queued.is_empty() {
+            return;
+        }
+
+        // Screen-space texts first, so that both kinds are contiguous in the buffer
+        self.queued.sort_by_key(|queued| queued.world_space);
+
+        let instances = match self.build_instances(queue) {
+            Some(instances) => instances,
+            None => {
+                // The glyphs of this frame do not fit alongside the ones of the previous
+                // frames, try again starting from an empty atlas
+                self.atlas.clear();
+                self.build_instances(queue).unwrap_or_else(|| {
+                    log::warn!("The glyph atlas is too small for the texts of this frame");
+                    vec![]
+                })
+            }
+        };
+        self.queued.clear();
+
+        if instances.is_empty() {
+            return;
+        }
+
+        if instances.len() > self.instances_capacity {
+            self.instances_capacity = instances.len().next_power_of_two();
+            self.instances_buffer =
+                TextRenderer::create_instances_buffer(device, self.instances_capacity);
+        }
+        let raws = instances.iter().map(|i| i.1).collect::<Vec<_>>();
+        queue.write_buffer(&self.instances_buffer, 0, bytemuck::cast_slice(&raws));
+
+        let screen_count = instances.iter().filter(|i| !i.0).count() as u32;
+        self.screen_instances = 0..screen_count;
+        self.world_instances = screen_count..instances.len() as u32;
+    }
+
+    // Returns the instances paired with their world-space flag, or None if the atlas is full.
+    fn build_instances(&mut self, queue: &Queue) -> Option<Vec<(bool, GlyphInstanceRaw)>> {
+        let mut instances = vec![];
+
+        for queued in self.queued.iter() {
+            let font = &self.fonts[queued.font.0];
+            let size = queued.style.size();
+
+            for glyph in font.layout(&queued.text, &queued.style) {
+                let key = GlyphKey {
+                    font: queued.font,
+                    id: glyph.id,
+                    size_bits: size.to_bits(),
+                };
+                let atlas_glyph = match self.atlas.get_or_insert(queue, font, key)? {
+                    Some(atlas_glyph) => atlas_glyph,
+                    None => continue,
+                };
+
+                let raw = GlyphInstanceRaw {
+                    origin_scale: [
+                        queued.origin[0],
+                        queued.origin[1],
+                        queued.origin[2],
+                        queued.units_per_pixel,
+                    ],
+                    rect: [
+                        glyph.position[0] + atlas_glyph.offset[0],
+                        glyph.position[1] + atlas_glyph.offset[1],
+                        atlas_glyph.size[0],
+                        atlas_glyph.size[1],
+                    ],
+                    uv_rect: atlas_glyph.uv_rect,
+                    color: queued.style.color(),
+                };
+                instances.push((queued.world_space, raw));
+            }
+        }
+
+        Some(instances)
+    }
+
+    /// Record the draw calls of the texts prepared for this frame.
+    ///
+    /// World-space texts need the bind group of the scene camera, without it they are skipped.
+    /// The screen-space ones are laid out on the window, or on the area of the screen camera
+    /// given.
+    pub(crate) fn draw<'a>(
+        &'a self,
+        render_pass: &mut wgpu::RenderPass<'a>,
+        camera_bind_group: Option<&'a CameraBindGroup>,
+        screen_camera_bind_group: Option<&'a CameraBindGroup>,
+        stats: &mut FrameStats,
+    ) {
+        if self.screen_instances.is_empty() && self.world_instances.is_empty() {
+            return;
+        }
+
+        render_pass.set_bind_group(0, self.atlas.bind_group_metadatas.bind_group(), &[]);
+        render_pass.set_vertex_buffer(0, self.vertex_buffer.slice(..));
+        render_pass.set_vertex_buffer(1, self.instances_buffer.slice(..));
+        render_pass.set_index_buffer(self.index_buffer.slice(..), wgpu::IndexFormat::Uint16);
+        stats.record_bind_group();
+
+        if let (false, Some(camera_bind_group)) =
+            (self.world_instances.is_empty(), camera_bind_group)
+        {
+            render_pass.set_pipeline(self.world_pipeline.expose_wrapped_render_pipeline());
+            render_pass.set_bind_group(1, camera_bind_group.bind_group(), &[]);
+            render_pass.draw_indexed(
+                0..QUAD_INDICES.len() as u32,
+                0,
+                self.world_instances.clone(),
+            );
+            stats.record_pipeline();
+            stats.record_bind_group();
+            stats.record_draw_indexed(
+                QUAD_INDICES.len() as u32,
+                self.world_instances.end - self.world_instances.start,
+                wgpu::PrimitiveTopology::TriangleList,
+            );
+        }
+
+        // Drawn last, over the world-space ones
+        if !self.screen_instances.is_empty() {
+            render_pass.set_pipeline(self.screen_pipeline.expose_wrapped_render_pipeline());
+            let screen_camera_bind_group =
+                screen_camera_bind_group.unwrap_or(&self.screen_camera_bind_group);
+            render_pass.set_bind_group(1, screen_camera_bind_group.bind_group(), &[]);
+            render_pass.draw_indexed(
+                0..QUAD_INDICES.len() as u32,
+                0,
+                self.screen_instances.clone(),
+            );
+            stats.record_pipeline();
+            stats.record_bind_group();
+            stats.record_draw_indexed(
+                QUAD_INDICES.len() as u32,
+                self.screen_instances.end - self.screen_instances.start,
+                wgpu::PrimitiveTopology::TriangleList,
+            );
+        }
+    }
+}
+
+//= GLYPH INSTANCE FOR SHADERS =====================================================================
+
+/// The per-glyph data that will go into the instances buffer.
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+pub(crate) struct GlyphInstanceRaw {
+    // World position of the text top-left corner and world units per pixel
+    origin_scale: [f32; 4],
+    // Glyph quad in pixels, relative to the text origin with the y axis pointing down
+    rect: [f32; 4],
+    uv_rect: [f32; 4],
+    color: [f32; 4],
+}
+
+impl GlyphInstanceRaw {
+    ///
+    pub(crate) fn desc<'a>() -> wgpu::VertexBufferLayout<'a> {
+        wgpu::VertexBufferLayout {
+            array_stride: mem::size_of::<GlyphInstanceRaw>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Instance,
+            // Same locations used by the 3D instances, after the vertex ones
+            attributes: &[
+                wgpu::VertexAttribute {
+                    offset: 0,
+                    shader_location: 5,
+                    format: wgpu::VertexFormat::Float32x4,
+                },
+                wgpu::VertexAttribute {
+                    offset: mem::size_of::<[f32; 4]>() as wgpu::BufferAddress,
+                    shader_location: 6,
+                    format: wgpu::VertexFormat::Float32x4,
+                },
+                wgpu::VertexAttribute {
+                    offset: mem::size_of::<[f32; 8]>() as wgpu::BufferAddress,
+                    shader_location: 7,
+                    format: wgpu::VertexFormat::Float32x4,
+                },
+                wgpu::VertexAttribute {
+                    offset: mem::size_of::<[f32; 12]>() as wgpu::BufferAddress,
+                    shader_location: 8,
+                    format: wgpu::VertexFormat::Float32x4,
+                },
+            ],
+        }
+    }
+}
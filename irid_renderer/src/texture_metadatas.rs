@@ -1,6 +1,11 @@
 //= USES ===========================================================================================
 
-use crate::device::Device;
+use std::sync::Arc;
+
+use crate::{
+    bind_group::{BindGroupBuilder, BindGroupLayoutBuilder},
+    device::Device,
+};
 
 //= TEXTURE IMAGE METADATAS ========================================================================
 
@@ -84,7 +89,7 @@ impl TextureImageMetadatas {
 ///
 #[derive(Debug)]
 pub struct TextureBindGroupMetadatas {
-    bind_group_layout: wgpu::BindGroupLayout,
+    bind_group_layout: Arc<wgpu::BindGroupLayout>,
     bind_group: wgpu::BindGroup,
 }
 
@@ -93,26 +98,17 @@ impl TextureBindGroupMetadatas {
 
     ///
     pub fn new(device: &Device, texture: &wgpu::Texture) -> Self {
-        let bind_group_layout = TextureBindGroupMetadatas::create_bind_group_layout(device);
-
-        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
-            layout: &bind_group_layout,
-            entries: &[
-                wgpu::BindGroupEntry {
-                    binding: 0,
-                    resource: wgpu::BindingResource::TextureView(
-                        &TextureBindGroupMetadatas::create_texture_view(texture),
-                    ),
-                },
-                wgpu::BindGroupEntry {
-                    binding: 1,
-                    resource: wgpu::BindingResource::Sampler(
-                        &TextureBindGroupMetadatas::create_sampler(device),
-                    ),
-                },
-            ],
-            label: Some("Diffuse Bind Group"),
-        });
+        let bind_group_layout =
+            Arc::new(TextureBindGroupMetadatas::create_bind_group_layout(device));
+
+        let view = TextureBindGroupMetadatas::create_texture_view(texture);
+        let sampler = TextureBindGroupMetadatas::create_sampler(device);
+
+        let bind_group = BindGroupBuilder::new(&bind_group_layout)
+            .with_label("Diffuse Bind Group")
+            .with_texture_view(0, &view)
+            .with_sampler(1, &sampler)
+            .build(device);
 
         Self {
             bind_group_layout,
@@ -120,28 +116,18 @@ impl TextureBindGroupMetadatas {
         }
     }
 
-    fn create_bind_group_layout(device: &Device) -> wgpu::BindGroupLayout {
-        device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
-            entries: &[
-                wgpu::BindGroupLayoutEntry {
-                    binding: 0,
-                    visibility: wgpu::ShaderStages::FRAGMENT,
-                    ty: wgpu::BindingType::Texture {
-                        multisampled: false,
-                        view_dimension: wgpu::TextureViewDimension::D2,
-                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
-                    },
-                    count: None,
-                },
-                wgpu::BindGroupLayoutEntry {
-                    binding: 1,
-                    visibility: wgpu::ShaderStages::FRAGMENT,
-                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
-                    count: None,
-                },
-            ],
-            label: Some("Texture Bind Group Layout"),
-        })
+    /// Create a layout compatible with every texture bind group, useful to build a pipeline
+    /// layout before any texture has been loaded.
+    pub(crate) fn create_bind_group_layout(device: &Device) -> wgpu::BindGroupLayout {
+        BindGroupLayoutBuilder::new()
+            .with_label("Texture Bind Group Layout")
+            .with_texture(
+                0,
+                wgpu::ShaderStages::FRAGMENT,
+                wgpu::TextureViewDimension::D2,
+            )
+            .with_sampler(1, wgpu::ShaderStages::FRAGMENT)
+            .build(device)
     }
 
     fn create_texture_view(texture: &wgpu::Texture) -> wgpu::TextureView {
@@ -167,7 +153,7 @@ impl TextureBindGroupMetadatas {
     //- Getters ------------------------------------------------------------------------------------
 
     ///
-    pub fn bind_group_layout(&self) -> &wgpu::BindGroupLayout {
+    pub fn bind_group_layout(&self) -> &Arc<wgpu::BindGroupLayout> {
         &self.bind_group_layout
     }
 
@@ -177,6 +163,160 @@ impl TextureBindGroupMetadatas {
     }
 }
 
+//= TEXTURE CUBE METADATAS =========================================================================
+
+/// Struct containing the cube texture, made of six array layers, and the values used by
+/// queue.write_cubemap()
+#[derive(Debug)]
+pub(crate) struct TextureCubeMetadatas {
+    texture: wgpu::Texture,
+    image_data_layout: wgpu::ImageDataLayout,
+    face_size: wgpu::Extent3d,
+}
+
+impl TextureCubeMetadatas {
+    //- Constructors -------------------------------------------------------------------------------
+
+    ///
+    pub(crate) fn new(
+        device: &Device,
+        face_size: u32,
+        bytes_per_texel: u32,
+        format: wgpu::TextureFormat,
+    ) -> Self {
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Cube Texture"),
+            format,
+            size: wgpu::Extent3d {
+                width: face_size,
+                height: face_size,
+                // A cube texture is a 2D texture array with one layer for each face
+                depth_or_array_layers: 6,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+        });
+
+        let image_data_layout = wgpu::ImageDataLayout {
+            offset: 0,
+            bytes_per_row: std::num::NonZeroU32::new(face_size * bytes_per_texel),
+            rows_per_image: std::num::NonZeroU32::new(face_size),
+        };
+
+        Self {
+            texture,
+            image_data_layout,
+            face_size: wgpu::Extent3d {
+                width: face_size,
+                height: face_size,
+                depth_or_array_layers: 1,
+            },
+        }
+    }
+
+    //- ImageCopyTexture ---------------------------------------------------------------------------
+
+    /// Create the copy destination of a single face.
+    pub(crate) fn create_image_copy(&self, layer: u32) -> wgpu::ImageCopyTexture<'_> {
+        wgpu::ImageCopyTexture {
+            texture: &self.texture,
+            mip_level: 0,
+            origin: wgpu::Origin3d {
+                x: 0,
+                y: 0,
+                z: layer,
+            },
+            aspect: wgpu::TextureAspect::All,
+        }
+    }
+
+    //- Getters ------------------------------------------------------------------------------------
+
+    ///
+    pub(crate) fn texture(&self) -> &wgpu::Texture {
+        &self.texture
+    }
+
+    ///
+    pub(crate) fn image_data_layout(&self) -> &wgpu::ImageDataLayout {
+        &self.image_data_layout
+    }
+
+    /// The size of a single face.
+    pub(crate) fn face_size(&self) -> &wgpu::Extent3d {
+        &self.face_size
+    }
+}
+
+//= TEXTURE CUBE BIND GROUP METADATAS ==============================================================
+
+/// Bind group exposing a cube texture and its sampler, used by the skybox and by the
+/// materials that need reflections.
+#[derive(Debug)]
+pub(crate) struct TextureCubeBindGroupMetadatas {
+    bind_group_layout: Arc<wgpu::BindGroupLayout>,
+    bind_group: wgpu::BindGroup,
+}
+
+impl TextureCubeBindGroupMetadatas {
+    //- Constructors -------------------------------------------------------------------------------
+
+    ///
+    pub(crate) fn new(device: &Device, texture: &wgpu::Texture) -> Self {
+        let bind_group_layout = BindGroupLayoutBuilder::new()
+            .with_label("Cube Texture Bind Group Layout")
+            .with_texture(
+                0,
+                wgpu::ShaderStages::FRAGMENT,
+                wgpu::TextureViewDimension::Cube,
+            )
+            .with_sampler(1, wgpu::ShaderStages::FRAGMENT)
+            .build(device);
+
+        let view = texture.create_view(&wgpu::TextureViewDescriptor {
+            label: Some("Cube Texture View"),
+            dimension: Some(wgpu::TextureViewDimension::Cube),
+            ..Default::default()
+        });
+
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("Cube Texture Sampler"),
+            address_mode_u: wgpu::AddressMode::ClampToEdge,
+            address_mode_v: wgpu::AddressMode::ClampToEdge,
+            address_mode_w: wgpu::AddressMode::ClampToEdge,
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            mipmap_filter: wgpu::FilterMode::Nearest,
+            ..Default::default()
+        });
+
+        let bind_group = BindGroupBuilder::new(&bind_group_layout)
+            .with_label("Cube Texture Bind Group")
+            .with_texture_view(0, &view)
+            .with_sampler(1, &sampler)
+            .build(device);
+
+        Self {
+            bind_group_layout: Arc::new(bind_group_layout),
+            bind_group,
+        }
+    }
+
+    //- Getters ------------------------------------------------------------------------------------
+
+    ///
+    pub(crate) fn bind_group_layout(&self) -> &Arc<wgpu::BindGroupLayout> {
+        &self.bind_group_layout
+    }
+
+    ///
+    pub(crate) fn bind_group(&self) -> &wgpu::BindGroup {
+        &self.bind_group
+    }
+}
+
 //= TEXTURE DEPTH METADATAS ========================================================================
 
 ///
@@ -188,16 +328,15 @@ pub(crate) struct TextureDepthMetadatas {
 }
 
 impl TextureDepthMetadatas {
-    //- Constants ----------------------------------------------------------------------------------
-
-    ///
-    pub(crate) const DEPTH_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Depth32Float;
-
     //- Constructors -------------------------------------------------------------------------------
 
     /// Our depth texture needs to be the same size as our screen if we want things
     /// to render correctly so we give to constructor windows_size value.
-    pub(crate) fn new(device: &Device, window_size: winit::dpi::PhysicalSize<u32>) -> Self {
+    pub(crate) fn new(
+        device: &Device,
+        window_size: winit::dpi::PhysicalSize<u32>,
+        format: wgpu::TextureFormat,
+    ) -> Self {
         let size = wgpu::Extent3d {
             width: window_size.width,
             height: window_size.height,
@@ -210,7 +349,7 @@ impl TextureDepthMetadatas {
             mip_level_count: 1,
             sample_count: 1,
             dimension: wgpu::TextureDimension::D2,
-            format: Self::DEPTH_FORMAT,
+            format,
             usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
         };
 
@@ -0,0 +1,608 @@
+//= USES ===========================================================================================
+
+use std::{
+    mem,
+    ops::{BitOr, BitOrAssign},
+};
+
+use crate::{
+    bind_group::{BindGroupBuilder, BindGroupLayoutBuilder},
+    camera::{Camera, OrthographicCamera},
+    camera_bind::CameraBindGroup,
+    depth::DepthConfig,
+    device::Device,
+    pipeline::{PipelineLayoutBuilder, RenderPipeline, RenderPipelineBuilder},
+    queue::Queue,
+    shader::{DEFAULT_FRAGMENT_ENTRY_POINT, DEFAULT_VERTEX_ENTRY_POINT, VIEWPORT_CLEAR_SHADER},
+    skybox::{Skybox, SkyboxView},
+    stats::FrameStats,
+    text::TextRenderer,
+};
+
+//= RENDER LAYERS ==================================================================================
+
+/// A mask of the kinds of objects drawn by a [Viewport].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct RenderLayers(u32);
+
+impl RenderLayers {
+    ///
+    pub const NONE: Self = Self(0);
+    /// The mesh of the renderer.
+    pub const MESH: Self = Self(1);
+    ///
+    pub const SKYBOX: Self = Self(1 << 1);
+    ///
+    pub const SPRITES: Self = Self(1 << 2);
+    ///
+    pub const PARTICLES: Self = Self(1 << 3);
+    ///
+    pub const DEBUG: Self = Self(1 << 4);
+    /// Both the screen and the world-space texts.
+    pub const TEXTS: Self = Self(1 << 5);
+    ///
+    pub const ALL: Self = Self(u32::MAX);
+
+    /// If all the layers of `other` are in this mask.
+    pub fn contains(self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    ///
+    pub fn without(self, other: Self) -> Self {
+        Self(self.0 & !other.0)
+    }
+}
+
+impl Default for RenderLayers {
+    fn default() -> Self {
+        Self::ALL
+    }
+}
+
+impl BitOr for RenderLayers {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self {
+        Self(self.0 | rhs.0)
+    }
+}
+
+impl BitOrAssign for RenderLayers {
+    fn bitor_assign(&mut self, rhs: Self) {
+        self.0 |= rhs.0;
+    }
+}
+
+//= VIEWPORT CLEAR =================================================================================
+
+/// What is cleared inside a [Viewport] before drawing it.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum ViewportClear {
+    /// The color, with the renderer clear color, and the depth.
+    #[default]
+    All,
+    /// The color, with the given one, and the depth.
+    Color(wgpu::Color),
+    /// Only the depth, to draw over the viewports below, like a picture-in-picture.
+    Depth,
+    /// Nothing, the viewport is drawn in the same space of the viewports below.
+    None,
+}
+
+//= VIEWPORT ID ====================================================================================
+
+/// Identifies a viewport added with [Renderer::add_viewport](crate::Renderer::add_viewport).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct ViewportId(usize);
+
+//= VIEWPORT =======================================================================================
+
+/// A camera drawn on a rectangle of the window, useful for split-screen multiplayer and
+/// picture-in-picture.
+///
+/// The rectangles are normalized: (0, 0) is the top-left corner of the window and (1, 1) the
+/// bottom-right one.
+#[derive(Clone, Debug)]
+pub struct Viewport<C: Camera> {
+    camera: C,
+    rect: [f32; 4],
+    scissor: Option<[f32; 4]>,
+    clear: ViewportClear,
+    layers: RenderLayers,
+}
+
+impl<C: Camera> Viewport<C> {
+    //- Constructors -------------------------------------------------------------------------------
+
+    /// A viewport covering the whole window, drawing all the layers.
+    pub fn new(camera: C) -> Self {
+        Self {
+            camera,
+            rect: [0.0, 0.0, 1.0, 1.0],
+            scissor: None,
+            clear: ViewportClear::default(),
+            layers: RenderLayers::default(),
+        }
+    }
+
+    //- Setters ------------------------------------------------------------------------------------
+
+    /// The normalized rectangle the camera is drawn on, its aspect ratio should match the
+    /// camera one.
+    #[inline]
+    pub fn with_rect(mut self, x: f32, y: f32, width: f32, height: f32) -> Self {
+        self.rect = [x, y, width, height];
+        self
+    }
+
+    /// A normalized rectangle that cuts the drawing, the viewport rect if None.
+    #[inline]
+    pub fn with_scissor<S: Into<Option<[f32; 4]>>>(mut self, scissor: S) -> Self {
+        self.scissor = scissor.into();
+        self
+    }
+
+    ///
+    #[inline]
+    pub fn with_clear(mut self, clear: ViewportClear) -> Self {
+        self.clear = clear;
+        self
+    }
+
+    ///
+    #[inline]
+    pub fn with_layers(mut self, layers: RenderLayers) -> Self {
+        self.layers = layers;
+        self
+    }
+
+    ///
+    pub fn set_rect(&mut self, x: f32, y: f32, width: f32, height: f32) {
+        self.rect = [x, y, width, height];
+    }
+
+    ///
+    pub fn set_layers(&mut self, layers: RenderLayers) {
+        self.layers = layers;
+    }
+
+    //- Getters ------------------------------------------------------------------------------------
+
+    ///
+    pub fn camera(&self) -> &C {
+        &self.camera
+    }
+
+    /// The camera, mutable to move it between the frames.
+    pub fn camera_mut(&mut self) -> &mut C {
+        &mut self.camera
+    }
+
+    /// The normalized rectangle as x, y, width and height.
+    pub fn rect(&self) -> [f32; 4] {
+        self.rect
+    }
+
+    ///
+    pub fn clear(&self) -> ViewportClear {
+        self.clear
+    }
+
+    ///
+    pub fn layers(&self) -> RenderLayers {
+        self.layers
+    }
+
+    /// The aspect ratio of the viewport on a window of the given size.
+    pub fn calc_aspect_ratio(&self, window_size: winit::dpi::PhysicalSize<u32>) -> f32 {
+        (self.rect[2] * window_size.width as f32) / (self.rect[3] * window_size.height as f32)
+    }
+
+    //- Conversions --------------------------------------------------------------------------------
+
+    /// The viewport rect in pixels, None if it is empty once clipped to the window.
+    pub(crate) fn pixel_rect(
+        &self,
+        window_size: winit::dpi::PhysicalSize<u32>,
+    ) -> Option<[f32; 4]> {
+        let width = window_size.width as f32;
+        let height = window_size.height as f32;
+        let x = (self.rect[0] * width).clamp(0.0, width);
+        let y = (self.rect[1] * height).clamp(0.0, height);
+        let w = (self.rect[2] * width).min(width - x);
+        let h = (self.rect[3] * height).min(height - y);
+        if w < 1.0 || h < 1.0 {
+            return None;
+        }
+        Some([x, y, w, h])
+    }
+
+    /// The scissor rect in pixels, None if it is empty once clipped to the window.
+    pub(crate) fn pixel_scissor(
+        &self,
+        window_size: winit::dpi::PhysicalSize<u32>,
+    ) -> Option<[u32; 4]> {
+        let rect = self.scissor.unwrap_or(self.rect);
+        let to_pixels =
+            |value: f32, size: u32| ((value * size as f32).round().max(0.0) as u32).min(size);
+        let x = to_pixels(rect[0], window_size.width);
+        let y = to_pixels(rect[1], window_size.height);
+        let w = to_pixels(rect[2], window_size.width).min(window_size.width - x);
+        let h = to_pixels(rect[3], window_size.height).min(window_size.height - y);
+        if w == 0 || h == 0 {
+            return None;
+        }
+        Some([x, y, w, h])
+    }
+}
+
+//= VIEWPORTS ======================================================================================
+
+// A viewport with the uniforms of its cameras and of its clear color.
+#[derive(Debug)]
+pub(crate) struct ViewportSlot<C: Camera> {
+    viewport: Viewport<C>,
+    camera_bind_group: CameraBindGroup,
+    // The sprite and the text screen cameras, sized to the viewport
+    sprite_camera_bind_group: CameraBindGroup,
+    screen_camera_bind_group: CameraBindGroup,
+    skybox_view: Option<SkyboxView>,
+    clear_buffer: wgpu::Buffer,
+    clear_bind_group: wgpu::BindGroup,
+}
+
+impl<C: Camera> ViewportSlot<C> {
+    ///
+    pub(crate) fn viewport(&self) -> &Viewport<C> {
+        &self.viewport
+    }
+
+    ///
+    pub(crate) fn viewport_mut(&mut self) -> &mut Viewport<C> {
+        &mut self.viewport
+    }
+
+    ///
+    pub(crate) fn into_viewport(self) -> Viewport<C> {
+        self.viewport
+    }
+
+    ///
+    pub(crate) fn camera_bind_group(&self) -> &CameraBindGroup {
+        &self.camera_bind_group
+    }
+
+    /// The sprite camera with the size of the viewport in pixels.
+    pub(crate) fn sprite_camera_bind_group(&self) -> &CameraBindGroup {
+        &self.sprite_camera_bind_group
+    }
+
+    /// The camera of the screen-space texts, with the origin on the top-left corner of the
+    /// viewport.
+    pub(crate) fn screen_camera_bind_group(&self) -> &CameraBindGroup {
+        &self.screen_camera_bind_group
+    }
+
+    ///
+    pub(crate) fn skybox_view(&self) -> Option<&SkyboxView> {
+        self.skybox_view.as_ref()
+    }
+
+    /// Record the clear of the viewport with a pipeline given by [Viewports::clear_pipeline] or
+    /// [Viewports::prepass_clear_pipeline].
+    pub(crate) fn draw_clear<'a>(
+        &'a self,
+        render_pass: &mut wgpu::RenderPass<'a>,
+        pipeline: &'a RenderPipeline,
+        stats: &mut FrameStats,
+    ) {
+        render_pass.set_pipeline(pipeline.expose_wrapped_render_pipeline());
+        render_pass.set_bind_group(0, &self.clear_bind_group, &[]);
+        render_pass.draw(0..3, 0..1);
+        stats.record_pipeline();
+        stats.record_bind_group();
+        stats.record_draw(3, 1, wgpu::PrimitiveTopology::TriangleList);
+    }
+}
+
+/// The viewports drawn in order by the renderer, and the pipelines clearing them.
+#[derive(Debug)]
+pub(crate) struct Viewports<C: Camera> {
+    slots: Vec<Option<ViewportSlot<C>>>,
+    far_depth: f32,
+    reverse_z: bool,
+    clear_bgl: wgpu::BindGroupLayout,
+    clear_color_and_depth: RenderPipeline,
+    clear_color: RenderPipeline,
+    clear_depth: RenderPipeline,
+    clear_prepass_depth: RenderPipeline,
+}
+
+impl<C: Camera> Viewports<C> {
+    //- Constructors -------------------------------------------------------------------------------
+
+    ///
+    pub(crate) fn new(
+        device: &Device,
+        color_format: wgpu::TextureFormat,
+        depth: &DepthConfig,
+    ) -> Self {
+        let shader_module = device.create_shader_module(&wgpu::ShaderModuleDescriptor {
+            label: Some("Viewport Clear Shader"),
+            source: wgpu::ShaderSource::Wgsl(std::borrow::Cow::Borrowed(VIEWPORT_CLEAR_SHADER)),
+        });
+
+        let clear_bgl = BindGroupLayoutBuilder::new()
+            .with_label("Viewport Clear Bind Group Layout")
+            .with_uniform(0, wgpu::ShaderStages::VERTEX | wgpu::ShaderStages::FRAGMENT)
+            .build(device);
+        let pipeline_layout = PipelineLayoutBuilder::new()
+            .with_label("Viewport Clear Pipeline Layout")
+            .with_bind_group_layouts(&[&clear_bgl])
+            .build(device);
+
+        let vertex_state = wgpu::VertexState {
+            module: &shader_module,
+            entry_point: DEFAULT_VERTEX_ENTRY_POINT,
+            buffers: &[],
+        };
+
+        // The depth test always passes, the writes are chosen by each pipeline
+        let create_pipeline = |label, write_mask, depth_write_enabled, has_color_target: bool| {
+            let color_targets = [wgpu::ColorTargetState {
+                format: color_format,
+                blend: Some(wgpu::BlendState::REPLACE),
+                write_mask,
+            }];
+            let mut builder = RenderPipelineBuilder::new(vertex_state.clone())
+                .with_label(label)
+                .with_layout(&pipeline_layout)
+                .with_depth_stencil(
+                    depth.depth_stencil_state(depth_write_enabled, wgpu::CompareFunction::Always),
+                );
+            if has_color_target {
+                builder = builder.with_fragment(wgpu::FragmentState {
+                    module: &shader_module,
+                    entry_point: DEFAULT_FRAGMENT_ENTRY_POINT,
+                    targets: &color_targets,
+                });
+            }
+            builder.build(device)
+        };
+
+        Self {
+            slots: vec![],
+            far_depth: depth.clear_depth(),
+            reverse_z: depth.is_reverse_z(),
+            clear_color_and_depth: create_pipeline(
+                "Viewport Clear Pipeline",
+                wgpu::ColorWrites::ALL,
+                true,
+                true,
+            ),
+            clear_color: create_pipeline(
+                "Viewport Clear Color Pipeline",
+                wgpu::ColorWrites::ALL,
+                false,
+                true,
+            ),
+            clear_depth: create_pipeline(
+                "Viewport Clear Depth Pipeline",
+                wgpu::ColorWrites::empty(),
+                true,
+                true,
+            ),
+            clear_prepass_depth: create_pipeline(
+                "Viewport Clear Prepass Pipeline",
+                wgpu::ColorWrites::empty(),
+                true,
+                false,
+            ),
+            clear_bgl,
+        }
+    }
+
+    //- Viewports ----------------------------------------------------------------------------------
+
+    /// Add a viewport drawn after the ones already added.
+    pub(crate) fn add(
+        &mut self,
+        device: &Device,
+        skybox: Option<&Skybox>,
+        viewport: Viewport<C>,
+    ) -> ViewportId {
+        let slot = self.create_slot(device, skybox, viewport);
+        match self.slots.iter().position(Option::is_none) {
+            Some(index) => {
+                self.slots[index] = Some(slot);
+                ViewportId(index)
+            }
+            None => {
+                self.slots.push(Some(slot));
+                ViewportId(self.slots.len() - 1)
+            }
+        }
+    }
+
+    /// Add again the viewports of a renderer whose device has been lost, with the same ids.
+    pub(crate) fn restore(
+        &mut self,
+        device: &Device,
+        skybox: Option<&Skybox>,
+        lost: &mut Viewports<C>,
+    ) {
+        self.slots = mem::take(&mut lost.slots)
+            .into_iter()
+            .map(|slot| slot.map(|slot| self.create_slot(device, skybox, slot.into_viewport())))
+            .collect();
+    }
+
+    /// The uniforms of a viewport, also used for the viewports drawn on other windows.
+    pub(crate) fn create_slot(
+        &self,
+        device: &Device,
+        skybox: Option<&Skybox>,
+        mut viewport: Viewport<C>,
+    ) -> ViewportSlot<C> {
+        viewport.camera.set_reverse_z(self.reverse_z);
+        let camera_bind_group = CameraBindGroup::new(viewport.camera(), device);
+        // Written with the size of the viewport once it is prepared
+        let sprite_camera_bind_group =
+            CameraBindGroup::new(&OrthographicCamera::new(1.0, 1.0), device);
+        let screen_camera_bind_group =
+            CameraBindGroup::new(&OrthographicCamera::new(1.0, 1.0), device);
+        let skybox_view = skybox.map(|skybox| skybox.create_view(device, viewport.camera()));
+
+        let clear_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Viewport Clear Buffer"),
+            size: std::mem::size_of::<ClearUniform>() as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let clear_bind_group = BindGroupBuilder::new(&self.clear_bgl)
+            .with_label("Viewport Clear Bind Group")
+            .with_buffer(0, &clear_buffer)
+            .build(device);
+
+        ViewportSlot {
+            viewport,
+            camera_bind_group,
+            sprite_camera_bind_group,
+            screen_camera_bind_group,
+            skybox_view,
+            clear_buffer,
+            clear_bind_group,
+        }
+    }
+
+    ///
+    pub(crate) fn remove(&mut self, id: ViewportId) -> Option<Viewport<C>> {
+        self.slots
+            .get_mut(id.0)
+            .and_then(Option::take)
+            .map(|slot| slot.viewport)
+    }
+
+    ///
+    pub(crate) fn get(&self, id: ViewportId) -> Option<&Viewport<C>> {
+        self.slots
+            .get(id.0)
+            .and_then(Option::as_ref)
+            .map(|slot| &slot.viewport)
+    }
+
+    ///
+    pub(crate) fn get_mut(&mut self, id: ViewportId) -> Option<&mut Viewport<C>> {
+        self.slots
+            .get_mut(id.0)
+            .and_then(Option::as_mut)
+            .map(|slot| &mut slot.viewport)
+    }
+
+    ///
+    pub(crate) fn is_empty(&self) -> bool {
+        self.slots.iter().all(Option::is_none)
+    }
+
+    /// The viewports in the order they are drawn.
+    pub(crate) fn iter(&self) -> impl Iterator<Item = &ViewportSlot<C>> {
+        self.slots.iter().flatten()
+    }
+
+    //- Rendering ----------------------------------------------------------------------------------
+
+    /// Upload the cameras and the clear colors of the viewports drawn on a target of the given
+    /// size, must be called before they are drawn.
+    ///
+    /// The sprites are seen by the sprite camera, resized to every viewport.
+    pub(crate) fn prepare(
+        &mut self,
+        queue: &Queue,
+        target_size: winit::dpi::PhysicalSize<u32>,
+        sprite_camera: &OrthographicCamera,
+        clear_color: wgpu::Color,
+    ) {
+        let mut slots = mem::take(&mut self.slots);
+        for slot in slots.iter_mut().flatten() {
+            self.prepare_slot(queue, slot, target_size, sprite_camera, clear_color);
+        }
+        self.slots = slots;
+    }
+
+    /// Upload the cameras and the clear color of a single viewport, see [Viewports::prepare].
+    ///
+    /// The reverse-Z of the camera follows the depth config again, in case the camera has been
+    /// replaced through [Viewport::camera_mut].
+    pub(crate) fn prepare_slot(
+        &self,
+        queue: &Queue,
+        slot: &mut ViewportSlot<C>,
+        target_size: winit::dpi::PhysicalSize<u32>,
+        sprite_camera: &OrthographicCamera,
+        clear_color: wgpu::Color,
+    ) {
+        slot.viewport.camera.set_reverse_z(self.reverse_z);
+        queue.write_camera_buffer(slot.viewport.camera(), &slot.camera_bind_group);
+        if let Some(skybox_view) = slot.skybox_view.as_ref() {
+            queue.write_skybox_buffer(slot.viewport.camera(), skybox_view);
+        }
+
+        // One world unit stays one pixel, whatever the size of the viewport
+        if let Some(rect) = slot.viewport.pixel_rect(target_size) {
+            let mut sprite_camera = sprite_camera.clone();
+            sprite_camera.resize(rect[2], rect[3]);
+            queue.write_camera_buffer(&sprite_camera, &slot.sprite_camera_bind_group);
+            let screen_camera = TextRenderer::create_screen_camera(rect[2], rect[3]);
+            queue.write_camera_buffer(&screen_camera, &slot.screen_camera_bind_group);
+        }
+
+        let color = match slot.viewport.clear() {
+            ViewportClear::Color(color) => color,
+            _ => clear_color,
+        };
+        let uniform = ClearUniform {
+            color: [
+                color.r as f32,
+                color.g as f32,
+                color.b as f32,
+                color.a as f32,
+            ],
+            depth: [self.far_depth, 0.0, 0.0, 0.0],
+        };
+        queue.write_buffer(&slot.clear_buffer, 0, bytemuck::cast_slice(&[uniform]));
+    }
+
+    /// The pipeline clearing a viewport in the color pass, after a depth prepass the depth
+    /// has already been cleared.
+    pub(crate) fn clear_pipeline(
+        &self,
+        clear: ViewportClear,
+        after_prepass: bool,
+    ) -> Option<&RenderPipeline> {
+        match (clear, after_prepass) {
+            (ViewportClear::None, _) | (ViewportClear::Depth, true) => None,
+            (ViewportClear::Depth, false) => Some(&self.clear_depth),
+            (_, true) => Some(&self.clear_color),
+            (_, false) => Some(&self.clear_color_and_depth),
+        }
+    }
+
+    /// The pipeline clearing the depth of a viewport in the depth prepass.
+    pub(crate) fn prepass_clear_pipeline(&self, clear: ViewportClear) -> Option<&RenderPipeline> {
+        match clear {
+            ViewportClear::None => None,
+            _ => Some(&self.clear_prepass_depth),
+        }
+    }
+}
+
+//= CLEAR UNIFORM BUFFER ===========================================================================
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
+struct ClearUniform {
+    color: [f32; 4],
+    // The x component is the depth of the far plane
+    depth: [f32; 4],
+}
@@ -0,0 +1,117 @@
+//= USES ===========================================================================================
+
+use crate::{
+    camera::Camera,
+    depth::DepthConfig,
+    device::Device,
+    skybox::Skybox,
+    surface::Surface,
+    texture_metadatas::TextureDepthMetadatas,
+    viewport::{ViewportSlot, Viewports},
+};
+
+//= WINDOW SURFACE =================================================================================
+
+/// An additional window drawn by the renderer, with its own surface and depth buffer but
+/// sharing the device, the queue and the pipelines of the main window.
+#[derive(Debug)]
+pub(crate) struct WindowSurface<C: Camera> {
+    id: winit::window::WindowId,
+    surface: Surface,
+    size: winit::dpi::PhysicalSize<u32>,
+    depth_metadatas: TextureDepthMetadatas,
+    slot: ViewportSlot<C>,
+}
+
+impl<C: Camera> WindowSurface<C> {
+    //- Constructors -------------------------------------------------------------------------------
+
+    /// The surface is configured here, the window must outlive it.
+    pub(crate) fn new(
+        window: &winit::window::Window,
+        surface: Surface,
+        device: &Device,
+        depth: &DepthConfig,
+        slot: ViewportSlot<C>,
+    ) -> Self {
+        let size = window.inner_size();
+        surface.configure(device);
+        Self {
+            id: window.id(),
+            surface,
+            size,
+            depth_metadatas: TextureDepthMetadatas::new(device, size, depth.format()),
+            slot,
+        }
+    }
+
+    /// Configure again the surface, with the depth buffer and the viewport uniforms, on the
+    /// device that replaced a lost one.
+    pub(crate) fn recreate(
+        mut self,
+        device: &Device,
+        depth: &DepthConfig,
+        viewports: &Viewports<C>,
+        skybox: Option<&Skybox>,
+    ) -> Self {
+        self.surface.update(device, self.size);
+        Self {
+            depth_metadatas: TextureDepthMetadatas::new(device, self.size, depth.format()),
+            slot: viewports.create_slot(device, skybox, self.slot.into_viewport()),
+            ..self
+        }
+    }
+
+    //- Getters ------------------------------------------------------------------------------------
+
+    ///
+    pub(crate) fn id(&self) -> winit::window::WindowId {
+        self.id
+    }
+
+    ///
+    pub(crate) fn size(&self) -> winit::dpi::PhysicalSize<u32> {
+        self.size
+    }
+
+    ///
+    pub(crate) fn surface(&self) -> &Surface {
+        &self.surface
+    }
+
+    ///
+    pub(crate) fn depth_view(&self) -> &wgpu::TextureView {
+        self.depth_metadatas.view()
+    }
+
+    ///
+    pub(crate) fn slot(&self) -> &ViewportSlot<C> {
+        &self.slot
+    }
+
+    ///
+    pub(crate) fn slot_mut(&mut self) -> &mut ViewportSlot<C> {
+        &mut self.slot
+    }
+
+    ///
+    pub(crate) fn into_slot(self) -> ViewportSlot<C> {
+        self.slot
+    }
+
+    //- Resize -------------------------------------------------------------------------------------
+
+    /// Reconfigure the surface and recreate the depth buffer, zero sizes are ignored.
+    pub(crate) fn resize(
+        &mut self,
+        device: &Device,
+        depth: &DepthConfig,
+        new_size: winit::dpi::PhysicalSize<u32>,
+    ) {
+        if new_size.width > 0 && new_size.height > 0 {
+            self.size = new_size;
+            self.surface.update(device, new_size);
+            self.depth_metadatas = TextureDepthMetadatas::new(device, new_size, depth.format());
+        }
+    }
+}